@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use ratatui::crossterm::event::KeyCode;
+
+use crate::keys::{Action, Keymap};
+
+/// How long an action stays highlighted in the bar after its key is pressed.
+pub const PRESS_FLASH: Duration = Duration::from_millis(300);
+
+/// Something the results screen offers, listed in its action bar.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ResultAction {
+    Restart,
+    /// Types the mistyped words again until each is typed cleanly.
+    Mistakes,
+    Settings,
+    Plan,
+    Layouts,
+    History,
+    /// Totals and averages of every saved run.
+    Stats,
+    Rivals,
+    /// Sorts the words table by one of its columns.
+    Sort,
+    /// Writes the run to a JSON or CSV file.
+    Export,
+    /// Plays the run back as it was typed.
+    Replay,
+    /// Back to the LAN race lobby for the next race.
+    Lobby,
+    Menu,
+    Quit,
+}
+
+impl ResultAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResultAction::Restart => "Restart",
+            ResultAction::Mistakes => "Train mistakes",
+            ResultAction::Settings => "Settings",
+            ResultAction::Plan => "Plan",
+            ResultAction::Layouts => "Layouts",
+            ResultAction::History => "History",
+            ResultAction::Stats => "Stats",
+            ResultAction::Rivals => "Rivals",
+            ResultAction::Sort => "Sort",
+            ResultAction::Export => "Export",
+            ResultAction::Replay => "Replay",
+            ResultAction::Lobby => "Lobby",
+            ResultAction::Menu => "Menu",
+            ResultAction::Quit => "Exit",
+        }
+    }
+
+    /// The rebindable command it runs, if any.
+    fn action(self) -> Option<Action> {
+        match self {
+            ResultAction::Restart => Some(Action::Restart),
+            ResultAction::Settings => Some(Action::Settings),
+            ResultAction::Plan => Some(Action::Plan),
+            ResultAction::Layouts => Some(Action::Layouts),
+            ResultAction::Menu => Some(Action::Menu),
+            ResultAction::Stats => Some(Action::Stats),
+            ResultAction::Quit => Some(Action::Quit),
+            ResultAction::History
+            | ResultAction::Rivals
+            | ResultAction::Sort
+            | ResultAction::Export
+            | ResultAction::Replay
+            | ResultAction::Lobby
+            | ResultAction::Mistakes => None,
+        }
+    }
+
+    /// Key shown in the bar, following the keymap.
+    pub fn key(self, keys: &Keymap) -> String {
+        match (self, self.action()) {
+            (_, Some(action)) => keys.label(action),
+            (ResultAction::History, _) => "H".to_string(),
+            (ResultAction::Rivals, _) => "V".to_string(),
+            (ResultAction::Export, _) => "E".to_string(),
+            (ResultAction::Replay, _) => "Y".to_string(),
+            (ResultAction::Lobby, _) => "L".to_string(),
+            (ResultAction::Mistakes, _) => "T".to_string(),
+            _ => "1-5".to_string(),
+        }
+    }
+
+    /// Whether `code`, bound to `action` in the keymap, runs it. Fixed keys
+    /// give way to rebound actions.
+    pub fn matches(self, code: KeyCode, action: Option<Action>) -> bool {
+        match (self, self.action()) {
+            (_, Some(own)) => action == Some(own),
+            (ResultAction::History, _) => code == KeyCode::Char('h') && action.is_none(),
+            (ResultAction::Rivals, _) => code == KeyCode::Char('v') && action.is_none(),
+            (ResultAction::Export, _) => code == KeyCode::Char('e') && action.is_none(),
+            (ResultAction::Replay, _) => code == KeyCode::Char('y') && action.is_none(),
+            (ResultAction::Lobby, _) => code == KeyCode::Char('l') && action.is_none(),
+            (ResultAction::Mistakes, _) => code == KeyCode::Char('t') && action.is_none(),
+            _ => matches!(code, KeyCode::Char('1'..='5')),
+        }
+    }
+}
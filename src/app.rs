@@ -0,0 +1,2326 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    io::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use htils::ternary;
+use rand::{Rng, distributions::Alphanumeric, seq::SliceRandom, thread_rng};
+use random_word::Lang;
+use ratatui::{
+    crossterm::event::KeyCode,
+    layout::{Position, Rect},
+    style::Style,
+    widgets::{ListState, TableState},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    actions::ResultAction,
+    calendar::{self, Calendar, Streak},
+    clipboard,
+    clock::TestClock,
+    config::{
+        Config, ConfigManager, Cue, PauseWord, TestMode, data_dir, default_accuracy_thresholds,
+        default_line_words, default_nickname, default_time_limit, default_volume, get_lang,
+        lang_code,
+    },
+    corpus::{self, Pair},
+    dashboard::Dashboard,
+    daytime::{self, DayStats},
+    devtools::DevStats,
+    drills::{self, Regression, Speeds},
+    export::{self, ExportFormat},
+    gate::Gate,
+    ghosts::{Ghost, Ghosts, Stroke},
+    governor::Governor,
+    graphemes::{self, Verdict},
+    history::{self, Timing},
+    journal::{self, JournalEvent, Recovered},
+    keys::Keymap,
+    keystats::{KeyStats, WEAK_KEYS},
+    lan::Lobby,
+    metrics::{Metrics, WordSample},
+    modifiers::Modifiers,
+    net::NetClient,
+    ngrams::{self, GramAccuracy, NgramSet},
+    packs,
+    plan::{Session, Step, StepResult},
+    quiet::QuietHours,
+    quotes::{self, Quote, QuoteLength},
+    race::{self, Bot, PLAYER, Race, RaceEvent},
+    records::{self, NewBest, Records},
+    replay::{Frame, Replay},
+    rivals::Rivals,
+    score::{Formula, Leaderboard},
+    settings::{Draft, Editor, PAGES, SettingItem},
+    snippets::{self, CodeLang, Snippet},
+    sound::{Player, Sound},
+    stats::SortColumn,
+    telemetry::Usage,
+    theme::{Theme, ThemeConfig},
+    tour::{self, Step as TourStep, TOUR_WORDS, Tour},
+    trend::{self, TrendAlert},
+    words::{self, Joiners, WordGen, WordMode},
+    worker::{IoWorker, Job},
+    zen::Zen,
+};
+
+pub struct App {
+    pub(crate) exit: bool,
+    pub(crate) app_state: AppState,
+    pub(crate) current_word: String,
+    pub(crate) input: String,
+    /// Test time, started by the first keystroke.
+    pub(crate) clock: TestClock,
+    pub(crate) wrong_input_chars: HashSet<usize>,
+    pub(crate) words_limit: usize,
+    pub(crate) mode: TestMode,
+    pub(crate) quote_length: QuoteLength,
+    /// Quote being typed in quote mode.
+    pub(crate) quote: Option<&'static Quote>,
+    pub(crate) code_lang: CodeLang,
+    /// Snippet being typed in code mode, a line per word.
+    pub(crate) snippet: Option<&'static Snippet>,
+    /// N-gram drill picked from the practice menu, typed until the menu is
+    /// reopened.
+    pub(crate) ngrams: Option<&'static NgramSet>,
+    /// Pairs typed in parallel mode instead of the bundled ones.
+    pub(crate) corpus: Option<&'static [Pair]>,
+    /// Pairs of the test in parallel mode, each with the index of its first word.
+    pub(crate) pairs: Vec<(usize, &'static Pair)>,
+    pub(crate) time_limit: Duration,
+    pub(crate) lang: Lang,
+    pub(crate) words: Vec<Word>,
+    pub(crate) wrong_words: HashSet<usize>,
+    pub(crate) settings_changed: bool,
+    pub(crate) selected_setting: SettingItem,
+    pub(crate) editor: Editor,
+    pub(crate) settings_search: Option<String>,
+    /// First settings row shown when they don't all fit.
+    pub(crate) settings_offset: usize,
+    /// Where the settings rows were last drawn, for mouse clicks.
+    pub(crate) settings_area: Rect,
+    pub(crate) results_sort: (SortColumn, bool),
+    pub(crate) race: Race,
+    pub(crate) bots: Vec<Bot>,
+    pub(crate) net: Option<NetClient>,
+    pub(crate) nickname: String,
+    pub(crate) peers: HashMap<String, usize>,
+    /// Head-to-head records against networked opponents.
+    pub(crate) rivals: Rivals,
+    pub(crate) records: Records,
+    /// Whether tests race the ghost of the fastest run of their kind.
+    pub(crate) ghost_race: bool,
+    pub(crate) ghosts: Ghosts,
+    /// Ghost raced in the current test.
+    pub(crate) ghost: Option<Ghost>,
+    /// Index of the ghost in `race`, joined with the first ghost raced.
+    pub(crate) ghost_racer: Option<usize>,
+    /// Keystrokes of the current test, kept as its ghost when it is the fastest.
+    pub(crate) strokes: Vec<Stroke>,
+    /// The input after each keystroke of the current test, for its replay.
+    pub(crate) frames: Vec<Frame>,
+    /// Modes used, for opt-in telemetry.
+    pub(crate) usage: Usage,
+    /// Personal bests the last run beat.
+    pub(crate) new_best: Option<NewBest>,
+    pub(crate) published: Option<Instant>,
+    pub(crate) plan: Vec<Step>,
+    pub(crate) session: Option<Session>,
+    pub(crate) word_gen: WordGen,
+    pub(crate) cue: Cue,
+    /// Key clicks and error tones, see `sound::Player`.
+    pub(crate) sound: bool,
+    /// Sound volume, 0-100.
+    pub(crate) volume: u32,
+    pub(crate) player: Player,
+    /// Last completed word, whether it was typed cleanly, shown as a border pulse.
+    pub(crate) pulse: Option<(Instant, bool)>,
+    pub(crate) show_cohort: bool,
+    /// Local time window in which sounds and notifications stay off.
+    pub(crate) quiet_hours: Option<QuietHours>,
+    pub(crate) io: IoWorker,
+    pub(crate) governor: Option<Governor>,
+    pub(crate) key_stats: KeyStats,
+    /// Formula of the headline score, if configured.
+    pub(crate) score: Option<Formula>,
+    pub(crate) leaderboard: Leaderboard,
+    /// Score and leaderboard place of the last saved run.
+    pub(crate) scored: Option<(f32, Option<usize>)>,
+    /// Installed word pack in use, if any.
+    pub(crate) pack: Option<String>,
+    /// Runs shorter than this are shown but neither saved nor counted.
+    pub(crate) min_words: usize,
+    pub(crate) min_seconds: f32,
+    /// Why the last result was not saved, if it wasn't.
+    pub(crate) unsaved: Option<String>,
+    /// Last saved runs shown on the start screen, oldest first.
+    pub(crate) recent: Vec<history::Entry>,
+    /// Keystroke intervals of the current test.
+    pub(crate) speeds: Speeds,
+    /// Letters and bigrams that got slower lately, offered as drills on the menu.
+    pub(crate) regressions: Vec<Regression>,
+    /// Every saved run, newest first, loaded when the history screen opens.
+    pub(crate) history: Vec<history::Entry>,
+    /// Page of the stats screen, an index into [`STATS_PAGES`].
+    pub(crate) stats_page: usize,
+    /// Totals of saved runs, loaded when the stats screen opens.
+    pub(crate) dashboard: Dashboard,
+    /// Saved runs by time of day, loaded when the stats screen opens.
+    pub(crate) day_stats: Vec<DayStats>,
+    pub(crate) calendar: Calendar,
+    /// Days in a row with a saved test, kept up to date as runs are saved.
+    pub(crate) streak: Streak,
+    /// Falling accuracy over the last runs, shown in Stats.
+    pub(crate) trend: Option<TrendAlert>,
+    pub(crate) keys: Keymap,
+    pub(crate) theme: Theme,
+    /// The `[theme]` section `theme` was resolved from.
+    pub(crate) theme_config: ThemeConfig,
+    /// File typed instead of the dictionary.
+    pub(crate) wordlist: Option<PathBuf>,
+    pub(crate) config_file: ConfigManager,
+    /// Action bar entry whose key was pressed last, and when.
+    pub(crate) pressed: Option<(ResultAction, Instant)>,
+    /// Settings is waiting for the key to bind to the selected action.
+    pub(crate) recording: bool,
+    /// Why the last key binding was refused.
+    pub(crate) settings_notice: Option<String>,
+    pub(crate) low_bandwidth: bool,
+    pub(crate) dev: DevStats,
+    pub(crate) forgive_rollover: bool,
+    /// Rollover typos forgiven in the current word.
+    pub(crate) forgiven: usize,
+    /// Letters typed in a row that only missed by their case.
+    pub(crate) case_slips: usize,
+    /// Whether the terminal reported Caps Lock on with the last key, which
+    /// only some terminals do.
+    pub(crate) caps_lock: bool,
+    pub(crate) last_key: Option<Instant>,
+    pub(crate) tour: Option<Tour>,
+    /// Languages alternated during a test, switching every `cycle_every` words.
+    pub(crate) cycle: Vec<Lang>,
+    pub(crate) cycle_every: usize,
+    /// Language of `current_word`.
+    pub(crate) current_lang: Lang,
+    /// Words of the test fixed in advance, shown on the preview screen.
+    pub(crate) target: VecDeque<String>,
+    /// Language of each word drawn ahead, by its position in the test.
+    pub(crate) queued_langs: HashMap<usize, Lang>,
+    pub(crate) metrics: Metrics,
+    pub(crate) joiners: Joiners,
+    pub(crate) modifiers: Modifiers,
+    /// Unix time in seconds at which the last test finished.
+    pub(crate) finished_at: u64,
+    /// Where the results screen exports runs to, the data directory's
+    /// `exports` when unset.
+    pub(crate) export_dir: Option<PathBuf>,
+    pub(crate) export_format: ExportFormat,
+    /// Path the finished run was exported to, or why it couldn't be.
+    pub(crate) exported: Option<Result<PathBuf, String>>,
+    /// Phrase picking the words of every test, from `seed` or `--seed`.
+    pub(crate) seed: Option<String>,
+    /// LAN race hosted or joined, from `--host` or `--join`.
+    pub(crate) lobby: Option<Lobby>,
+    /// Characters typed in the current test, mistakes and forgiven ones included.
+    pub(crate) keystrokes: usize,
+    /// Correct characters typed by the end of each second of the test.
+    pub(crate) timeline: Vec<usize>,
+    /// Mistakes in the current test by the key that should have been pressed.
+    pub(crate) key_errors: BTreeMap<char, u32>,
+    /// Words shown a line at a time and submitted with space.
+    pub(crate) sentence_mode: bool,
+    pub(crate) line_words: usize,
+    /// Index in `words` of the first word on the current line.
+    pub(crate) line_start: usize,
+    /// The timer only runs while a word is in progress.
+    pub(crate) boundary_pause: bool,
+    /// Live accuracy at or above the first value is shown as good, at or
+    /// above the second as a warning, and below it as bad.
+    pub(crate) accuracy_thresholds: [f32; 2],
+    /// Live stats go in a sidebar on wide terminals.
+    pub(crate) sidebar: bool,
+    /// Target WPM of the pace caret, 0 when it's off.
+    pub(crate) pace_wpm: u32,
+    /// Characters shown from the caret on in shadow typing, 0 when it's off.
+    pub(crate) reveal: u32,
+    /// Next words are shown under the current one.
+    pub(crate) upcoming: bool,
+    /// Wrong keys are refused instead of typed.
+    pub(crate) strict: bool,
+    pub(crate) pause_word: PauseWord,
+    /// Focus gate target when launched with `--gate`.
+    pub(crate) gate: Option<Gate>,
+    /// Clipboard text or mistyped words typed instead of generated words until
+    /// the menu is reopened.
+    pub(crate) scratch: Option<Scratch>,
+    /// Why the last menu action failed.
+    pub(crate) menu_notice: Option<String>,
+}
+
+/// Temporary word list that is typed but never saved.
+pub(crate) struct Scratch {
+    pub(crate) words: Vec<String>,
+    /// Words limit to restore once the scratch list is dropped.
+    pub(crate) limit: usize,
+    /// Mistyped words of a run, each queued again until it's typed cleanly.
+    pub(crate) mistakes: bool,
+}
+
+pub(crate) const PULSE_DURATION: Duration = Duration::from_millis(200);
+/// Longest gap between a wrong key and the correct one for it to count as a
+/// rollover typo.
+pub(crate) const ROLLOVER_WINDOW: Duration = Duration::from_millis(60);
+/// Letters in a row typed in the wrong case before Caps Lock is suspected.
+pub(crate) const CASE_SLIPS: usize = 3;
+/// Longest clipboard text typed, in words.
+pub(crate) const CLIPBOARD_WORDS: usize = 1000;
+/// Runs listed on the start screen.
+pub(crate) const RECENT_RUNS: usize = 5;
+/// Words shown under the current one when it's typed on its own.
+pub(crate) const UPCOMING_WORDS: usize = 3;
+/// Pages of the stats screen, switched with Tab.
+pub(crate) const STATS_PAGES: [&str; 2] = ["Overview", "Activity"];
+
+#[derive(Default)]
+pub(crate) enum AppState {
+    /// Start screen shown on launch.
+    Menu,
+    /// Target text of a custom test, shown before it starts.
+    Preview,
+    #[default]
+    Input,
+    Pause,
+    Results(TableState),
+    Settings,
+    /// Session steps being edited, returning to the screen it was opened
+    /// from.
+    Plan(ListState, Box<AppState>),
+    Report,
+    Zen(Zen),
+    /// Raw speed and volume of a finished zen session.
+    ZenResults(Zen),
+    /// Keyboard layout comparison, keeping the results table to return to.
+    Layouts(TableState),
+    /// Saved runs, keeping the results table to return to.
+    History(ListState, TableState),
+    /// Head-to-head records, returning to the results in the second state.
+    Rivals(TableState, TableState),
+    /// Playback of the last run, keeping the results table to return to.
+    Replay(Replay, TableState),
+    /// Drills of common letter sequences to pick from.
+    Practice(ListState),
+    /// Totals and averages of saved runs, returning to the screen it was
+    /// opened from.
+    Stats(Box<AppState>),
+    /// Players of a LAN race waiting for the host to start it.
+    Lobby,
+}
+
+impl AppState {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            AppState::Menu => "Menu",
+            AppState::Preview => "Preview",
+            AppState::Input => "Input",
+            AppState::Pause => "Pause",
+            AppState::Results(_) => "Results",
+            AppState::Settings => "Settings",
+            AppState::Plan(..) => "Plan",
+            AppState::Report => "Report",
+            AppState::Zen(_) => "Zen",
+            AppState::ZenResults(_) => "ZenResults",
+            AppState::Layouts(_) => "Layouts",
+            AppState::History(..) => "History",
+            AppState::Rivals(..) => "Rivals",
+            AppState::Replay(..) => "Replay",
+            AppState::Stats(_) => "Stats",
+            AppState::Practice(_) => "Practice",
+            AppState::Lobby => "Lobby",
+        }
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            app_state: AppState::Input,
+            current_word: String::new(),
+            input: String::new(),
+            wrong_input_chars: HashSet::new(),
+            words_limit: 50,
+            mode: TestMode::default(),
+            quote_length: QuoteLength::default(),
+            quote: None,
+            code_lang: CodeLang::default(),
+            snippet: None,
+            ngrams: None,
+            corpus: None,
+            pairs: Vec::new(),
+            time_limit: Duration::from_secs(default_time_limit()),
+            lang: Lang::En,
+            words: Vec::new(),
+            wrong_words: HashSet::new(),
+            clock: TestClock::default(),
+            settings_changed: false,
+            selected_setting: SettingItem::default(),
+            editor: Editor::default(),
+            settings_search: None,
+            settings_offset: 0,
+            settings_area: Rect::default(),
+            results_sort: (SortColumn::default(), false),
+            race: Race::new("you", 50),
+            bots: Vec::new(),
+            net: None,
+            nickname: String::new(),
+            peers: HashMap::new(),
+            rivals: Rivals::default(),
+            records: Records::default(),
+            ghost_race: false,
+            ghosts: Ghosts::default(),
+            ghost: None,
+            ghost_racer: None,
+            strokes: Vec::new(),
+            frames: Vec::new(),
+            usage: Usage::default(),
+            new_best: None,
+            published: None,
+            plan: Vec::new(),
+            session: None,
+            word_gen: WordGen::new(WordMode::Random, 1.0),
+            cue: Cue::default(),
+            sound: false,
+            volume: default_volume(),
+            player: Player::default(),
+            pulse: None,
+            show_cohort: true,
+            quiet_hours: None,
+            io: IoWorker::default(),
+            governor: None,
+            key_stats: KeyStats::default(),
+            score: None,
+            leaderboard: Leaderboard::default(),
+            scored: None,
+            pack: None,
+            min_words: 0,
+            min_seconds: 0.0,
+            unsaved: None,
+            recent: Vec::new(),
+            speeds: Speeds::default(),
+            regressions: Vec::new(),
+            history: Vec::new(),
+            day_stats: Vec::new(),
+            stats_page: 0,
+            dashboard: Dashboard::default(),
+            calendar: Calendar::default(),
+            streak: Streak::default(),
+            trend: None,
+            keys: Keymap::default(),
+            theme: Theme::default(),
+            theme_config: ThemeConfig::default(),
+            wordlist: None,
+            config_file: ConfigManager::default(),
+            pressed: None,
+            recording: false,
+            settings_notice: None,
+            low_bandwidth: false,
+            dev: DevStats::default(),
+            forgive_rollover: false,
+            forgiven: 0,
+            case_slips: 0,
+            caps_lock: false,
+            last_key: None,
+            tour: None,
+            cycle: Vec::new(),
+            cycle_every: 0,
+            current_lang: Lang::En,
+            target: VecDeque::new(),
+            queued_langs: HashMap::new(),
+            metrics: Metrics::default(),
+            scratch: None,
+            menu_notice: None,
+            joiners: Joiners::default(),
+            modifiers: Modifiers::default(),
+            finished_at: 0,
+            export_dir: None,
+            export_format: ExportFormat::default(),
+            exported: None,
+            seed: None,
+            lobby: None,
+            keystrokes: 0,
+            key_errors: BTreeMap::new(),
+            timeline: Vec::new(),
+            sentence_mode: false,
+            line_words: default_line_words(),
+            line_start: 0,
+            boundary_pause: false,
+            accuracy_thresholds: default_accuracy_thresholds(),
+            sidebar: true,
+            pace_wpm: 0,
+            reveal: 0,
+            upcoming: true,
+            strict: false,
+            pause_word: PauseWord::default(),
+            gate: None,
+        }
+    }
+}
+
+impl App {
+    pub fn from(config: &Config) -> Self {
+        let mut word_gen = WordGen::new(config.word_mode, config.curve);
+        word_gen.set_pack(config.pack.as_deref());
+        let mut app = Self::default();
+        app.lang = get_lang(&config.lang).unwrap_or(Lang::En);
+        app.word_gen = word_gen;
+        app.current_word = app.word_gen.next(app.lang, 0.0).to_string();
+        app.pack = config
+            .pack
+            .clone()
+            .filter(|code| packs::load(code).is_some());
+        app.words_limit = config.limit;
+        app.mode = config.mode;
+        app.quote_length = config.quote_length;
+        app.code_lang = config.code_lang;
+        app.time_limit = Duration::from_secs(config.time_limit.max(1));
+        app.race.reset(app.words_limit);
+        for (i, wpm) in config.bots.iter().enumerate() {
+            let racer = app.race.join(&format!("bot {}", i + 1));
+            app.bots.push(Bot {
+                racer,
+                wpm: *wpm as f32,
+            });
+        }
+        app.nickname = config.nickname.clone();
+        app.net = config.server.as_deref().map(NetClient::connect);
+        app.plan = config.plan.clone();
+        app.cue = config.cue;
+        app.sound = config.sound;
+        app.volume = config.volume.min(100);
+        app.show_cohort = config.cohort;
+        app.quiet_hours = config.quiet_hours;
+        app.governor = Governor::new(config.max_wpm, config.governor);
+        app.min_words = config.min_words;
+        app.min_seconds = config.min_seconds;
+        let history = history::load();
+        app.streak = Streak::new(&history);
+        app.recent = history[history.len().saturating_sub(RECENT_RUNS)..].to_vec();
+        app.regressions = drills::regressions(history::now());
+        app.keys = config.keys.clone();
+        app.theme = config.theme.resolve();
+        app.theme_config = config.theme.clone();
+        app.wordlist = config.wordlist.clone();
+        app.seed = config.seed.clone();
+        app.config_file = ConfigManager::load();
+        app.low_bandwidth = config.render.is_low();
+        app.forgive_rollover = config.forgive_rollover;
+        app.cycle = config.cycle.iter().filter_map(|l| get_lang(l)).collect();
+        app.cycle_every = config.cycle_every;
+        app.metrics = Metrics::new(&config.metrics);
+        app.joiners = config.joiners;
+        app.modifiers = Modifiers::new(config.numbers, config.punctuation);
+        app.export_dir = config.export_dir.clone();
+        app.export_format = config.export_format;
+        app.sentence_mode = config.sentence_mode;
+        app.boundary_pause = config.boundary_pause;
+        app.line_words = config.line_words.max(1);
+        app.accuracy_thresholds = config.accuracy_thresholds;
+        app.sidebar = config.sidebar;
+        app.pace_wpm = config.pace_wpm;
+        app.reveal = config.reveal;
+        app.upcoming = config.upcoming;
+        app.strict = config.strict;
+        app.pause_word = config.pause_word;
+        app.app_state = AppState::Menu;
+        app.key_stats = KeyStats::load();
+        // Checked before the app starts, an invalid formula ranks by speed.
+        app.score = config
+            .score
+            .as_deref()
+            .and_then(|score| Formula::parse(score).ok());
+        app.leaderboard = Leaderboard::load();
+        app.word_gen.set_weak_keys(app.key_stats.weakest(WEAK_KEYS));
+        app.rivals = Rivals::load();
+        app.records = Records::load();
+        app.ghost_race = config.ghost;
+        app.ghosts = Ghosts::load();
+        app.usage = Usage::load();
+        if let Some(recovered) = journal::recover() {
+            app.recover(recovered);
+        } else if app.recent.is_empty() && !tour::seen() {
+            app.start_tour();
+        }
+        app
+    }
+
+    /// Restores the completed words of a crashed test and pauses it.
+    pub(crate) fn recover(&mut self, recovered: Recovered) {
+        if recovered.words.len() >= recovered.limit {
+            return;
+        }
+        self.lang = get_lang(&recovered.lang).unwrap_or(self.lang);
+        self.words_limit = recovered.limit;
+        self.race.reset(self.words_limit);
+
+        for (word, wrong, ms) in recovered.words {
+            let previous = self.words.last().map(|w| w.finished_at).unwrap_or_default();
+            let length = graphemes::count(&word);
+            for i in 0..length {
+                self.metrics.key(!wrong.contains(&i));
+            }
+            self.keystrokes += length;
+            let missed: Vec<char> = word
+                .graphemes(true)
+                .enumerate()
+                .filter(|(i, _)| wrong.contains(i))
+                .filter_map(|(_, grapheme)| graphemes::base(grapheme))
+                .collect();
+            for ch in missed {
+                self.miss(ch);
+            }
+            self.metrics.word(&WordSample {
+                word: &word,
+                wrong: wrong.len(),
+                time: Duration::from_millis(ms).saturating_sub(previous),
+            });
+            if !wrong.is_empty() {
+                self.wrong_words.insert(self.words.len());
+            }
+            self.words.push(Word {
+                word,
+                wrong_chars: wrong.into_iter().collect(),
+                finished_at: Duration::from_millis(ms),
+                forgiven: 0,
+                lang: self.lang,
+            });
+        }
+
+        self.line_start = self.words.len();
+        let now = Instant::now();
+        let typed = self.words.last().map(|w| w.finished_at).unwrap_or_default();
+        self.clock.start(now.checked_sub(typed).unwrap_or(now));
+        self.clock.split(now);
+        self.clock.pause(now);
+        self.app_state = AppState::Pause;
+        self.io.send(Job::ResumeJournal);
+        self.new_word();
+    }
+
+    pub(crate) fn restart(&mut self) {
+        self.scored = None;
+        if let Some(scratch) = &self.scratch {
+            self.words_limit = scratch.words.len();
+        } else if let Some(lobby) = self.lobby.as_ref().filter(|_| self.in_race()) {
+            self.words_limit = lobby.words.len();
+        }
+        self.app_state = AppState::Input;
+        self.input.clear();
+        self.wrong_input_chars.clear();
+        self.words.clear();
+        self.wrong_words.clear();
+        self.clock.reset();
+        self.new_best = None;
+        self.exported = None;
+        self.metrics.reset();
+        self.keystrokes = 0;
+        self.case_slips = 0;
+        self.key_errors.clear();
+        self.timeline.clear();
+        self.line_start = 0;
+        self.speeds = Speeds::default();
+        self.io.send(Job::DiscardJournal);
+        if let Some(governor) = &mut self.governor {
+            governor.reset();
+        }
+        self.target.clear();
+        self.queued_langs.clear();
+        self.modifiers.reset();
+        self.word_gen.reseed(self.seed.as_deref());
+        self.quote = None;
+        self.snippet = None;
+        self.pairs.clear();
+        if let Some(scratch) = &self.scratch {
+            self.target.extend(scratch.words.iter().cloned());
+        } else if let Some(lobby) = self.lobby.as_ref().filter(|_| self.in_race()) {
+            self.target.extend(lobby.words.iter().cloned());
+        } else if let Some(set) = self.ngrams.filter(|_| self.session.is_none()) {
+            self.target = ngrams::drill(set, self.words_limit, self.word_gen.rng()).into();
+        } else if self.is_quote() {
+            self.quote = quotes::random(self.lang, self.quote_length, |quote| {
+                !quote
+                    .text
+                    .split_whitespace()
+                    .any(|w| self.word_gen.blocks(w))
+            });
+            if let Some(quote) = self.quote {
+                self.target = self.text_words(&quote.text).into();
+            }
+        } else if self.is_code() {
+            self.snippet = snippets::random(self.code_lang, self.word_gen.rng());
+            if let Some(snippet) = self.snippet {
+                self.target = snippets::lines(&snippet.code).into();
+            }
+        } else if self.is_parallel() {
+            self.pick_pairs();
+        } else if self.needs_preview() {
+            while self.target.len() < self.words_limit {
+                let progress = self.target.len() as f32 / self.words_limit.max(1) as f32;
+                self.generate(self.lang, progress);
+            }
+            self.target.truncate(self.words_limit);
+            self.app_state = AppState::Preview;
+        }
+        self.race.reset(self.limit());
+        self.strokes.clear();
+        self.frames.clear();
+        self.load_ghost();
+        self.new_word();
+    }
+
+    /// Puts the ghost of the test's kind in the race, or leaves it out when
+    /// there is none or ghosts aren't raced.
+    fn load_ghost(&mut self) {
+        let key = self.best_key(&self.result_lang());
+        self.ghost = self
+            .ghosts
+            .ghosts
+            .get(&key)
+            .filter(|_| self.ghost_race)
+            .cloned();
+        if self.ghost.is_none() && self.ghost_racer.is_none() {
+            return;
+        }
+        let racer = *self
+            .ghost_racer
+            .get_or_insert_with(|| self.race.join("ghost"));
+        self.race.racers[racer].hidden = self.ghost.is_none();
+    }
+
+    /// Moves the ghost to where its run was `elapsed` into the test.
+    fn poll_ghost(&mut self, elapsed: Duration) {
+        let (Some(ghost), Some(racer)) = (&self.ghost, self.ghost_racer) else {
+            return;
+        };
+        if let Some(stroke) = ghost.at(elapsed) {
+            self.race.apply(RaceEvent::Progress {
+                racer,
+                words: stroke.words as usize,
+                wpm: ghost.wpm,
+            });
+        }
+    }
+
+    /// Starts a test over the clipboard text, kept until the menu is reopened.
+    pub(crate) fn clipboard_test(&mut self) {
+        let text = match clipboard::read() {
+            Ok(text) => text,
+            Err(err) => {
+                self.menu_notice = Some(format!("Could not read the clipboard: {}", err));
+                return;
+            }
+        };
+        let words: Vec<String> = text
+            .split_whitespace()
+            .filter(|word| !self.word_gen.blocks(word))
+            .flat_map(|word| self.joiners.apply(word))
+            .take(CLIPBOARD_WORDS)
+            .collect();
+        if words.is_empty() {
+            self.menu_notice = Some("The clipboard has no text to type".to_string());
+            return;
+        }
+
+        self.menu_notice = None;
+        let limit = self.scratch.take().map_or(self.words_limit, |s| s.limit);
+        self.scratch = Some(Scratch {
+            words,
+            limit,
+            mistakes: false,
+        });
+        self.restart();
+    }
+
+    /// Starts a test over the words mistyped in the last one, repeating each
+    /// until it's typed cleanly.
+    pub(crate) fn train_mistakes(&mut self) {
+        let mut indices: Vec<usize> = self.wrong_words.iter().copied().collect();
+        indices.sort_unstable();
+        let mut words: Vec<String> = Vec::new();
+        for word in indices.into_iter().filter_map(|i| self.words.get(i)) {
+            // Lines of code are trained without their indentation and line
+            // break, which only Tab and Enter of code mode type.
+            let word = word.word.trim().to_string();
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+        if words.is_empty() {
+            return;
+        }
+        let limit = self.scratch.take().map_or(self.words_limit, |s| s.limit);
+        self.scratch = Some(Scratch {
+            words,
+            limit,
+            mistakes: true,
+        });
+        self.restart();
+    }
+
+    pub(crate) fn is_training_mistakes(&self) -> bool {
+        self.scratch
+            .as_ref()
+            .is_some_and(|scratch| scratch.mistakes)
+    }
+
+    /// Starts a test made of words practicing the regressed letters and bigrams.
+    pub(crate) fn start_drill(&mut self) {
+        let keys: Vec<String> = self.regressions.iter().map(|r| r.key.clone()).collect();
+        if keys.is_empty() {
+            return;
+        }
+        let pool = self.word_gen.pool(self.lang);
+        let words = drills::drill_words(&pool, &keys, self.words_limit);
+        if words.is_empty() {
+            self.menu_notice = Some("No words practice the slow keys in this language".to_string());
+            return;
+        }
+        self.menu_notice = None;
+        self.restart();
+        self.target = words.into();
+        self.app_state = AppState::Input;
+        self.new_word();
+    }
+
+    pub(crate) fn open_practice(&mut self) {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+        self.app_state = AppState::Practice(list_state);
+    }
+
+    pub(crate) fn start_ngrams(&mut self, set: &'static NgramSet) {
+        self.ngrams = Some(set);
+        self.restart();
+    }
+
+    /// Accuracy of each n-gram of the finished drill, least accurate first.
+    pub(crate) fn ngram_accuracy(&self) -> Vec<GramAccuracy> {
+        self.ngrams.map_or_else(Vec::new, |set| {
+            ngrams::accuracy(
+                set,
+                self.words
+                    .iter()
+                    .map(|word| (word.word.as_str(), word.wrong_chars.len())),
+            )
+        })
+    }
+
+    pub(crate) fn leave_scratch(&mut self) {
+        if let Some(scratch) = self.scratch.take() {
+            self.words_limit = scratch.limit;
+        }
+    }
+
+    pub(crate) fn open_menu(&mut self) {
+        self.leave_scratch();
+        self.ngrams = None;
+        self.leave_lobby();
+        // Includes the run just saved once the worker has written it.
+        self.io.flush();
+        self.regressions = drills::regressions(history::now());
+        self.restart();
+        self.app_state = AppState::Menu;
+    }
+
+    /// Records a crash-recovery event, unless the test is not to be saved at all.
+    pub(crate) fn journal(&self, event: JournalEvent) {
+        if self.scratch.is_none() {
+            self.io.send(Job::Journal(event));
+        }
+    }
+
+    /// Custom word packs show their text before the test starts, unless
+    /// shadow typing keeps it hidden.
+    pub(crate) fn needs_preview(&self) -> bool {
+        self.pack.is_some() && self.session.is_none() && self.reveal == 0
+    }
+
+    /// Handles a character typed during the test. `at` is when the key event
+    /// was read, so every duration is measured between keystrokes rather than
+    /// whenever the event loop gets to them.
+    pub(crate) fn type_char(&mut self, ch: char, at: Instant) {
+        self.watch_case(ch);
+        self.take_char(ch, at);
+        // The last keystroke is kept by `finish`, before the run is saved.
+        if self.clock.is_started() && self.clock.finished().is_none() {
+            self.stroke(at);
+        }
+    }
+
+    /// Counts letters that only miss the one expected at the caret by their
+    /// case, until one is typed in the right case.
+    fn watch_case(&mut self, ch: char) {
+        let Some(expected) = graphemes::nth(&self.current_word, graphemes::count(&self.input))
+            .and_then(graphemes::base)
+        else {
+            return;
+        };
+        if !ch.is_alphabetic() || !expected.is_alphabetic() {
+            return;
+        }
+        if ch == expected {
+            self.case_slips = 0;
+        } else if ch.to_lowercase().eq(expected.to_lowercase()) {
+            self.case_slips += 1;
+        }
+    }
+
+    /// Whether Caps Lock looks to be on, as reported by the terminal or
+    /// guessed from letters typed in the wrong case.
+    pub(crate) fn caps_warning(&self) -> bool {
+        self.caps_lock || self.case_slips >= CASE_SLIPS
+    }
+
+    /// Keeps where the test is after a keystroke at `at`, for its ghost and
+    /// replay.
+    fn stroke(&mut self, at: Instant) {
+        let elapsed = self.elapsed_at(at);
+        self.strokes.push(Stroke {
+            ms: elapsed.as_millis().min(u32::MAX as u128) as u32,
+            words: self.words.len() as u32,
+            typed: graphemes::count(&self.input) as u32,
+        });
+        // The last keystroke finishes the test with the last word typed.
+        let (word, target, wrong) = match self.words.last() {
+            Some(last) if self.current_word.is_empty() => (
+                self.words.len() - 1,
+                last.word.clone(),
+                last.wrong_chars.clone(),
+            ),
+            _ => (
+                self.words.len(),
+                self.current_word.clone(),
+                self.wrong_input_chars.clone(),
+            ),
+        };
+        self.frames.push(Frame {
+            at: elapsed,
+            word,
+            target,
+            input: self.input.clone(),
+            wrong,
+        });
+    }
+
+    fn take_char(&mut self, ch: char, at: Instant) {
+        if self.time_up(at) {
+            return;
+        }
+        if let Some(governor) = &mut self.governor
+            && !governor.allow(at)
+        {
+            return;
+        }
+        let typed = graphemes::count(&self.input);
+        let length = graphemes::count(&self.current_word);
+        let pending = self.is_pending();
+        if self.shows_line() && ch != ' ' && typed >= length && !pending {
+            // Extra characters past the end of a word are not taken.
+            return;
+        }
+        if self.shows_line() && ch == ' ' && typed == 0 {
+            return;
+        }
+        if !self.clock.is_started() {
+            self.start(at);
+        }
+        self.observe(at);
+        // Time between words doesn't count.
+        self.clock.release(at);
+        self.sample_timeline(self.elapsed_at(at));
+
+        // The journal keeps every raw keystroke, forgiven or not.
+        self.journal(JournalEvent::Key {
+            ms: self.elapsed_at(at).as_millis() as u64,
+            ch,
+        });
+        // The caret waits for the right key; the position counts as a
+        // mistake once however often it is missed.
+        if self.strict
+            && let Some(index) = self.refused(ch)
+        {
+            let expected = graphemes::nth(&self.current_word, index).and_then(graphemes::base);
+            if self.wrong_input_chars.insert(index)
+                && let Some(expected) = expected
+            {
+                self.miss(expected);
+            }
+            self.metrics.key(false);
+            self.keystrokes += 1;
+            self.play(Sound::Error);
+            return;
+        }
+        let previous_key = self.last_key;
+        let rollover = self.forgive_rollover
+            && !self.strict
+            && previous_key
+                .is_some_and(|last| at.saturating_duration_since(last) <= ROLLOVER_WINDOW);
+        self.last_key = Some(at);
+
+        if self.shows_line() && ch == ' ' {
+            // Skipped characters, and one typed only in part, count as mistakes.
+            let done = typed - usize::from(pending);
+            self.wrong_input_chars.extend(done..length);
+            let skipped: Vec<char> = graphemes::skip(&self.current_word, done)
+                .graphemes(true)
+                .filter_map(graphemes::base)
+                .collect();
+            for expected in skipped {
+                self.miss(expected);
+            }
+            self.complete_word(at);
+            return;
+        }
+
+        if rollover
+            && typed > 0
+            && self.wrong_input_chars.contains(&(typed - 1))
+            && graphemes::nth(&self.current_word, typed - 1) == Some(ch.to_string().as_str())
+        {
+            // A stray key pressed together with the right one: drop it.
+            self.input.pop();
+            self.wrong_input_chars.remove(&(typed - 1));
+            self.forgiven += 1;
+            if let Some(count) = self.key_errors.get_mut(&key(ch)) {
+                *count = count.saturating_sub(1);
+            }
+        }
+
+        self.input.push(ch);
+        let input_len = graphemes::count(&self.input);
+        let index = input_len.saturating_sub(1);
+
+        let expected = graphemes::nth(&self.current_word, index).and_then(graphemes::base);
+        let verdict = graphemes::judge(
+            graphemes::nth(&self.current_word, index),
+            graphemes::nth(&self.input, index).unwrap_or_default(),
+        );
+        let correct = verdict != Verdict::Wrong;
+        if !correct {
+            self.wrong_input_chars.insert(index);
+            if let Some(expected) = expected {
+                self.miss(expected);
+            }
+        }
+        self.metrics.key(correct);
+        self.keystrokes += 1;
+        self.play(ternary!(correct, Sound::Click, Sound::Error));
+        if verdict == Verdict::Partial {
+            // The rest of the grapheme is still to come.
+            return;
+        }
+        // The first key of a word also carries the move from the last one.
+        if correct
+            && index > 0
+            && let Some(previous_key) = previous_key
+            && let Some(previous) =
+                graphemes::nth(&self.current_word, index - 1).and_then(graphemes::base)
+            && let Some(current) = expected
+        {
+            self.speeds.record(
+                previous,
+                current,
+                at.saturating_duration_since(previous_key),
+            );
+        }
+
+        // In sentence mode only the last word of the test ends without a space.
+        let last = !self.is_timed() && self.words.len() + 1 >= self.limit();
+        if input_len >= length && (!self.shows_line() || last) {
+            self.complete_word(at);
+        }
+    }
+
+    /// Tab in code mode: the expected indentation up to the next tab stop,
+    /// typed as spaces, or a tab character that is wrong anywhere else.
+    pub(crate) fn type_tab(&mut self, at: Instant) {
+        let typed = graphemes::count(&self.input);
+        match snippets::tab_spaces(&self.current_word, typed) {
+            0 => self.type_char('\t', at),
+            spaces => {
+                for _ in 0..spaces {
+                    self.type_char(' ', at);
+                }
+            }
+        }
+    }
+
+    /// Position of the grapheme `ch` would get wrong, for strict mode to
+    /// refuse it. Typing past the end is left to the caller.
+    fn refused(&self, ch: char) -> Option<usize> {
+        let next = format!("{}{}", self.input, ch);
+        let index = graphemes::count(&next) - 1;
+        let expected = graphemes::nth(&self.current_word, index)?;
+        let verdict = graphemes::judge(Some(expected), graphemes::nth(&next, index)?);
+        (verdict == Verdict::Wrong).then_some(index)
+    }
+
+    /// Whether the last typed grapheme is only the start of the expected one.
+    fn is_pending(&self) -> bool {
+        let typed = graphemes::count(&self.input);
+        typed > 0
+            && graphemes::judge(
+                graphemes::nth(&self.current_word, typed - 1),
+                graphemes::nth(&self.input, typed - 1).unwrap_or_default(),
+            ) == Verdict::Partial
+    }
+
+    /// Counts a mistake against the key for `expected`.
+    pub(crate) fn miss(&mut self, expected: char) {
+        *self.key_errors.entry(key(expected)).or_default() += 1;
+    }
+
+    pub(crate) fn complete_word(&mut self, at: Instant) {
+        if !self.wrong_input_chars.is_empty() {
+            self.wrong_words.insert(self.words.len());
+            // Comes back at the end of the test until it's typed cleanly.
+            if self.is_training_mistakes() {
+                self.target.push_back(self.current_word.clone());
+                self.words_limit += 1;
+            }
+        }
+        if self.word_gen.mode == WordMode::Adaptive {
+            self.word_gen.adapt(self.wrong_input_chars.is_empty());
+        }
+        self.word_completed(self.wrong_input_chars.is_empty());
+
+        let mut wrong: Vec<usize> = self.wrong_input_chars.iter().copied().collect();
+        wrong.sort_unstable();
+        self.journal(JournalEvent::Word {
+            ms: self.elapsed_at(at).as_millis() as u64,
+            word: self.current_word.clone(),
+            wrong,
+        });
+
+        let finished_at = self.elapsed_at(at);
+        let time = self.clock.split(at);
+        self.metrics.word(&WordSample {
+            word: &self.current_word,
+            wrong: self.wrong_input_chars.len(),
+            time,
+        });
+
+        self.words.push(Word {
+            word: std::mem::take(&mut self.current_word),
+            wrong_chars: std::mem::take(&mut self.wrong_input_chars),
+            finished_at,
+            forgiven: std::mem::take(&mut self.forgiven),
+            lang: self.current_lang,
+        });
+        if self.boundary_pause {
+            self.clock.hold(at);
+        }
+        if !self.pairs.is_empty() {
+            // Each pair is a line of its own.
+            if self
+                .pairs
+                .iter()
+                .any(|&(start, _)| start == self.words.len())
+            {
+                self.line_start = self.words.len();
+            }
+        } else if self.words.len() - self.line_start >= self.line_words {
+            self.line_start = self.words.len();
+        }
+        self.tick();
+
+        if !self.is_timed() && self.words.len() >= self.limit() {
+            self.finish(at);
+        } else {
+            self.new_word();
+        }
+    }
+
+    pub(crate) fn start_tour(&mut self) {
+        self.tour = Some(Tour::new(self.words_limit));
+        self.words_limit = TOUR_WORDS;
+        self.restart();
+        self.app_state = AppState::Menu;
+    }
+
+    pub(crate) fn end_tour(&mut self) {
+        let Some(tour) = self.tour.take() else {
+            return;
+        };
+        if self.words_limit == TOUR_WORDS {
+            self.words_limit = tour.base_limit;
+        }
+        self.io.send(Job::TourSeen);
+        self.restart();
+        self.app_state = AppState::Menu;
+    }
+
+    /// Moves the tour on once the user has done what the current step asks.
+    pub(crate) fn advance_tour(&mut self) {
+        let Some(tour) = &mut self.tour else {
+            return;
+        };
+        let next = match (tour.step, &self.app_state) {
+            (TourStep::Start, AppState::Input) => TourStep::Type,
+            (TourStep::Type, AppState::Input) if self.clock.is_started() => TourStep::Pause,
+            (TourStep::Pause, AppState::Pause) => TourStep::Resume,
+            (TourStep::Type | TourStep::Pause | TourStep::Resume, AppState::Results(_)) => {
+                TourStep::Results
+            }
+            (TourStep::Results, AppState::Settings) => TourStep::Settings,
+            (TourStep::Settings, AppState::Settings) => return,
+            (TourStep::Settings, _) => return self.end_tour(),
+            _ => return,
+        };
+        tour.step = next;
+    }
+
+    pub(crate) fn pause(&mut self) {
+        self.clock.pause(Instant::now());
+        self.io.send(Job::SyncJournal);
+        self.app_state = AppState::Pause
+    }
+
+    pub(crate) fn resume(&mut self) {
+        let paused = self.clock.resume(Instant::now());
+        // The pause doesn't count as time between two keystrokes either.
+        self.shift_last_key(paused);
+        if self.pause_word == PauseWord::Clear && !self.input.is_empty() {
+            self.input.clear();
+            self.wrong_input_chars.clear();
+            self.forgiven = 0;
+            self.last_key = None;
+        }
+        self.app_state = AppState::Input;
+    }
+
+    pub(crate) fn start(&mut self, at: Instant) {
+        self.clock.start(at);
+        self.journal(JournalEvent::Start {
+            lang: lang_code(self.lang),
+            limit: self.limit(),
+        });
+    }
+
+    pub(crate) fn finish(&mut self, at: Instant) {
+        let limit = self.is_timed().then_some(self.time_limit);
+        self.clock.finish(at, limit);
+        self.finished_at = history::timestamp_at(at);
+        self.io.send(Job::DiscardJournal);
+        // Ticks stop with the test, so the last word hasn't reached the race yet.
+        self.sync_peers();
+        self.poll_ghost(self.elapsed_at(at));
+        self.stroke(at);
+        self.race.apply(RaceEvent::Progress {
+            racer: PLAYER,
+            words: self.words.len(),
+            wpm: self.live_wpm(),
+        });
+        let too_short = self.too_short().is_some();
+        self.save_result();
+        // Drills and other unsaved practice still count as usage, accidental
+        // restarts below the minimums don't.
+        if !too_short {
+            self.count_usage();
+        }
+        let (wpm, accuracy) = (self.live_wpm(), self.calculate_accuracy());
+        if self.unsaved.is_none()
+            && let Some(gate) = &mut self.gate
+        {
+            gate.record(wpm, accuracy);
+        }
+        if self.session.is_some() {
+            self.advance_session();
+            return;
+        }
+        let mut table_state = TableState::default();
+        table_state.select_first();
+        self.app_state = AppState::Results(table_state)
+    }
+
+    /// Why the finished run is below the configured minimums, if it is.
+    fn too_short(&self) -> Option<String> {
+        let seconds = self.finished_time().unwrap_or_default().as_secs_f32();
+        if self.words.len() < self.min_words {
+            Some(format!("fewer than {} words", self.min_words))
+        } else if seconds < self.min_seconds {
+            Some(format!("shorter than {}s", self.min_seconds))
+        } else {
+            None
+        }
+    }
+
+    /// Persists the finished run unless it is practice that isn't kept or is
+    /// below the configured minimums.
+    pub(crate) fn save_result(&mut self) {
+        let seconds = self.finished_time().unwrap_or_default().as_secs_f32();
+        self.unsaved = if self.is_training_mistakes() {
+            Some("mistake training".to_string())
+        } else if self.scratch.is_some() {
+            Some("clipboard text".to_string())
+        } else if self.ngrams.is_some() {
+            Some("n-gram drill".to_string())
+        } else {
+            self.too_short()
+        };
+        if self.unsaved.is_some() {
+            return;
+        }
+
+        for word in &self.words {
+            self.key_stats.record(&word.word);
+        }
+        self.key_stats.record_errors(&self.key_errors);
+        self.word_gen
+            .set_weak_keys(self.key_stats.weakest(WEAK_KEYS));
+        self.io.send(Job::KeyStats(self.key_stats.clone()));
+        self.rank_run();
+        self.io.send(Job::Leaderboard(self.leaderboard.clone()));
+        let entry = history::Entry {
+            timestamp: self.finished_at,
+            lang: self.result_lang(),
+            words: self.words.len(),
+            wpm: self.live_wpm(),
+            accuracy: self.calculate_accuracy(),
+            seconds,
+            mode: self.word_gen.mode,
+            timing: ternary!(self.boundary_pause, Timing::Words, Timing::Continuous),
+            hour: daytime::hour_at(self.finished_at),
+            key_errors: self.key_errors.clone(),
+            raw: Some(self.live_speeds().1),
+            consistency: self.word_consistency(),
+            test: Some(self.test_mode()),
+        };
+        self.io.send(Job::History(entry.clone()));
+        if let Some(day) = calendar::local_day(entry.timestamp) {
+            self.streak.record(day);
+        }
+        if self.rivals.record(
+            &self.race,
+            self.peers.values().copied(),
+            entry.wpm,
+            entry.timestamp,
+        ) {
+            self.io.send(Job::Rivals(self.rivals.clone()));
+        }
+        if entry.timing == Timing::Continuous {
+            let key = self.best_key(&entry.lang);
+            self.new_best = self.records.record(key.clone(), entry.wpm, entry.accuracy);
+            self.io.send(Job::Records(self.records.clone()));
+            let ghost = Ghost {
+                wpm: entry.wpm,
+                timestamp: entry.timestamp,
+                strokes: std::mem::take(&mut self.strokes),
+            };
+            if self.ghosts.record(key, ghost) {
+                self.io.send(Job::Ghosts(self.ghosts.clone()));
+            }
+        }
+        self.speeds.timestamp = entry.timestamp;
+        self.io.send(Job::Speeds(std::mem::take(&mut self.speeds)));
+        self.recent.push(entry);
+        if self.recent.len() > RECENT_RUNS {
+            self.recent.remove(0);
+        }
+    }
+
+    /// Kind of test the personal bests of a run in `lang` are kept for.
+    fn best_key(&self, lang: &str) -> String {
+        if self.is_timed() {
+            records::key("time", lang, &self.time_limit.as_secs().to_string())
+        } else if self.is_quote() {
+            records::key("quote", lang, self.quote_length.name())
+        } else if self.is_code() {
+            records::key("code", lang, "snippet")
+        } else if self.is_parallel() {
+            records::key("parallel", lang, &self.words_limit.to_string())
+        } else {
+            records::key("words", lang, &self.limit().to_string())
+        }
+    }
+
+    /// Language code of the run, every cycled one joined with "+", or the
+    /// programming language of a snippet.
+    fn result_lang(&self) -> String {
+        if self.is_code() {
+            self.code_lang.label().to_string()
+        } else if self.is_cycling() {
+            self.cycle
+                .iter()
+                .map(|&l| lang_code(l))
+                .collect::<Vec<_>>()
+                .join("+")
+        } else {
+            lang_code(self.lang)
+        }
+    }
+
+    /// Writes the finished run to the export directory, noting the path
+    /// written or what went wrong.
+    pub(crate) fn export_run(&mut self) {
+        let run = export::Run {
+            timestamp: self.finished_at,
+            lang: self.result_lang(),
+            wpm: self.live_wpm(),
+            accuracy: self.calculate_accuracy(),
+            seconds: self.finished_time().unwrap_or_default().as_secs_f32(),
+            key_errors: self.key_errors.clone(),
+            words: self
+                .words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    let mut wrong_chars: Vec<usize> = word.wrong_chars.iter().copied().collect();
+                    wrong_chars.sort_unstable();
+                    export::RunWord {
+                        word: word.word.clone(),
+                        correct: !self.wrong_words.contains(&i),
+                        wrong_chars,
+                        finished_at: word.finished_at.as_secs_f32(),
+                    }
+                })
+                .collect(),
+        };
+        let dir = self
+            .export_dir
+            .clone()
+            .or_else(|| data_dir().map(|dir| dir.join("exports")))
+            .ok_or("Could not find project directories");
+        self.exported = Some(
+            dir.map_err(Into::into)
+                .and_then(|dir| export::write(&run, &dir, self.export_format))
+                .map_err(|err| err.to_string()),
+        );
+    }
+
+    /// Counts the mode and features of the finished test. Only counts are
+    /// kept, never the text.
+    fn count_usage(&mut self) {
+        let mode = if self.is_timed() {
+            "time"
+        } else if self.is_quote() {
+            "quote"
+        } else if self.is_code() {
+            "code"
+        } else if self.is_parallel() {
+            "parallel"
+        } else {
+            "words"
+        };
+        let features = [
+            (self.sentence_mode, "sentence"),
+            (self.boundary_pause, "boundary_pause"),
+            (self.strict, "strict"),
+            (self.modifiers.numbers, "numbers"),
+            (self.modifiers.punctuation, "punctuation"),
+            (self.pace_wpm > 0, "pace"),
+            (self.reveal > 0, "reveal"),
+            (self.is_cycling(), "cycle"),
+            (!self.race.is_solo(), "race"),
+            (self.session.is_some(), "plan"),
+            (
+                self.scratch.is_some() && !self.is_training_mistakes(),
+                "clipboard",
+            ),
+            (self.is_training_mistakes(), "mistakes"),
+            (self.ngrams.is_some(), "ngrams"),
+        ];
+        self.usage.count(mode);
+        self.usage.count(self.word_gen.mode.name());
+        for (_, name) in features.into_iter().filter(|(used, _)| *used) {
+            self.usage.count(name);
+        }
+        self.io.send(Job::Usage(self.usage.clone()));
+    }
+
+    pub(crate) fn start_zen(&mut self) {
+        self.usage.count("zen");
+        self.io.send(Job::Usage(self.usage.clone()));
+        self.app_state = AppState::Zen(Zen::default());
+    }
+
+    /// Shows the results of the zen session, or leaves it when nothing was
+    /// written.
+    pub(crate) fn finish_zen(&mut self) {
+        let AppState::Zen(mut zen) = std::mem::replace(&mut self.app_state, AppState::Menu) else {
+            return;
+        };
+        if !zen.is_started() {
+            self.restart();
+            return;
+        }
+        zen.finish();
+        self.app_state = AppState::ZenResults(zen);
+    }
+
+    /// Kind of the current test, as plans, the tour, clipboard text and drills
+    /// count words whatever the mode.
+    pub(crate) fn test_mode(&self) -> TestMode {
+        if self.is_timed() {
+            TestMode::Time
+        } else if self.is_quote() {
+            TestMode::Quote
+        } else if self.is_code() {
+            TestMode::Code
+        } else if self.is_parallel() {
+            TestMode::Parallel
+        } else {
+            TestMode::Words
+        }
+    }
+
+    /// Plans, the tour, clipboard text and drills always run to the last word.
+    pub(crate) fn is_timed(&self) -> bool {
+        self.mode == TestMode::Time
+            && self.ngrams.is_none()
+            && self.session.is_none()
+            && self.tour.is_none()
+            && self.scratch.is_none()
+            && !self.in_race()
+    }
+
+    /// Like timed tests, quotes give way to plans, the tour, clipboard text
+    /// and drills.
+    pub(crate) fn is_quote(&self) -> bool {
+        self.mode == TestMode::Quote
+            && self.ngrams.is_none()
+            && self.session.is_none()
+            && self.tour.is_none()
+            && self.scratch.is_none()
+            && !self.in_race()
+    }
+
+    pub(crate) fn is_code(&self) -> bool {
+        self.mode == TestMode::Code
+            && self.ngrams.is_none()
+            && self.session.is_none()
+            && self.tour.is_none()
+            && self.scratch.is_none()
+            && !self.in_race()
+    }
+
+    pub(crate) fn is_parallel(&self) -> bool {
+        self.mode == TestMode::Parallel
+            && self.ngrams.is_none()
+            && self.session.is_none()
+            && self.tour.is_none()
+            && self.scratch.is_none()
+            && !self.in_race()
+    }
+
+    /// Words in the test: the quote's when one is typed, every pair's, or
+    /// the snippet's lines.
+    pub(crate) fn limit(&self) -> usize {
+        if let Some(&(start, pair)) = self.pairs.last() {
+            return start + self.text_words(&pair.text).len();
+        }
+        if let Some(snippet) = self.snippet {
+            return snippets::lines(&snippet.code).len();
+        }
+        self.quote
+            .map_or(self.words_limit, |quote| self.text_words(&quote.text).len())
+    }
+
+    /// Words typed for a quote or pair `text`, joiners applied.
+    pub(crate) fn text_words(&self, text: &str) -> Vec<String> {
+        text.split_whitespace()
+            .flat_map(|word| self.joiners.apply(word))
+            .collect()
+    }
+
+    /// Words shown dimmed under the current one when it's typed on its own,
+    /// none past the end of the test or while shadow typing.
+    pub(crate) fn upcoming_words(&self) -> Vec<&str> {
+        if !self.upcoming || self.shows_line() || self.reveal > 0 {
+            return Vec::new();
+        }
+        let left = ternary!(
+            self.is_timed(),
+            usize::MAX,
+            self.limit().saturating_sub(self.words.len() + 1)
+        );
+        self.target
+            .iter()
+            .take(UPCOMING_WORDS.min(left))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether the test is typed a line at a time, pressing space after each
+    /// word. Quotes and pairs always are, to keep their punctuation in context.
+    pub(crate) fn shows_line(&self) -> bool {
+        // Snippets are typed a line at a time, spaces and all.
+        self.snippet.is_none()
+            && (self.sentence_mode || self.quote.is_some() || !self.pairs.is_empty())
+    }
+
+    /// Queues pairs of the corpus, or the bundled ones, until `words_limit`
+    /// words are queued. Pairs with blocked words are skipped unless every
+    /// pair has one.
+    fn pick_pairs(&mut self) {
+        let corpus = self.corpus.unwrap_or_else(|| corpus::bundled(self.lang));
+        let allowed: Vec<&'static Pair> = corpus
+            .iter()
+            .filter(|pair| {
+                !pair
+                    .text
+                    .split_whitespace()
+                    .any(|w| self.word_gen.blocks(w))
+            })
+            .collect();
+        let pool = ternary!(allowed.is_empty(), corpus.iter().collect(), allowed);
+        while self.target.len() < self.words_limit.max(1) {
+            let Some(&pair) = pool.choose(self.word_gen.rng()) else {
+                break;
+            };
+            // The same sentence twice in a row reads as a mistake.
+            if pool.len() > 1 && self.pairs.last().is_some_and(|&(_, last)| last == pair) {
+                continue;
+            }
+            self.pairs.push((self.target.len(), pair));
+            let words = self.text_words(&pair.text);
+            self.target.extend(words);
+        }
+    }
+
+    /// Pair of the word being typed, in parallel mode.
+    fn current_pair(&self) -> Option<&'static Pair> {
+        self.pairs
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= self.words.len())
+            .map(|&(_, pair)| pair)
+    }
+
+    /// Translation shown under the line while a pair is typed.
+    pub(crate) fn translation(&self) -> Option<&'static str> {
+        self.current_pair().map(|pair| pair.translation.as_str())
+    }
+
+    /// Finishes a timed test whose time ran out by `at`.
+    pub(crate) fn time_up(&mut self, at: Instant) -> bool {
+        let up =
+            self.is_timed() && self.clock.is_started() && self.elapsed_at(at) >= self.time_limit;
+        if up {
+            self.finish(at);
+        }
+        up
+    }
+
+    /// Test progress shown in the input title: words typed, or seconds left.
+    pub(crate) fn counter(&self) -> String {
+        if self.is_timed() {
+            let left = self.time_limit.saturating_sub(self.elapsed());
+            return format!("{}s", left.as_secs_f32().ceil() as u64);
+        }
+        format!("{}/{}", self.words.len(), self.limit())
+    }
+
+    pub(crate) fn length_label(&self) -> String {
+        if let Some(set) = self.ngrams {
+            format!("{} words of {}", self.words_limit, set.name.to_lowercase())
+        } else if self.is_timed() {
+            format!("{}s", self.time_limit.as_secs())
+        } else if self.is_quote() {
+            "quote".to_string()
+        } else if self.is_code() {
+            format!("{} code", self.code_lang.label())
+        } else if self.is_parallel() {
+            format!("{} words parallel", self.words_limit)
+        } else {
+            format!("{} words", self.words_limit)
+        }
+    }
+
+    pub(crate) fn exit(&mut self) {
+        self.io.send(Job::DiscardJournal);
+        self.exit = true;
+    }
+
+    /// Time spent typing so far, excluding the current pause.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    /// Time spent typing up to `at`.
+    pub(crate) fn elapsed_at(&self, at: Instant) -> Duration {
+        self.clock.elapsed(at)
+    }
+
+    /// Length of the finished test.
+    pub(crate) fn finished_time(&self) -> Option<Duration> {
+        self.clock.finished()
+    }
+
+    /// Leaves a suspend of the process out of the test and of the time since
+    /// the last keystroke.
+    fn observe(&mut self, at: Instant) {
+        let stalled = self.clock.observe(at);
+        self.shift_last_key(stalled);
+    }
+
+    fn shift_last_key(&mut self, by: Duration) {
+        self.last_key = self.last_key.and_then(|at| at.checked_add(by));
+    }
+
+    /// Fills in the seconds that ended before `elapsed`. The count only changes
+    /// on keystrokes, so sampling before each key keeps every second exact.
+    pub(crate) fn sample_timeline(&mut self, elapsed: Duration) {
+        let correct = self.correct_chars();
+        while (self.timeline.len() as u64) < elapsed.as_secs() {
+            self.timeline.push(correct);
+        }
+    }
+
+    pub(crate) fn tick(&mut self) {
+        if let Some(failure) = self.io.failures().pop() {
+            self.unsaved = Some(failure);
+        }
+        self.sync_peers();
+        if let AppState::Replay(replay, _) = &mut self.app_state {
+            replay.advance(Instant::now());
+        }
+        if !matches!(self.app_state, AppState::Input) || !self.clock.is_started() {
+            return;
+        }
+        let now = Instant::now();
+        self.observe(now);
+        if self.time_up(now) {
+            return;
+        }
+
+        let elapsed = self.elapsed();
+        self.sample_timeline(elapsed);
+        for bot in &self.bots {
+            self.race.apply(bot.poll(elapsed));
+        }
+        self.poll_ghost(elapsed);
+        let (words, wpm) = (self.words.len(), self.live_wpm());
+        self.race.apply(RaceEvent::Progress {
+            racer: PLAYER,
+            words,
+            wpm,
+        });
+
+        if let Some(net) = &self.net
+            && self
+                .published
+                .is_none_or(|at| at.elapsed() >= PUBLISH_INTERVAL)
+        {
+            net.send(race::encode_progress(&self.nickname, words, wpm));
+            self.published = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn sync_peers(&mut self) {
+        let Some(net) = &mut self.net else {
+            return;
+        };
+        for line in net.poll() {
+            if let Some((_, lang, words)) = race::decode_start(&line) {
+                self.begin_race(get_lang(&lang).unwrap_or(self.lang), words);
+                continue;
+            }
+            let Some((name, words, wpm)) = race::decode_progress(&line) else {
+                continue;
+            };
+            if name == self.nickname {
+                continue;
+            }
+            let racer = *self
+                .peers
+                .entry(name)
+                .or_insert_with_key(|name| self.race.join(name));
+            self.race.apply(RaceEvent::Progress { racer, words, wpm });
+        }
+    }
+
+    pub(crate) fn word_completed(&mut self, correct: bool) {
+        if self.cue == Cue::Off {
+            return;
+        }
+        self.pulse = Some((Instant::now(), correct));
+        if self.cue == Cue::Bell && !correct && !self.is_quiet() {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+    }
+
+    /// Plays keystroke feedback when sound is on, outside quiet hours.
+    fn play(&mut self, sound: Sound) {
+        if self.sound && !self.is_quiet() {
+            self.player.play(sound, self.volume as f32 / 100.0);
+        }
+    }
+
+    /// Sounds and notifications are suppressed during quiet hours.
+    pub(crate) fn is_quiet(&self) -> bool {
+        self.quiet_hours.is_some_and(|hours| hours.is_now())
+    }
+
+    pub(crate) fn pulse_style(&self) -> Style {
+        match self.pulse {
+            Some((at, correct)) if at.elapsed() < PULSE_DURATION && !self.low_bandwidth => {
+                Style::new().fg(ternary!(correct, self.theme.correct, self.theme.wrong))
+            }
+            _ => Style::new().fg(self.theme.border),
+        }
+    }
+
+    /// Opens the lobby of a LAN race, connecting to its relay at `addr`.
+    pub(crate) fn open_lobby(&mut self, lobby: Lobby, addr: &str) {
+        // Players going by the default name would not tell each other apart.
+        if self.nickname == default_nickname() {
+            self.nickname = format!("{}-{}", self.nickname, thread_rng().gen_range(100..1000));
+        }
+        let net = NetClient::connect(addr);
+        // Announces the player, queued until connected.
+        net.send(race::encode_progress(&self.nickname, 0, 0.0));
+        self.net = Some(net);
+        self.lobby = Some(lobby);
+        self.race.reset(self.words_limit);
+        self.app_state = AppState::Lobby;
+    }
+
+    /// Whether the test is a LAN race, typing the words the host sent.
+    pub(crate) fn in_race(&self) -> bool {
+        self.lobby
+            .as_ref()
+            .is_some_and(|lobby| !lobby.words.is_empty())
+    }
+
+    /// Starts a race for every player in the lobby, on words drawn from a
+    /// fresh seed. Only the host starts races.
+    pub(crate) fn start_race(&mut self) {
+        if !self.lobby.as_ref().is_some_and(Lobby::is_host) {
+            return;
+        }
+        let seed: String = thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(8)
+            .map(|b| char::from(b).to_ascii_lowercase())
+            .collect();
+        self.word_gen.reseed(Some(&seed));
+        self.modifiers.reset();
+        self.target.clear();
+        while self.target.len() < self.words_limit {
+            let progress = self.target.len() as f32 / self.words_limit.max(1) as f32;
+            self.generate(self.lang, progress);
+        }
+        let words: Vec<String> = self.target.drain(..).take(self.words_limit).collect();
+        if let Some(net) = &self.net {
+            net.send(race::encode_start(&seed, &lang_code(self.lang), &words));
+        }
+        self.begin_race(self.lang, words);
+    }
+
+    fn begin_race(&mut self, lang: Lang, words: Vec<String>) {
+        let Some(lobby) = &mut self.lobby else {
+            return;
+        };
+        lobby.words = words;
+        self.leave_scratch();
+        self.lang = lang;
+        self.restart();
+    }
+
+    /// Leaves the LAN race, closing its relay when hosting. Other players
+    /// are left out of later races.
+    pub(crate) fn leave_lobby(&mut self) {
+        if self.lobby.take().is_none() {
+            return;
+        }
+        self.net = None;
+        for (_, racer) in self.peers.drain() {
+            self.race.racers[racer].hidden = true;
+        }
+    }
+
+    pub(crate) fn net_status(&self) -> Option<&'static str> {
+        self.net
+            .as_ref()
+            .map(|net| ternary!(net.is_connected(), "online", "offline"))
+    }
+
+    /// Actions the results screen offers for this run, in bar order.
+    pub(crate) fn result_actions(&self) -> Vec<ResultAction> {
+        [
+            (!self.in_race(), ResultAction::Restart),
+            (
+                !self.in_race() && !self.wrong_words.is_empty(),
+                ResultAction::Mistakes,
+            ),
+            (self.lobby.is_some(), ResultAction::Lobby),
+            (self.words.len() > 1, ResultAction::Sort),
+            (true, ResultAction::History),
+            (true, ResultAction::Stats),
+            (!self.rivals.rivals.is_empty(), ResultAction::Rivals),
+            (!self.words.is_empty(), ResultAction::Layouts),
+            (!self.words.is_empty(), ResultAction::Export),
+            (!self.frames.is_empty(), ResultAction::Replay),
+            (true, ResultAction::Plan),
+            (true, ResultAction::Settings),
+            (true, ResultAction::Menu),
+            (true, ResultAction::Quit),
+        ]
+        .into_iter()
+        .filter_map(|(offered, action)| offered.then_some(action))
+        .collect()
+    }
+
+    pub(crate) fn open_history(&mut self, results: TableState) {
+        self.history = history::load();
+        self.history.reverse();
+        let mut list_state = ListState::default();
+        list_state.select_first();
+        self.app_state = AppState::History(list_state, results);
+    }
+
+    pub(crate) fn open_replay(&mut self, results: TableState) {
+        let end = self.finished_time().unwrap_or_default();
+        self.app_state = AppState::Replay(Replay::new(end), results);
+    }
+
+    pub(crate) fn open_stats(&mut self) {
+        // Includes the run just saved once the worker has written it.
+        self.io.flush();
+        let history = history::load();
+        self.dashboard = Dashboard::new(&history);
+        self.day_stats = daytime::by_day_part(&history);
+        self.calendar = Calendar::new(&history, calendar::today());
+        self.trend = trend::accuracy_alert(&history);
+        let back = std::mem::take(&mut self.app_state);
+        self.app_state = AppState::Stats(Box::new(back));
+    }
+
+    pub(crate) fn close_stats(&mut self) {
+        if let AppState::Stats(back) = &mut self.app_state {
+            self.app_state = *std::mem::take(back);
+        }
+    }
+
+    pub(crate) fn next_stats_page(&mut self) {
+        self.stats_page = (self.stats_page + 1) % STATS_PAGES.len();
+    }
+
+    pub(crate) fn open_plan(&mut self) {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+        let back = std::mem::take(&mut self.app_state);
+        self.app_state = AppState::Plan(list_state, Box::new(back));
+    }
+
+    pub(crate) fn close_plan(&mut self) {
+        if let AppState::Plan(_, back) = &mut self.app_state {
+            self.app_state = *std::mem::take(back);
+        }
+    }
+
+    pub(crate) fn start_session(&mut self) {
+        let Some(session) = Session::new(&self.plan, (self.lang, self.words_limit)) else {
+            return;
+        };
+        self.leave_scratch();
+        self.session = Some(session);
+        self.begin_step();
+    }
+
+    pub(crate) fn begin_step(&mut self) {
+        if let Some(step) = self.session.as_ref().and_then(Session::step) {
+            self.lang = step.lang();
+            self.words_limit = step.limit;
+        }
+        self.restart();
+    }
+
+    pub(crate) fn advance_session(&mut self) {
+        let result = StepResult {
+            label: self
+                .session
+                .as_ref()
+                .and_then(Session::step)
+                .map(|step| step.label.clone())
+                .unwrap_or_default(),
+            lang: self.lang,
+            words: self.words.len(),
+            wpm: self.live_wpm(),
+            accuracy: self.calculate_accuracy(),
+            seconds: self.finished_time().unwrap_or_default().as_secs_f32(),
+        };
+        let Some(session) = &mut self.session else {
+            return;
+        };
+
+        if session.advance(result).is_some() {
+            self.begin_step();
+        } else {
+            (self.lang, self.words_limit) = session.base;
+            self.app_state = AppState::Report;
+        }
+    }
+
+    pub(crate) fn leave_session(&mut self) {
+        self.session = None;
+        self.restart();
+    }
+
+    pub(crate) fn is_cycling(&self) -> bool {
+        self.session.is_none() && self.cycle.len() >= 2 && self.cycle_every > 0
+    }
+
+    /// Language of the word typed at `position` of the test.
+    pub(crate) fn word_lang(&self, position: usize) -> Lang {
+        if !self.is_cycling() {
+            return self.lang;
+        }
+        self.cycle[position / self.cycle_every % self.cycle.len()]
+    }
+
+    /// How far into the test the word at `position` is typed, from 0 to 1.
+    /// Timed tests assume the words ahead come at the pace so far.
+    fn progress_at(&self, position: usize) -> f32 {
+        if !self.is_timed() {
+            return position as f32 / self.limit().max(1) as f32;
+        }
+        let now = self.elapsed().as_secs_f32() / self.time_limit.as_secs_f32();
+        let done = self.words.len();
+        if done == 0 {
+            return now;
+        }
+        (now + position.saturating_sub(done) as f32 * now / done as f32).min(1.0)
+    }
+
+    pub(crate) fn new_word(&mut self) {
+        let position = self.words.len();
+        if self.word_gen.mode == WordMode::Adaptive {
+            // Words drawn ahead at an older level are drawn again at the
+            // current one. They are the last ones queued, after any fixed text.
+            let fixed = (0..self.target.len())
+                .take_while(|i| !self.queued_langs.contains_key(&(position + i)))
+                .count();
+            self.target.truncate(fixed);
+        }
+        if self.target.is_empty() {
+            self.generate_at(position);
+        }
+        self.current_word = self.target.pop_front().unwrap_or_default();
+        self.current_lang = self
+            .queued_langs
+            .remove(&position)
+            .unwrap_or_else(|| self.word_lang(position));
+        // The whole line, or the upcoming words, are shown ahead, so they
+        // are generated in advance.
+        let ahead = match (self.sentence_mode, self.upcoming) {
+            (true, _) => self.line_words,
+            (false, true) => UPCOMING_WORDS,
+            (false, false) => 0,
+        };
+        while self.quote.is_none()
+            && self.snippet.is_none()
+            && self.ngrams.is_none()
+            && self.pairs.is_empty()
+            && self.scratch.is_none()
+            && self.target.len() < ahead
+        {
+            self.generate_at(position + 1 + self.target.len());
+        }
+        self.input.clear();
+        self.wrong_input_chars.clear();
+    }
+
+    /// Queues the word typed at `position`, in the language and at the
+    /// difficulty of that point of the test.
+    fn generate_at(&mut self, position: usize) {
+        let lang = self.word_lang(position);
+        let queued = self.target.len();
+        self.generate(lang, self.progress_at(position));
+        // The parts of a split word keep the language it was drawn in.
+        for part in position..position + self.target.len() - queued {
+            self.queued_langs.insert(part, lang);
+        }
+    }
+
+    /// Queues the next generated word, or its parts when it is split, with
+    /// numbers and punctuation sprinkled in.
+    pub(crate) fn generate(&mut self, lang: Lang, progress: f32) {
+        let word = self.word_gen.next(lang, progress);
+        for part in self.joiners.apply(word) {
+            let rng = self.word_gen.rng();
+            self.target.push_back(self.modifiers.apply(part, rng));
+        }
+    }
+
+    /// Words of the current line: typed ones, the current one, then upcoming ones.
+    pub(crate) fn line(&self) -> (&[Word], Vec<&str>) {
+        let done = &self.words[self.line_start.min(self.words.len())..];
+        let left = ternary!(
+            self.is_timed(),
+            usize::MAX,
+            self.limit().saturating_sub(self.words.len() + 1)
+        );
+        // Pairs end their line at the next pair, however long they are.
+        let shown = match self
+            .pairs
+            .iter()
+            .find(|&&(start, _)| start > self.words.len())
+        {
+            Some(&(next, _)) => next - self.words.len() - 1,
+            None if !self.pairs.is_empty() => left,
+            None => self.line_words.saturating_sub(done.len() + 1),
+        };
+        let upcoming = self
+            .target
+            .iter()
+            .take(shown.min(left))
+            .map(String::as_str)
+            .collect();
+        (done, upcoming)
+    }
+
+    pub(crate) fn open_settings(&mut self) {
+        self.editor = Editor::new(self.current_draft());
+        self.settings_search = None;
+        self.settings_notice = None;
+        self.settings_offset = 0;
+        self.app_state = AppState::Settings;
+    }
+
+    pub(crate) fn current_draft(&self) -> Draft {
+        Draft {
+            lang: self.lang,
+            mode: self.mode,
+            limit: self.words_limit.to_string(),
+            duration: self.time_limit.as_secs().to_string(),
+            word_mode: self.word_gen.mode,
+            curve: self.word_gen.curve,
+            cue: self.cue,
+            sound: self.sound,
+            volume: self.volume,
+            cohort: self.show_cohort,
+            ghost: self.ghost_race,
+            pack: self.pack.clone(),
+            wordlist: self
+                .wordlist
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            forgive_rollover: self.forgive_rollover,
+            strict: self.strict,
+            min_length: self.word_gen.lengths().0,
+            max_length: self.word_gen.lengths().1,
+            numbers: self.modifiers.numbers,
+            punctuation: self.modifiers.punctuation,
+            pace: self.pace_wpm,
+            reveal: self.reveal,
+            upcoming: self.upcoming,
+            theme: self.theme_config.preset,
+            keys: self.keys.clone(),
+        }
+    }
+
+    /// Applies the edited settings. A wordlist that can't be loaded is
+    /// reported before anything changes.
+    pub(crate) fn apply_settings(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.editor.draft.wordlist.trim();
+        let wordlist = (!path.is_empty()).then(|| PathBuf::from(path));
+        let loaded = if wordlist != self.wordlist {
+            Some(wordlist.as_deref().map(words::load_wordlist).transpose()?)
+        } else {
+            None
+        };
+        let new_duration = self
+            .editor
+            .draft
+            .duration
+            .parse::<u64>()
+            .ok()
+            .filter(|&seconds| seconds > 0)
+            .map_or(self.time_limit, Duration::from_secs);
+        let new_limit = self
+            .editor
+            .draft
+            .limit
+            .parse::<usize>()
+            .map(|val| if val > 0 { val } else { self.words_limit })
+            .unwrap_or(self.words_limit);
+
+        let draft = &self.editor.draft;
+        if self.lang != draft.lang
+            || self.words_limit != new_limit
+            || self.word_gen.mode != draft.word_mode
+            || self.word_gen.curve != draft.curve
+            || self.pack != draft.pack
+            || self.mode != draft.mode
+            || self.time_limit != new_duration
+            || self.word_gen.lengths() != (draft.min_length, draft.max_length)
+            || self.modifiers.numbers != draft.numbers
+            || self.modifiers.punctuation != draft.punctuation
+            || loaded.is_some()
+        {
+            self.lang = draft.lang;
+            self.mode = draft.mode;
+            self.words_limit = new_limit;
+            self.time_limit = new_duration;
+            self.word_gen.mode = draft.word_mode;
+            self.word_gen.curve = draft.curve;
+            self.word_gen
+                .set_lengths(draft.min_length, draft.max_length);
+            self.modifiers.numbers = draft.numbers;
+            self.modifiers.punctuation = draft.punctuation;
+            self.pack = draft.pack.clone();
+            self.word_gen.set_pack(self.pack.as_deref());
+            if let Some(loaded) = loaded {
+                self.word_gen.set_wordlist(loaded);
+                self.wordlist = wordlist;
+            }
+            self.settings_changed = true;
+        }
+        self.cue = draft.cue;
+        self.sound = draft.sound;
+        self.volume = draft.volume;
+        self.show_cohort = draft.cohort;
+        self.ghost_race = draft.ghost;
+        self.forgive_rollover = draft.forgive_rollover;
+        self.strict = draft.strict;
+        self.pace_wpm = draft.pace;
+        self.reveal = draft.reveal;
+        self.upcoming = draft.upcoming;
+        self.theme_config.preset = draft.theme;
+        self.theme = self.theme_config.resolve();
+        self.keys = draft.keys.clone();
+        Ok(())
+    }
+
+    /// Writes the applied settings to the config file as the defaults of
+    /// later sessions.
+    pub(crate) fn save_settings(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = self.config_file.reload()?.clone();
+        config.lang = lang_code(self.lang);
+        config.mode = self.mode;
+        config.limit = self.words_limit;
+        config.time_limit = self.time_limit.as_secs();
+        config.word_mode = self.word_gen.mode;
+        config.curve = self.word_gen.curve;
+        (config.min_length, config.max_length) = self.word_gen.lengths();
+        config.pack = self.pack.clone();
+        config.wordlist = self.wordlist.clone();
+        config.cue = self.cue;
+        config.sound = self.sound;
+        config.volume = self.volume;
+        config.cohort = self.show_cohort;
+        config.ghost = self.ghost_race;
+        config.forgive_rollover = self.forgive_rollover;
+        config.strict = self.strict;
+        config.numbers = self.modifiers.numbers;
+        config.punctuation = self.modifiers.punctuation;
+        config.pace_wpm = self.pace_wpm;
+        config.reveal = self.reveal;
+        config.upcoming = self.upcoming;
+        config.theme.preset = self.theme_config.preset;
+        config.keys = self.keys.clone();
+        self.config_file.save(config)
+    }
+
+    pub(crate) fn record_key(&mut self, code: KeyCode) {
+        let SettingItem::Key(action) = self.selected_setting else {
+            return;
+        };
+        let mut keys = self.editor.draft.keys.clone();
+        match keys.bind(action, code) {
+            Ok(()) => {
+                self.settings_notice = None;
+                self.editor.edit(|draft| draft.keys = keys);
+            }
+            Err(err) => self.settings_notice = Some(err),
+        }
+    }
+
+    /// Settings currently listed in the popup, narrowed by the search query.
+    pub(crate) fn visible_settings(&self) -> Vec<SettingItem> {
+        SettingItem::matching(self.settings_search.as_deref().unwrap_or_default())
+    }
+
+    pub(crate) fn move_setting(&mut self, forward: bool) {
+        self.settings_notice = None;
+        let visible = self.visible_settings();
+        if visible.is_empty() {
+            return;
+        }
+        let next = match visible
+            .iter()
+            .position(|&item| item == self.selected_setting)
+        {
+            Some(i) if forward => (i + 1) % visible.len(),
+            Some(i) => (i + visible.len() - 1) % visible.len(),
+            None => 0,
+        };
+        self.selected_setting = visible[next];
+    }
+
+    /// Selects the first setting of the next or previous page.
+    pub(crate) fn move_page(&mut self, forward: bool) {
+        self.settings_notice = None;
+        let pages = PAGES.len();
+        let page = self.selected_setting.page();
+        let page = ternary!(forward, page + 1, page + pages - 1) % pages;
+        if let Some(&item) = SettingItem::on_page(page).first() {
+            self.selected_setting = item;
+        }
+    }
+
+    /// Selects the setting drawn at row `y` of the screen, if any.
+    pub(crate) fn click_setting(&mut self, x: u16, y: u16) {
+        if !self.settings_area.contains(Position::new(x, y)) {
+            return;
+        }
+        let row = (y - self.settings_area.y) as usize + self.settings_offset;
+        if let Some(&item) = self.visible_settings().get(row) {
+            self.settings_notice = None;
+            self.recording = false;
+            self.selected_setting = item;
+        }
+    }
+
+    /// Jumps to the first setting matching the search query.
+    pub(crate) fn jump_to_match(&mut self) {
+        if let Some(&item) = self.visible_settings().first() {
+            self.selected_setting = item;
+        }
+    }
+}
+
+pub(crate) struct Word {
+    pub(crate) word: String,
+    pub(crate) wrong_chars: HashSet<usize>,
+    /// Test time at which the word was completed.
+    pub(crate) finished_at: Duration,
+    /// Rollover typos that were forgiven instead of marking the word wrong.
+    pub(crate) forgiven: usize,
+    pub(crate) lang: Lang,
+}
+
+impl From<&str> for Word {
+    fn from(value: &str) -> Self {
+        Self {
+            word: value.to_string(),
+            wrong_chars: HashSet::new(),
+            finished_at: Duration::ZERO,
+            forgiven: 0,
+            lang: Lang::En,
+        }
+    }
+}
+
+/// Upper and lower case share a key.
+fn key(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+pub(crate) const PUBLISH_INTERVAL: Duration = Duration::from_millis(500);
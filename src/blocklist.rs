@@ -0,0 +1,55 @@
+use std::{fs, path::Path};
+
+/// Words kept out of every test, read from a file with one word or pattern
+/// per line. `*` stands for any run of characters, so `damn*` also blocks
+/// "damnation"; matching ignores case. Blank lines and `#` comments are skipped.
+#[derive(Default, Clone)]
+pub struct Blocklist {
+    patterns: Vec<String>,
+}
+
+impl Blocklist {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read blocklist {}: {}", path.display(), err))?;
+        let patterns = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect();
+        Ok(Self { patterns })
+    }
+
+    pub fn blocks(&self, word: &str) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+        // Punctuation around words of quotes and pasted text doesn't hide them.
+        let word = word
+            .trim_matches(|ch: char| !ch.is_alphanumeric())
+            .to_lowercase();
+        self.patterns.iter().any(|pattern| matches(pattern, &word))
+    }
+}
+
+/// Whether `word` matches `pattern` as a whole, `*` matching anything.
+fn matches(pattern: &str, word: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = word.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::config::project_dirs;
+
+pub type Words = &'static [&'static str];
+
+type Build = Box<dyn FnOnce() -> Vec<&'static str> + Send>;
+
+/// An expensive word list, identified by what it is derived from.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Source {
+    pub name: String,
+    /// Size of the input the list is derived from, so stale disk entries are
+    /// rebuilt when a pack is reinstalled with different contents.
+    pub input_len: usize,
+}
+
+impl Source {
+    fn path(&self) -> Option<PathBuf> {
+        project_dirs().map(|dirs| {
+            dirs.cache_dir().join("words").join(format!(
+                "{}-{}-{}.txt",
+                self.name,
+                env!("CARGO_PKG_VERSION"),
+                self.input_len
+            ))
+        })
+    }
+
+    fn read(&self) -> Option<Words> {
+        let text = fs::read_to_string(self.path()?).ok()?;
+        let words: Vec<&'static str> = Box::leak(text.into_boxed_str()).lines().collect();
+        (!words.is_empty()).then(|| &*Box::leak(words.into_boxed_slice()))
+    }
+
+    fn write(&self, words: Words) {
+        if let Some(path) = self.path()
+            && let Some(dir) = path.parent()
+            && fs::create_dir_all(dir).is_ok()
+        {
+            let _ = fs::write(path, words.join("\n"));
+        }
+    }
+
+    /// Disk cache first, then a full build that is written back for next time.
+    fn load(&self, build: Build) -> Words {
+        if let Some(words) = self.read() {
+            return words;
+        }
+        let words: Words = Box::leak(build().into_boxed_slice());
+        self.write(words);
+        words
+    }
+}
+
+/// Memory and disk cache of expensive word lists, filled by a worker thread
+/// so the UI never waits on a build.
+pub struct SourceCache {
+    memory: HashMap<Source, Words>,
+    pending: HashSet<Source>,
+    requests: Sender<(Source, Build)>,
+    loaded: Receiver<(Source, Words)>,
+}
+
+impl Default for SourceCache {
+    fn default() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<(Source, Build)>();
+        let (loaded_tx, loaded_rx) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok((source, build)) = request_rx.recv() {
+                let words = source.load(build);
+                if loaded_tx.send((source, words)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            memory: HashMap::new(),
+            pending: HashSet::new(),
+            requests: request_tx,
+            loaded: loaded_rx,
+        }
+    }
+}
+
+impl SourceCache {
+    /// The list if it is ready. Otherwise it is queued for the worker, which
+    /// reads it from disk or runs `build`, and `None` is returned meanwhile.
+    pub fn get(
+        &mut self,
+        source: &Source,
+        build: impl FnOnce() -> Vec<&'static str> + Send + 'static,
+    ) -> Option<Words> {
+        while let Ok((source, words)) = self.loaded.try_recv() {
+            self.pending.remove(&source);
+            self.memory.insert(source, words);
+        }
+        if let Some(words) = self.memory.get(source) {
+            return Some(words);
+        }
+        if !self.pending.contains(source)
+            && self
+                .requests
+                .send((source.clone(), Box::new(build)))
+                .is_ok()
+        {
+            self.pending.insert(source.clone());
+        }
+        None
+    }
+
+    /// The list, blocking until it is ready: the worker's build if it was
+    /// queued already, otherwise one run on this thread.
+    pub fn wait(
+        &mut self,
+        source: &Source,
+        build: impl FnOnce() -> Vec<&'static str> + Send + 'static,
+    ) -> Words {
+        while let Ok((source, words)) = self.loaded.try_recv() {
+            self.pending.remove(&source);
+            self.memory.insert(source, words);
+        }
+        if let Some(words) = self.memory.get(source) {
+            return words;
+        }
+        if self.pending.contains(source) {
+            while let Ok((loaded, words)) = self.loaded.recv() {
+                self.pending.remove(&loaded);
+                self.memory.insert(loaded.clone(), words);
+                if loaded == *source {
+                    return words;
+                }
+            }
+        }
+        let words = source.load(Box::new(build));
+        self.memory.insert(source.clone(), words);
+        words
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+
+use crate::history::Entry;
+
+/// Weeks shown, ending with the current one.
+pub const WEEKS: usize = 26;
+/// Shades of activity, from a single test to the busiest days.
+pub const LEVELS: usize = 4;
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Local day of a Unix time in seconds.
+pub fn local_day(timestamp: u64) -> Option<NaiveDate> {
+    Local
+        .timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|time| time.date_naive())
+}
+
+/// Tests per day over the last [`WEEKS`] weeks, a column per week starting
+/// on Monday.
+#[derive(Default)]
+pub struct Calendar {
+    /// Monday of each week, oldest first.
+    pub starts: Vec<NaiveDate>,
+    /// Tests by week and weekday, `None` for days still to come.
+    pub days: Vec<[Option<u32>; 7]>,
+    pub busiest: u32,
+}
+
+impl Calendar {
+    pub fn new(entries: &[Entry], today: NaiveDate) -> Self {
+        let mut counts: HashMap<NaiveDate, u32> = HashMap::new();
+        for day in entries
+            .iter()
+            .filter_map(|entry| local_day(entry.timestamp))
+        {
+            *counts.entry(day).or_default() += 1;
+        }
+
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let starts: Vec<NaiveDate> = (0..WEEKS)
+            .rev()
+            .map(|week| monday - Duration::weeks(week as i64))
+            .collect();
+        let days: Vec<[Option<u32>; 7]> = starts
+            .iter()
+            .map(|start| {
+                std::array::from_fn(|weekday| {
+                    let day = *start + Duration::days(weekday as i64);
+                    (day <= today).then(|| counts.get(&day).copied().unwrap_or(0))
+                })
+            })
+            .collect();
+        let busiest = days.iter().flatten().flatten().copied().max().unwrap_or(0);
+        Self {
+            starts,
+            days,
+            busiest,
+        }
+    }
+
+    /// Shade of a day with `tests` tests, 0 for none and up to [`LEVELS`].
+    pub fn level(&self, tests: u32) -> usize {
+        if tests == 0 || self.busiest == 0 {
+            return 0;
+        }
+        (tests as usize * LEVELS).div_ceil(self.busiest as usize)
+    }
+}
+
+/// Consecutive days with at least one saved test.
+#[derive(Default, Clone, Copy)]
+pub struct Streak {
+    /// Days in a row up to the last test.
+    run: u32,
+    pub longest: u32,
+    last: Option<NaiveDate>,
+}
+
+impl Streak {
+    pub fn new(entries: &[Entry]) -> Self {
+        let mut days: Vec<NaiveDate> = entries
+            .iter()
+            .filter_map(|entry| local_day(entry.timestamp))
+            .collect();
+        days.sort_unstable();
+        let mut streak = Self::default();
+        for day in days {
+            streak.record(day);
+        }
+        streak
+    }
+
+    /// Counts a test on `day`, which is never before the last one recorded.
+    pub fn record(&mut self, day: NaiveDate) {
+        match self.last {
+            Some(last) if day <= last => return,
+            Some(last) if day == last + Duration::days(1) => self.run += 1,
+            _ => self.run = 1,
+        }
+        self.last = Some(day);
+        self.longest = self.longest.max(self.run);
+    }
+
+    /// Days in the streak on `today`, which is kept until a whole day passes
+    /// without a test.
+    pub fn current(&self, today: NaiveDate) -> u32 {
+        match self.last {
+            Some(last) if today - last <= Duration::days(1) => self.run,
+            _ => 0,
+        }
+    }
+
+    pub fn label(&self, today: NaiveDate) -> String {
+        format!(
+            "{}-day streak, longest {}",
+            self.current(today),
+            self.longest
+        )
+    }
+}
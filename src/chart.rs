@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    symbols::Marker,
+    widgets::{Axis, BorderType, Chart, Dataset, GraphType, Widget},
+};
+
+use crate::{clock, theme::Theme};
+
+/// WPM over the course of a test: the running average, and the speed within
+/// each second underneath it.
+pub struct WpmChart<'a> {
+    /// Correct characters typed by the end of each second.
+    timeline: &'a [usize],
+    /// Length of the test and the correct characters at its end.
+    end: (Duration, usize),
+    theme: Theme,
+}
+
+impl<'a> WpmChart<'a> {
+    pub fn new(timeline: &'a [usize], end: (Duration, usize), theme: Theme) -> Self {
+        Self {
+            timeline,
+            end,
+            theme,
+        }
+    }
+}
+
+/// WPM of `chars` characters typed over the first `seconds` seconds.
+fn wpm(chars: usize, seconds: usize) -> f64 {
+    clock::wpm(chars, Duration::from_secs(seconds as u64)) as f64
+}
+
+impl Widget for WpmChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (end, total) = self.end;
+        let mut average: Vec<(f64, f64)> = self
+            .timeline
+            .iter()
+            .enumerate()
+            .map(|(i, &chars)| ((i + 1) as f64, wpm(chars, i + 1)))
+            .collect();
+        if end > Duration::from_secs(self.timeline.len() as u64) {
+            average.push((end.as_secs_f64(), clock::wpm(total, end) as f64));
+        }
+        let per_second: Vec<(f64, f64)> = self
+            .timeline
+            .iter()
+            .enumerate()
+            .map(|(i, &chars)| {
+                let before = i.checked_sub(1).map_or(0, |i| self.timeline[i]);
+                ((i + 1) as f64, wpm(chars.saturating_sub(before), 1))
+            })
+            .collect();
+
+        let top = average
+            .iter()
+            .chain(&per_second)
+            .map(|&(_, wpm)| wpm)
+            .fold(0.0, f64::max)
+            .max(10.0)
+            .ceil();
+        let right = end.as_secs_f64().max(1.0);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("per second")
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(Style::new().fg(self.theme.dim))
+                .data(&per_second),
+            Dataset::default()
+                .name("wpm")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::new().fg(self.theme.highlight))
+                .data(&average),
+        ];
+
+        Chart::new(datasets)
+            .block(
+                self.theme
+                    .block()
+                    .title("WPM")
+                    .border_type(BorderType::Rounded),
+            )
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, right])
+                    .labels(["0s".to_string(), format!("{:.0}s", right)])
+                    .style(Style::new().fg(self.theme.dim)),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, top])
+                    .labels(["0".to_string(), format!("{:.0}", top)])
+                    .style(Style::new().fg(self.theme.dim)),
+            )
+            .render(area, buf);
+    }
+}
+
+/// Accuracy of the last saved runs, oldest on the left.
+pub struct AccuracyChart<'a> {
+    accuracy: &'a [f32],
+    theme: Theme,
+}
+
+impl<'a> AccuracyChart<'a> {
+    pub fn new(accuracy: &'a [f32], theme: Theme) -> Self {
+        Self { accuracy, theme }
+    }
+}
+
+impl Widget for AccuracyChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let points: Vec<(f64, f64)> = self
+            .accuracy
+            .iter()
+            .enumerate()
+            .map(|(i, &accuracy)| ((i + 1) as f64, accuracy as f64))
+            .collect();
+        // Down to the nearest ten below the worst run, so small changes show.
+        let bottom = points
+            .iter()
+            .map(|&(_, accuracy)| accuracy)
+            .fold(100.0, f64::min)
+            .div_euclid(10.0)
+            .min(9.0)
+            * 10.0;
+        let right = (points.len() as f64).max(2.0);
+
+        let datasets = vec![
+            Dataset::default()
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(Style::new().fg(self.theme.dim))
+                .data(&points),
+            Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::new().fg(self.theme.highlight))
+                .data(&points),
+        ];
+
+        Chart::new(datasets)
+            .block(
+                self.theme
+                    .block()
+                    .title(format!("Accuracy - last {} runs", points.len()))
+                    .border_type(BorderType::Rounded),
+            )
+            .x_axis(
+                Axis::default()
+                    .bounds([1.0, right])
+                    .labels(["oldest", "latest"])
+                    .style(Style::new().fg(self.theme.dim)),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([bottom, 100.0])
+                    .labels([format!("{:.0}%", bottom), "100%".to_string()])
+                    .style(Style::new().fg(self.theme.dim)),
+            )
+            .render(area, buf);
+    }
+}
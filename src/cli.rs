@@ -0,0 +1,307 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use regex::Regex;
+
+use crate::{
+    config::{CONFIG, Config, TestMode, get_lang},
+    lan, monkeytype, packs, profile, report,
+    score::{Formula, Leaderboard, SHOWN},
+    snippets::CodeLang,
+    telemetry::Usage,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Terminal typing trainer")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Word language for this session (RU, DE, ES, FR, JA, ZH or EN), overriding `lang` from the config
+    #[arg(long, value_name = "CODE", value_parser = parse_lang)]
+    pub lang: Option<String>,
+    /// Words per test for this session, overriding `limit` from the config
+    #[arg(long, value_name = "N", value_parser = parse_limit)]
+    pub limit: Option<usize>,
+    /// Run timed tests of this many seconds for this session instead of counting words
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub time: Option<u64>,
+    /// Only type words made of these characters for this session, e.g. "asdfjkl;"
+    #[arg(long, value_name = "CHARS")]
+    pub charset: Option<String>,
+    /// Only type words matching this regular expression for this session, e.g. "^[a-m]+$"
+    #[arg(long, value_name = "REGEX", value_parser = parse_pattern)]
+    pub pattern: Option<String>,
+    /// Focus gate: start a test right away and exit with 0 only once one meets the
+    /// gate_wpm and gate_accuracy targets, 1 otherwise
+    #[arg(long)]
+    pub gate: bool,
+    /// WPM a focus gate test has to reach, overriding `gate_wpm` from the config
+    #[arg(long, value_name = "WPM", requires = "gate")]
+    pub min_wpm: Option<f32>,
+    /// Accuracy in percent a focus gate test has to reach, overriding `gate_accuracy` from the config
+    #[arg(long, value_name = "PERCENT", requires = "gate")]
+    pub min_accuracy: Option<f32>,
+    /// Type words from this file instead of the dictionary, overriding `wordlist` from the config
+    #[arg(long, value_name = "PATH")]
+    pub wordlist: Option<PathBuf>,
+    /// Type the sentences of this text<TAB>translation file in parallel mode,
+    /// overriding `corpus` from the config
+    #[arg(long, value_name = "PATH")]
+    pub corpus: Option<PathBuf>,
+    /// Pick the words of every test from this phrase, e.g. "banana42", so anyone
+    /// using it types the same words, or "daily" for today's words, overriding
+    /// `seed` from the config
+    #[arg(long, value_name = "PHRASE")]
+    pub seed: Option<String>,
+    /// Type code snippets in this language (rust, python or js) for this session
+    #[arg(long, value_name = "LANG", value_parser = parse_code_lang)]
+    pub code: Option<CodeLang>,
+    /// Host a race on the LAN, on PORT or 7878, and show the code players join with
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "7878", conflicts_with = "join")]
+    pub host: Option<u16>,
+    /// Join a LAN race by the code its host shows, e.g. 61GK2-0FMB8
+    #[arg(long, value_name = "CODE", value_parser = parse_code)]
+    pub join: Option<String>,
+    /// Run N simulated tests headless and report memory and write timings
+    #[arg(long, value_name = "N", hide = true)]
+    pub soak: Option<usize>,
+}
+
+impl Cli {
+    /// Merges the one-off options over `config`, leaving the file untouched.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(lang) = &self.lang {
+            config.lang = lang.clone();
+        }
+        if let Some(limit) = self.limit {
+            config.limit = limit;
+        }
+        if let Some(seconds) = self.time {
+            config.mode = TestMode::Time;
+            config.time_limit = seconds;
+        }
+        if let Some(charset) = &self.charset {
+            config.charset = Some(charset.clone());
+        }
+        if let Some(pattern) = &self.pattern {
+            config.pattern = Some(pattern.clone());
+        }
+        if let Some(wpm) = self.min_wpm {
+            config.gate_wpm = wpm;
+        }
+        if let Some(accuracy) = self.min_accuracy {
+            config.gate_accuracy = accuracy;
+        }
+        if let Some(path) = &self.wordlist {
+            config.wordlist = Some(path.clone());
+        }
+        if let Some(seed) = &self.seed {
+            config.seed = Some(seed.clone());
+        }
+        if let Some(lang) = self.code {
+            config.mode = TestMode::Code;
+            config.code_lang = lang;
+        }
+        if let Some(path) = &self.corpus {
+            config.mode = TestMode::Parallel;
+            config.corpus = Some(path.clone());
+        }
+    }
+}
+
+fn parse_lang(lang: &str) -> Result<String, String> {
+    get_lang(lang)
+        .map(|_| lang.to_uppercase())
+        .ok_or_else(|| "expected one of RU, DE, ES, FR, JA, ZH, EN".to_string())
+}
+
+fn parse_code_lang(lang: &str) -> Result<CodeLang, String> {
+    CodeLang::from_name(lang).ok_or_else(|| "expected one of rust, python, js".to_string())
+}
+
+fn parse_code(code: &str) -> Result<String, String> {
+    lan::parse_code(code)
+        .map(|_| code.to_uppercase())
+        .ok_or_else(|| "not a race code".to_string())
+}
+
+fn parse_pattern(pattern: &str) -> Result<String, String> {
+    Regex::new(pattern)
+        .map(|_| pattern.to_string())
+        .map_err(|err| err.to_string())
+}
+
+fn parse_limit(limit: &str) -> Result<usize, String> {
+    match limit.parse() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(limit) => Ok(limit),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Bundle config, wordlists, history and other app data into a .tar.gz archive
+    ExportProfile { path: PathBuf },
+    /// Restore a profile archive created by export-profile, backing up the files it replaces
+    ImportProfile { path: PathBuf },
+    /// Write a Markdown summary of recent training
+    Report {
+        /// Period to cover, e.g. 30d, 2w or 12h
+        #[arg(long, default_value = "30d")]
+        since: String,
+        /// Output file, printed to stdout when omitted
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Export history as weekly aggregates without timestamps or text, safe to share
+    ExportAggregate { path: PathBuf },
+    /// Export history as monkeytype results JSON, for tools and dashboards that read it
+    ExportMonkeytype { path: PathBuf },
+    /// Add the results of a monkeytype JSON export to the history, skipping runs already there
+    ImportMonkeytype { path: PathBuf },
+    /// Print the best runs, ranked by the `score` formula from the config or by WPM
+    Leaderboard {
+        /// Places to show
+        #[arg(long, default_value_t = SHOWN)]
+        top: usize,
+    },
+    /// Manage downloadable language packs
+    Lang {
+        #[command(subcommand)]
+        action: LangCommand,
+    },
+    /// Opt-in anonymous usage counts
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TelemetryCommand {
+    /// Print exactly what would be sent when quitting
+    Preview,
+}
+
+#[derive(Subcommand)]
+pub enum LangCommand {
+    /// Download a pack from the index and verify its checksum
+    Install {
+        code: String,
+        /// Index URL, overriding `pack_index` from the config
+        #[arg(long)]
+        index: Option<String>,
+    },
+    /// Show packs available in the index and whether they are installed
+    List {
+        #[arg(long)]
+        index: Option<String>,
+    },
+}
+
+fn index_url(index: Option<String>) -> Result<String, Box<dyn std::error::Error>> {
+    index
+        .or_else(|| CONFIG.pack_index.clone())
+        .ok_or_else(|| "No language pack index configured: set pack_index or pass --index".into())
+}
+
+pub fn execute(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::ExportProfile { path } => {
+            profile::export(&path)?;
+            println!("Profile exported to {}", path.display());
+        }
+        Command::ImportProfile { path } => {
+            let backup = profile::import(&path)?;
+            println!(
+                "Profile imported from {}, the previous files are kept in {}",
+                path.display(),
+                backup.display()
+            );
+        }
+        Command::Report { since, out } => {
+            let report = report::generate(&since, report::parse_since(&since)?);
+            match out {
+                Some(path) => {
+                    std::fs::write(&path, report)?;
+                    println!("Report written to {}", path.display());
+                }
+                None => print!("{}", report),
+            }
+        }
+        Command::ExportAggregate { path } => {
+            let buckets = report::aggregate();
+            std::fs::write(&path, serde_json::to_string_pretty(&buckets)?)?;
+            println!(
+                "Exported {} aggregate rows to {}",
+                buckets.len(),
+                path.display()
+            );
+        }
+        Command::ExportMonkeytype { path } => {
+            let count = monkeytype::export(&path)?;
+            println!("Exported {} results to {}", count, path.display());
+        }
+        Command::ImportMonkeytype { path } => {
+            let count = monkeytype::import(&path)?;
+            println!("Imported {} results from {}", count, path.display());
+        }
+        Command::Leaderboard { top } => {
+            let formula = CONFIG
+                .score
+                .as_deref()
+                .map(Formula::parse)
+                .transpose()
+                .map_err(|err| format!("Invalid score in the config: {}", err))?;
+            let ranked = Leaderboard::load().ranked(formula.as_ref());
+            if ranked.is_empty() {
+                println!("No runs on the leaderboard yet");
+            }
+            for (place, (score, run)) in ranked.iter().take(top).enumerate() {
+                println!(
+                    "{:>2}. {:>8.1}  {:.0} wpm  {:.1}%  {} errors  {:.1}s",
+                    place + 1,
+                    score,
+                    run.wpm,
+                    run.acc * 100.0,
+                    run.errors,
+                    run.seconds
+                );
+            }
+        }
+        Command::Lang {
+            action: LangCommand::Install { code, index },
+        } => {
+            let pack = packs::install(&code, &index_url(index)?)?;
+            println!("Installed {} pack {} ({})", pack.kind, pack.code, pack.name);
+        }
+        Command::Lang {
+            action: LangCommand::List { index },
+        } => {
+            for pack in packs::fetch_index(&index_url(index)?)? {
+                let installed = packs::installed_kind(&pack.kind);
+                let mark = if installed.contains(&pack.code.to_lowercase()) {
+                    " (installed)"
+                } else {
+                    ""
+                };
+                println!("{}\t{}\t{}{}", pack.code, pack.kind, pack.name, mark);
+            }
+        }
+        Command::Telemetry {
+            action: TelemetryCommand::Preview,
+        } => {
+            let status = match (CONFIG.telemetry, &CONFIG.telemetry_url) {
+                (true, Some(url)) => format!("Telemetry is on, sending to {}", url),
+                (true, None) => "Telemetry is on, but no telemetry_url is set".to_string(),
+                (false, _) => {
+                    "Telemetry is off: nothing is sent unless telemetry = true".to_string()
+                }
+            };
+            println!("{}", status);
+            println!("{}", serde_json::to_string_pretty(&Usage::load().report())?);
+        }
+    }
+    Ok(())
+}
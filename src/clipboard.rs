@@ -0,0 +1,26 @@
+use std::process::Command;
+
+/// Reads the system clipboard through the platform's command line tools.
+pub fn read() -> Result<String, Box<dyn std::error::Error>> {
+    let candidates: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbpaste", &[])]
+    } else if cfg!(windows) {
+        &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])]
+    } else {
+        &[
+            ("wl-paste", &["--no-newline"]),
+            ("xclip", &["-selection", "clipboard", "-o"]),
+            ("xsel", &["--clipboard", "--output"]),
+        ]
+    };
+
+    for (program, args) in candidates {
+        // Missing tools and tools without a display to talk to both fail here.
+        if let Ok(output) = Command::new(program).args(*args).output()
+            && output.status.success()
+        {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    Err("no clipboard tool available".into())
+}
@@ -0,0 +1,283 @@
+use std::time::{Duration, Instant};
+
+/// Longest expected gap between two observations of a running clock. Ticks
+/// arrive at least every half second, so a longer silence means the process
+/// was suspended (Ctrl-Z, a sleeping laptop) and the gap is left out.
+pub const MAX_STALL: Duration = Duration::from_secs(5);
+
+/// Test time: runs from the first keystroke, stops while paused or held
+/// between words, and keeps a split for the word being typed.
+#[derive(Debug, Default, Clone)]
+pub struct TestClock {
+    /// When the test would have started had it never stopped; moved forward
+    /// by every pause.
+    start: Option<Instant>,
+    /// Paused by the player.
+    paused: Option<Instant>,
+    /// Held between words while only words are timed.
+    held: Option<Instant>,
+    /// Test time when the current word began.
+    split: Duration,
+    /// Last time the running clock was observed, to catch suspends.
+    seen: Option<Instant>,
+    finished: Option<Duration>,
+}
+
+impl TestClock {
+    pub fn start(&mut self, at: Instant) {
+        *self = Self {
+            start: Some(at),
+            seen: Some(at),
+            ..Self::default()
+        };
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+            && self.paused.is_none()
+            && self.held.is_none()
+            && self.finished.is_none()
+    }
+
+    /// Stops the clock until [`resume`](Self::resume). Does nothing before the
+    /// test starts.
+    pub fn pause(&mut self, at: Instant) {
+        if self.start.is_some() && self.paused.is_none() {
+            self.paused = Some(at);
+        }
+    }
+
+    /// Restarts a paused clock. Returns how long it was paused.
+    pub fn resume(&mut self, at: Instant) -> Duration {
+        let Some(paused) = self.paused.take() else {
+            return Duration::ZERO;
+        };
+        match self.held {
+            // Still held between words, which now covers the pause as well.
+            Some(held) => self.held = Some(held.min(paused)),
+            None => self.unstop(paused, at),
+        }
+        at.saturating_duration_since(paused)
+    }
+
+    /// Stops the clock between words, until [`release`](Self::release).
+    pub fn hold(&mut self, at: Instant) {
+        if self.start.is_some() && self.held.is_none() {
+            self.held = Some(at);
+        }
+    }
+
+    pub fn release(&mut self, at: Instant) {
+        let Some(held) = self.held.take() else {
+            return;
+        };
+        match self.paused {
+            Some(paused) => self.paused = Some(paused.min(held)),
+            None => self.unstop(held, at),
+        }
+    }
+
+    /// Leaves the time stopped since `since` out of the test.
+    fn unstop(&mut self, since: Instant, at: Instant) {
+        self.shift(at.saturating_duration_since(since));
+        self.seen = Some(at);
+    }
+
+    fn shift(&mut self, by: Duration) {
+        self.start = self
+            .start
+            .map(|start| start.checked_add(by).unwrap_or(start));
+    }
+
+    /// Notes that the clock was looked at `at`. A running clock unobserved for
+    /// longer than [`MAX_STALL`] was suspended, and that gap doesn't count.
+    /// Returns the time left out.
+    pub fn observe(&mut self, at: Instant) -> Duration {
+        if !self.is_running() {
+            return Duration::ZERO;
+        }
+        let gap = self
+            .seen
+            .replace(at)
+            .map_or(Duration::ZERO, |seen| at.saturating_duration_since(seen));
+        if gap <= MAX_STALL {
+            return Duration::ZERO;
+        }
+        self.shift(gap);
+        gap
+    }
+
+    /// Test time up to `at`, frozen while stopped and once finished.
+    pub fn elapsed(&self, at: Instant) -> Duration {
+        if let Some(finished) = self.finished {
+            return finished;
+        }
+        let Some(start) = self.start else {
+            return Duration::ZERO;
+        };
+        let end = [self.paused, self.held, Some(at)]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(at);
+        end.saturating_duration_since(start)
+    }
+
+    /// Ends the current word, returning its time: the word timer.
+    pub fn split(&mut self, at: Instant) -> Duration {
+        let elapsed = self.elapsed(at);
+        let word = elapsed.saturating_sub(self.split);
+        self.split = elapsed;
+        word
+    }
+
+    /// Stops the clock for good, at no more than `limit`.
+    pub fn finish(&mut self, at: Instant, limit: Option<Duration>) -> Duration {
+        let elapsed = self.elapsed(at);
+        let elapsed = limit.map_or(elapsed, |limit| elapsed.min(limit));
+        self.finished = Some(elapsed);
+        elapsed
+    }
+
+    pub fn finished(&self) -> Option<Duration> {
+        self.finished
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Speed of `chars` characters typed in `time`, five characters to a word.
+/// Computed from the exact duration, so short tests don't drift with rounding.
+pub fn wpm(chars: usize, time: Duration) -> f32 {
+    if time.is_zero() {
+        return 0.0;
+    }
+    (chars as f64 / 5.0 * 60.0 / time.as_secs_f64()) as f32
+}
+
+/// Test time to the millisecond, e.g. "9.871s", or "1:23.456" from a minute on.
+pub fn format_time(time: Duration) -> String {
+    let millis = time.as_millis();
+    let (minutes, seconds, millis) = (millis / 60_000, millis / 1000 % 60, millis % 1000);
+    if minutes > 0 {
+        format!("{}:{:02}.{:03}", minutes, seconds, millis)
+    } else {
+        format!("{}.{:03}s", seconds, millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn pause_before_start_is_ignored() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.pause(t0);
+        assert_eq!(clock.resume(t0 + secs(3)), Duration::ZERO);
+        assert_eq!(clock.elapsed(t0 + secs(4)), Duration::ZERO);
+
+        clock.start(t0 + secs(5));
+        assert_eq!(clock.elapsed(t0 + secs(7)), secs(2));
+    }
+
+    #[test]
+    fn multiple_pauses_are_left_out() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.start(t0);
+        clock.pause(t0 + secs(2));
+        assert_eq!(clock.elapsed(t0 + secs(10)), secs(2));
+        assert_eq!(clock.resume(t0 + secs(12)), secs(10));
+        clock.pause(t0 + secs(13));
+        // Pausing twice keeps the first pause.
+        clock.pause(t0 + secs(14));
+        clock.resume(t0 + secs(20));
+        assert_eq!(clock.elapsed(t0 + secs(21)), secs(4));
+    }
+
+    #[test]
+    fn pause_while_held_counts_once() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.start(t0);
+        clock.hold(t0 + secs(1));
+        clock.pause(t0 + secs(2));
+        clock.resume(t0 + secs(5));
+        // Still held until the next word starts.
+        assert_eq!(clock.elapsed(t0 + secs(6)), secs(1));
+        clock.release(t0 + secs(8));
+        assert_eq!(clock.elapsed(t0 + secs(9)), secs(2));
+    }
+
+    #[test]
+    fn suspend_is_left_out() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.start(t0);
+        assert_eq!(clock.observe(t0 + secs(1)), Duration::ZERO);
+        assert_eq!(clock.observe(t0 + secs(2)), Duration::ZERO);
+        // Nothing observed the clock for a minute.
+        assert_eq!(clock.observe(t0 + secs(62)), secs(60));
+        assert_eq!(clock.elapsed(t0 + secs(63)), secs(3));
+    }
+
+    #[test]
+    fn resume_after_suspend_does_not_drift() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.start(t0);
+        clock.pause(t0 + secs(1));
+        // A long pause is not a suspend.
+        clock.resume(t0 + secs(60));
+        assert_eq!(clock.observe(t0 + secs(61)), Duration::ZERO);
+        assert_eq!(clock.elapsed(t0 + secs(61)), secs(2));
+    }
+
+    #[test]
+    fn splits_time_each_word() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.start(t0);
+        assert_eq!(clock.split(t0 + secs(2)), secs(2));
+        clock.pause(t0 + secs(3));
+        clock.resume(t0 + secs(10));
+        // The pause is left out of the word it interrupted.
+        assert_eq!(clock.split(t0 + secs(12)), secs(3));
+    }
+
+    #[test]
+    fn finish_freezes_at_the_limit() {
+        let t0 = Instant::now();
+        let mut clock = TestClock::default();
+        clock.start(t0);
+        assert_eq!(clock.finish(t0 + secs(31), Some(secs(30))), secs(30));
+        assert_eq!(clock.elapsed(t0 + secs(40)), secs(30));
+        assert_eq!(clock.observe(t0 + secs(50)), Duration::ZERO);
+    }
+
+    #[test]
+    fn wpm_uses_the_exact_time() {
+        assert_eq!(wpm(50, Duration::ZERO), 0.0);
+        assert_eq!(wpm(50, secs(60)), 10.0);
+        assert_eq!(wpm(25, Duration::from_millis(1500)), 200.0);
+    }
+
+    #[test]
+    fn times_show_milliseconds() {
+        assert_eq!(format_time(Duration::from_millis(9871)), "9.871s");
+        assert_eq!(format_time(Duration::from_millis(83_456)), "1:23.456");
+        assert_eq!(format_time(Duration::from_millis(600_005)), "10:00.005");
+    }
+}
@@ -0,0 +1,54 @@
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Cohort {
+    mode: String,
+    lang: String,
+    /// Largest word limit this distribution applies to, unbounded if missing.
+    max_limit: Option<usize>,
+    percentiles: Vec<(f32, f32)>,
+}
+
+#[derive(Deserialize)]
+struct Cohorts {
+    cohort: Vec<Cohort>,
+}
+
+static COHORTS: Lazy<Vec<Cohort>> = Lazy::new(|| {
+    toml::from_str::<Cohorts>(include_str!("../assets/cohort.toml"))
+        .map(|c| c.cohort)
+        .unwrap_or_default()
+});
+
+/// Percentile of `wpm` among reference typists for the same kind of test,
+/// or `None` when no distribution is bundled for it.
+pub fn percentile(mode: &str, lang: &str, limit: usize, wpm: f32) -> Option<f32> {
+    let cohort = COHORTS
+        .iter()
+        .find(|c| c.mode == mode && c.lang == lang && c.max_limit.is_none_or(|max| limit <= max))?;
+    let points = &cohort.percentiles;
+    let (first, last) = (points.first()?, points.last()?);
+
+    if wpm <= first.1 {
+        return Some(first.0 * (wpm / first.1).max(0.0));
+    }
+    if wpm >= last.1 {
+        return Some(last.0);
+    }
+    points.windows(2).find_map(|pair| {
+        let ((p0, w0), (p1, w1)) = (pair[0], pair[1]);
+        (wpm <= w1).then(|| p0 + (p1 - p0) * (wpm - w0) / (w1 - w0))
+    })
+}
+
+pub fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
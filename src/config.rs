@@ -0,0 +1,491 @@
+use std::{
+    fs::{self},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use directories_next::ProjectDirs;
+use once_cell::sync::Lazy;
+use random_word::Lang;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    export::ExportFormat,
+    governor::GovernorMode,
+    keys::Keymap,
+    metrics::{self, MetricKind},
+    plan::Step,
+    quiet::QuietHours,
+    quotes::QuoteLength,
+    snippets::CodeLang,
+    theme::ThemeConfig,
+    words::{Joiners, WordMode},
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Cue {
+    Off,
+    /// Input border flashes green or red when a word is completed.
+    #[default]
+    Pulse,
+    /// Pulse plus a terminal bell on mistyped words.
+    Bell,
+}
+
+/// What happens to a partly typed word when the test is paused.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum PauseWord {
+    /// The word is frozen as typed, mistakes included, and continued on resume.
+    #[default]
+    Keep,
+    /// The word is typed again from its start on resume.
+    Clear,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TestMode {
+    /// The test ends once `limit` words are typed.
+    #[default]
+    Words,
+    /// The test ends `time_limit` seconds after the first keystroke.
+    Time,
+    /// A bundled quote is typed to its end.
+    Quote,
+    /// A bundled snippet of `code_lang` is typed line by line, Enter and
+    /// Tab included.
+    Code,
+    /// Sentences are typed with their translation shown underneath, until
+    /// `limit` words are typed.
+    Parallel,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RenderProfile {
+    /// Low-bandwidth inside SSH sessions, full otherwise.
+    #[default]
+    Auto,
+    Full,
+    /// Fewer redraws, no animations and plain styling for remote terminals.
+    Low,
+}
+
+impl RenderProfile {
+    pub(crate) fn is_low(self) -> bool {
+        match self {
+            RenderProfile::Auto => ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+                .iter()
+                .any(|var| std::env::var_os(var).is_some()),
+            RenderProfile::Full => false,
+            RenderProfile::Low => true,
+        }
+    }
+}
+
+pub(crate) fn get_lang(lang: &str) -> Option<Lang> {
+    match lang.to_uppercase().as_str() {
+        "RU" => Some(Lang::Ru),
+        "DE" => Some(Lang::De),
+        "ES" => Some(Lang::Es),
+        "FR" => Some(Lang::Fr),
+        "JA" => Some(Lang::Ja),
+        "ZH" => Some(Lang::Zh),
+        "EN" => Some(Lang::En),
+        _ => None,
+    }
+}
+
+pub(crate) fn lang_code(lang: Lang) -> String {
+    format!("{:?}", lang).to_uppercase()
+}
+
+pub(crate) fn next_lang(lang: Lang) -> Lang {
+    match lang {
+        Lang::En => Lang::Ru,
+        Lang::Ru => Lang::De,
+        Lang::De => Lang::Es,
+        Lang::Es => Lang::Fr,
+        Lang::Fr => Lang::Ja,
+        Lang::Ja => Lang::Zh,
+        Lang::Zh => Lang::En,
+    }
+}
+
+pub(crate) fn prev_lang(lang: Lang) -> Lang {
+    match lang {
+        Lang::En => Lang::Zh,
+        Lang::Ru => Lang::En,
+        Lang::De => Lang::Ru,
+        Lang::Es => Lang::De,
+        Lang::Fr => Lang::Es,
+        Lang::Ja => Lang::Fr,
+        Lang::Zh => Lang::Ja,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Config {
+    pub(crate) lang: String,
+    pub(crate) limit: usize,
+    #[serde(default)]
+    pub(crate) bots: Vec<u32>,
+    #[serde(default)]
+    pub(crate) server: Option<String>,
+    #[serde(default = "default_nickname")]
+    pub(crate) nickname: String,
+    #[serde(default)]
+    pub(crate) plan: Vec<Step>,
+    #[serde(default)]
+    pub(crate) mode: TestMode,
+    #[serde(default)]
+    pub(crate) quote_length: QuoteLength,
+    #[serde(default)]
+    pub(crate) code_lang: CodeLang,
+    #[serde(default = "default_time_limit")]
+    pub(crate) time_limit: u64,
+    #[serde(default)]
+    pub(crate) word_mode: WordMode,
+    #[serde(default = "default_curve")]
+    pub(crate) curve: f32,
+    #[serde(default)]
+    pub(crate) cue: Cue,
+    #[serde(default)]
+    pub(crate) sound: bool,
+    #[serde(default = "default_volume")]
+    pub(crate) volume: u32,
+    #[serde(default = "default_true")]
+    pub(crate) cohort: bool,
+    #[serde(default)]
+    pub(crate) ghost: bool,
+    #[serde(default)]
+    pub(crate) quiet_hours: Option<QuietHours>,
+    #[serde(default)]
+    pub(crate) max_wpm: u32,
+    #[serde(default)]
+    pub(crate) governor: GovernorMode,
+    #[serde(default)]
+    pub(crate) pack_index: Option<String>,
+    #[serde(default)]
+    pub(crate) pack: Option<String>,
+    #[serde(default)]
+    pub(crate) score: Option<String>,
+    #[serde(default)]
+    pub(crate) wordlist: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) corpus: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) seed: Option<String>,
+    #[serde(default)]
+    pub(crate) blocklist: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) charset: Option<String>,
+    #[serde(default)]
+    pub(crate) pattern: Option<String>,
+    #[serde(default)]
+    pub(crate) min_length: usize,
+    #[serde(default)]
+    pub(crate) max_length: usize,
+    #[serde(default = "default_min_words")]
+    pub(crate) min_words: usize,
+    #[serde(default = "default_min_seconds")]
+    pub(crate) min_seconds: f32,
+    #[serde(default)]
+    pub(crate) render: RenderProfile,
+    #[serde(default)]
+    pub(crate) forgive_rollover: bool,
+    #[serde(default)]
+    pub(crate) cycle: Vec<String>,
+    #[serde(default = "default_cycle_every")]
+    pub(crate) cycle_every: usize,
+    #[serde(default)]
+    pub(crate) joiners: Joiners,
+    #[serde(default)]
+    pub(crate) sentence_mode: bool,
+    #[serde(default)]
+    pub(crate) boundary_pause: bool,
+    #[serde(default = "default_line_words")]
+    pub(crate) line_words: usize,
+    #[serde(default = "default_accuracy_thresholds")]
+    pub(crate) accuracy_thresholds: [f32; 2],
+    #[serde(default = "default_true")]
+    pub(crate) sidebar: bool,
+    #[serde(default)]
+    pub(crate) pace_wpm: u32,
+    #[serde(default)]
+    pub(crate) reveal: u32,
+    #[serde(default = "default_true")]
+    pub(crate) upcoming: bool,
+    #[serde(default)]
+    pub(crate) strict: bool,
+    #[serde(default)]
+    pub(crate) numbers: bool,
+    #[serde(default)]
+    pub(crate) punctuation: bool,
+    #[serde(default)]
+    pub(crate) export_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) export_format: ExportFormat,
+    #[serde(default)]
+    pub(crate) pause_word: PauseWord,
+    #[serde(default = "default_gate_wpm")]
+    pub(crate) gate_wpm: f32,
+    #[serde(default = "default_gate_accuracy")]
+    pub(crate) gate_accuracy: f32,
+    #[serde(default)]
+    pub(crate) telemetry: bool,
+    #[serde(default)]
+    pub(crate) telemetry_url: Option<String>,
+    #[serde(default = "metrics::default_metrics")]
+    pub(crate) metrics: Vec<MetricKind>,
+    /// Left out of the generated file so options appended below the help
+    /// comments don't end up inside the `[keys]` table.
+    #[serde(default, skip_serializing_if = "Keymap::is_default")]
+    pub(crate) keys: Keymap,
+    /// Left out of the generated file for the same reason as `keys`.
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_default")]
+    pub(crate) theme: ThemeConfig,
+}
+
+pub(crate) fn default_line_words() -> usize {
+    8
+}
+
+pub(crate) fn default_accuracy_thresholds() -> [f32; 2] {
+    [98.0, 95.0]
+}
+
+pub(crate) fn default_volume() -> u32 {
+    50
+}
+
+pub(crate) fn default_gate_wpm() -> f32 {
+    40.0
+}
+
+pub(crate) fn default_gate_accuracy() -> f32 {
+    95.0
+}
+
+pub(crate) fn default_time_limit() -> u64 {
+    60
+}
+
+pub(crate) fn default_cycle_every() -> usize {
+    10
+}
+
+pub(crate) fn default_min_words() -> usize {
+    5
+}
+
+pub(crate) fn default_min_seconds() -> f32 {
+    3.0
+}
+
+pub(crate) fn default_true() -> bool {
+    true
+}
+
+pub(crate) fn default_curve() -> f32 {
+    1.0
+}
+
+pub(crate) fn default_nickname() -> String {
+    "player".to_string()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            lang: "EN".to_string(),
+            limit: 50,
+            bots: Vec::new(),
+            server: None,
+            nickname: default_nickname(),
+            plan: Vec::new(),
+            mode: TestMode::default(),
+            quote_length: QuoteLength::default(),
+            code_lang: CodeLang::default(),
+            time_limit: default_time_limit(),
+            word_mode: WordMode::Random,
+            curve: default_curve(),
+            cue: Cue::default(),
+            sound: false,
+            volume: default_volume(),
+            cohort: true,
+            ghost: false,
+            quiet_hours: None,
+            max_wpm: 0,
+            governor: GovernorMode::default(),
+            pack_index: None,
+            pack: None,
+            score: None,
+            wordlist: None,
+            seed: None,
+            corpus: None,
+            blocklist: None,
+            charset: None,
+            pattern: None,
+            min_length: 0,
+            max_length: 0,
+            min_words: default_min_words(),
+            min_seconds: default_min_seconds(),
+            render: RenderProfile::default(),
+            forgive_rollover: false,
+            cycle: Vec::new(),
+            cycle_every: default_cycle_every(),
+            joiners: Joiners::default(),
+            sentence_mode: false,
+            boundary_pause: false,
+            line_words: default_line_words(),
+            accuracy_thresholds: default_accuracy_thresholds(),
+            sidebar: true,
+            pace_wpm: 0,
+            reveal: 0,
+            upcoming: true,
+            strict: false,
+            numbers: false,
+            punctuation: false,
+            export_dir: None,
+            export_format: ExportFormat::default(),
+            pause_word: PauseWord::default(),
+            gate_wpm: default_gate_wpm(),
+            gate_accuracy: default_gate_accuracy(),
+            telemetry: false,
+            telemetry_url: None,
+            metrics: metrics::default_metrics(),
+            keys: Keymap::default(),
+            theme: ThemeConfig::default(),
+        }
+    }
+}
+
+pub(crate) static CONFIG: Lazy<Config> = Lazy::new(|| {
+    get_config().unwrap_or_else(|err| {
+        eprintln!("Failed to load config: {}. Using default.", err);
+        Config::default()
+    })
+});
+
+pub(crate) fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "hdvtdev", "ktapper")
+}
+
+/// Replaces the data directory for the whole process, used by soak runs so
+/// synthetic results never reach the real history.
+pub(crate) static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub(crate) fn data_dir() -> Option<PathBuf> {
+    DATA_DIR
+        .get()
+        .cloned()
+        .or_else(|| project_dirs().map(|dirs| dirs.data_dir().to_path_buf()))
+}
+
+/// Help comments appended to a written config file.
+const HELP: &str = concat!(
+    "# Limit range: 0 < limit <= usize\n",
+    "# Available words languages: \"RU\" \"DE\" \"ES\" \"FR\" \"JA\" \"ZH\" \"EN\" \n",
+    "# This will not affect the language of the interface.\n",
+    "# Bots: WPM of each bot opponent to race against, e.g. [40, 65]\n",
+    "# Server: race relay address, e.g. server = \"192.168.1.10:7878\"\n",
+    "# Plan: practice session steps, e.g. [[plan]] label = \"warm-up\" lang = \"EN\" limit = 10 repeat = 2\n",
+    "# Mode: \"words\" (ends after limit words), \"time\" (ends after time_limit seconds, e.g. 30, 60 or 120), \"quote\" (type a famous quote, quote_length = \"short\", \"medium\", \"long\" or \"all\"), \"parallel\" (type sentences with their translation shown underneath) or \"code\" (type a bundled snippet with its indentation and line breaks, Tab indenting and Enter ending each line, code_lang = \"rust\", \"python\" or \"js\")\n",
+    "# Word mode: \"random\", \"curve\" (words get harder through the test, curve > 1 ramps up later) \"rare\" (vocabulary practice) \"adaptive\" (clean words bring longer and rarer ones, mistakes shorter and common ones) or \"weak\" (words with your most-missed keys over every saved run come up more often)\n",
+    "# Cue on word completion: \"pulse\" (border flash), \"bell\" (flash + bell on mistakes) or \"off\"\n",
+    "# Sound: click on every key and a low tone on mistakes (true/false), at volume 0-100; needs a build with the \"sound\" feature, also set in Settings\n",
+    "# Quiet hours: local time window without sounds or notifications, e.g. quiet_hours = \"22:00-08:00\"\n",
+    "# Cohort: compare results against bundled reference typists (true/false)\n",
+    "# Ghost: race a replay of your fastest run of the same kind of test, kept as its keystrokes are typed (true/false), also set in Settings\n",
+    "# Max WPM: pace limit for accuracy-first training (0 disables), governor = \"warn\" or \"block\" keys over it\n",
+    "# Pack index: URL of the language pack index used by `ktapper lang install <code>`\n",
+    "# Pack: installed word pack to type instead of the built-in dictionary, e.g. pack = \"eo\"\n",
+    "# Score: formula of the headline score shown in the results and ranking the leaderboard, e.g. score = \"wpm * acc^2 - errors\", using wpm, acc (0-1), errors, seconds and words with + - * / ^ and parentheses (without it runs rank by wpm); `ktapper leaderboard` prints the best runs\n",
+    "# Wordlist: whitespace-separated word file typed instead of the dictionary and packs, e.g. wordlist = \"/path/to/words.txt\" (or --wordlist)\n",
+    "# Corpus: text<TAB>translation file, one pair per line, typed in parallel mode instead of the bundled sentences, e.g. corpus = \"/path/to/pairs.tsv\" (or --corpus)\n",
+    "# Seed: phrase picking the words of every test, so everyone using it types the same words, e.g. seed = \"banana42\", or \"daily\" for words that change every day (or --seed)\n",
+    "# Blocklist: file of words kept out of every test, one per line, * matching anything (e.g. damn*), e.g. blocklist = \"/path/to/blocklist.txt\"\n",
+    "# Charset / pattern: only type words made of these characters, e.g. charset = \"asdfjkl;\", or matching a regular expression, e.g. pattern = \"^[a-m]+$\"\n",
+    "# Word length: only type words of min_length to max_length characters, e.g. 5 and 8 (0 leaves that end open), also set in Settings\n",
+    "# Command line: --lang, --limit, --time, --charset, --pattern, --wordlist, --corpus, --seed and --code override these settings for one session without editing this file\n",
+    "# Min words / min seconds: shorter runs still show results but are not saved to history or stats\n",
+    "# Render: \"auto\" (low-bandwidth over SSH), \"full\" or \"low\" (fewer redraws, no animations, plain styling)\n",
+    "# Forgive rollover: a wrong key immediately followed by the right one counts as a minor error instead of a mistyped word (true/false)\n",
+    "# Cycle: languages to alternate within a test, e.g. cycle = [\"EN\", \"RU\"], switching every cycle_every words\n",
+    "# Joiners: words with hyphens or apostrophes like \"well-known\": \"keep\", \"strip\" (wellknown) or \"split\" (well, known)\n",
+    "# Sentence mode: type a line of line_words words at a time, pressing space after each word (true/false)\n",
+    "# Boundary pause: the timer only runs while a word is being typed, for untimed thinking between words; such runs are kept apart in history (true/false)\n",
+    "# Accuracy thresholds: live accuracy at or above the first value shows green, at or above the second yellow, red below, e.g. [98.0, 95.0]\n",
+    "# Sidebar: on terminals at least 120 columns wide, show live speed, accuracy, streak and the word heat strip beside the test (true/false)\n",
+    "# Pace WPM: show a ghost caret where typing at this speed would be, e.g. 60 (0 disables), also set in Settings\n",
+    "# Reveal: shadow typing, only this many characters from the caret on are shown and the rest is veiled, with no preview of pack text (0 shows everything), also set in Settings\n",
+    "# Upcoming: show the next few words dimmed under the current one to read ahead (true/false), also set in Settings\n",
+    "# Strict: a wrong key is not typed, the caret waits for the right one and the mistake counts once (true/false)\n",
+    "# Numbers / punctuation: mix number tokens into generated words, or add commas and sentence endings with capitalized sentence starts (true/false), also set in Settings\n",
+    "# Pause word: a word paused halfway is \"keep\" (continued as typed on resume) or \"clear\" (typed again from its start); the pause never counts towards its time\n",
+    "# Export: directory and format (\"json\" or \"csv\") of runs exported with E on the results screen, e.g. export_dir = \"/path/to/exports\" (defaults to the data directory's exports)\n",
+    "# Gate WPM / gate accuracy: targets of `ktapper --gate`, which exits with 0 only after a test meets both (--min-wpm and --min-accuracy override them)\n",
+    "# Telemetry: opt in to sending how often each mode was used, never typed text, to telemetry_url when quitting (true/false); `ktapper telemetry preview` prints exactly what would be sent\n",
+    "# Metrics: shown after each test, any of \"wpm\" \"raw\" \"accuracy\" \"consistency\" \"burst\"\n",
+    "# Keys: [keys] pause = \"esc\" quit = \"q\" ... (lowercase letters, \"f1\"-\"f12\", \"tab\", \"esc\", ..., each key bound once per screen or ktapper refuses to start; the start screen has tour, clipboard, drill and practice, the plan add_step, delete_step, step_lang, more_repeats and fewer_repeats, the settings search, undo, redo and save_defaults), also recordable in Settings; F12, unless bound, toggles a developer overlay with frame and event timings\n",
+    "# Theme: [theme] preset = \"dark\", \"light\", \"gruvbox\" or \"solarized\", optionally overriding correct, wrong, border, title or highlight with a color name or \"#rrggbb\"",
+);
+
+fn config_path() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+/// Writes `config` with the help comments below it.
+fn write_config(path: &Path, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, format!("{}\n{}\n", toml::to_string(config)?, HELP))?;
+    Ok(())
+}
+
+pub(crate) fn get_config() -> Result<Config, Box<dyn std::error::Error>> {
+    let path = config_path().ok_or("Could not find project directories")?;
+    if path.exists() {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    } else {
+        let default_config = Config::default();
+        write_config(&path, &default_config)?;
+        Ok(default_config)
+    }
+}
+
+/// The config file as loaded at startup, without one-off command line
+/// overrides, written back when settings are saved as defaults.
+#[derive(Default)]
+pub(crate) struct ConfigManager {
+    pub(crate) config: Config,
+}
+
+impl ConfigManager {
+    pub(crate) fn load() -> Self {
+        Self {
+            config: CONFIG.clone(),
+        }
+    }
+
+    /// Re-reads the file, picking up edits made outside the app since it
+    /// was loaded.
+    pub(crate) fn reload(&mut self) -> Result<&Config, Box<dyn std::error::Error>> {
+        self.config = get_config()?;
+        Ok(&self.config)
+    }
+
+    /// Writes `config` to the file. Comments other than the help below the
+    /// options are not kept.
+    pub(crate) fn save(&mut self, config: Config) -> Result<(), Box<dyn std::error::Error>> {
+        let path = config_path().ok_or("Could not find project directories")?;
+        write_config(&path, &config)?;
+        self.config = config;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use once_cell::sync::Lazy;
+use random_word::Lang;
+
+use crate::config::lang_code;
+
+/// A text to type with its translation, shown underneath while typing.
+#[derive(Debug, PartialEq)]
+pub struct Pair {
+    pub text: String,
+    pub translation: String,
+}
+
+/// Bundled `lang<TAB>text<TAB>translation` sentences by lowercase language
+/// code, translated to English, and English ones to Spanish.
+static BUNDLED: Lazy<HashMap<String, Vec<Pair>>> = Lazy::new(|| {
+    let mut pairs: HashMap<String, Vec<Pair>> = HashMap::new();
+    for line in include_str!("../assets/parallel.tsv").lines() {
+        let mut fields = line.splitn(3, '\t');
+        if let (Some(lang), Some(text), Some(translation)) =
+            (fields.next(), fields.next(), fields.next())
+        {
+            pairs.entry(lang.to_string()).or_default().push(Pair {
+                text: text.to_string(),
+                translation: translation.to_string(),
+            });
+        }
+    }
+    pairs
+});
+
+/// Bundled pairs to type in `lang`, or in English for languages without any.
+pub fn bundled(lang: Lang) -> &'static [Pair] {
+    BUNDLED
+        .get(&lang_code(lang).to_lowercase())
+        .or_else(|| BUNDLED.get("en"))
+        .map_or(&[], Vec::as_slice)
+}
+
+/// Reads a `text<TAB>translation` file, one pair per line. Blank lines and
+/// lines starting with `#` are skipped.
+pub fn load(path: &Path) -> Result<&'static [Pair], Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read corpus {}: {}", path.display(), err))?;
+    let mut pairs = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((text, translation)) = line.split_once('\t') else {
+            return Err(format!(
+                "Corpus {} line {} has no tab between text and translation",
+                path.display(),
+                i + 1
+            )
+            .into());
+        };
+        if text.split_whitespace().next().is_none() {
+            return Err(format!("Corpus {} line {} has no text", path.display(), i + 1).into());
+        }
+        pairs.push(Pair {
+            text: text.trim().to_string(),
+            translation: translation.trim().to_string(),
+        });
+    }
+    if pairs.is_empty() {
+        return Err(format!("Corpus {} contains no pairs", path.display()).into());
+    }
+    Ok(Box::leak(pairs.into_boxed_slice()))
+}
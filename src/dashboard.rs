@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use htils::ternary;
+
+use crate::history::{Entry, Timing};
+
+/// Runs the accuracy chart of the dashboard goes back over.
+pub const DASHBOARD_RUNS: usize = 30;
+
+/// Speed and accuracy of the saved runs in one language.
+pub struct LangStats {
+    pub lang: String,
+    pub runs: usize,
+    pub wpm: f32,
+    pub best: f32,
+    pub accuracy: f32,
+}
+
+/// Totals and averages over every saved run. Speeds only count runs timed
+/// continuously, as runs timed within words aren't comparable.
+#[derive(Default)]
+pub struct Dashboard {
+    pub runs: usize,
+    pub wpm: f32,
+    pub best: f32,
+    pub accuracy: f32,
+    pub time: Duration,
+    pub words: usize,
+    /// Accuracy of the last [`DASHBOARD_RUNS`] runs, oldest first.
+    pub recent_accuracy: Vec<f32>,
+    /// Most typed languages first.
+    pub langs: Vec<LangStats>,
+}
+
+impl Dashboard {
+    pub fn new(entries: &[Entry]) -> Self {
+        let mut langs: Vec<LangStats> = Vec::new();
+        for entry in entries {
+            if langs.iter().all(|stats| stats.lang != entry.lang) {
+                let runs: Vec<&Entry> = entries.iter().filter(|e| e.lang == entry.lang).collect();
+                let (wpm, best) = speeds(&runs);
+                langs.push(LangStats {
+                    lang: entry.lang.clone(),
+                    runs: runs.len(),
+                    wpm,
+                    best,
+                    accuracy: average(runs.iter().map(|e| e.accuracy)),
+                });
+            }
+        }
+        langs.sort_by_key(|stats| std::cmp::Reverse(stats.runs));
+
+        let all: Vec<&Entry> = entries.iter().collect();
+        let (wpm, best) = speeds(&all);
+        Self {
+            runs: entries.len(),
+            wpm,
+            best,
+            accuracy: average(entries.iter().map(|e| e.accuracy)),
+            time: entries
+                .iter()
+                .map(|e| Duration::from_secs_f32(e.seconds.max(0.0)))
+                .sum(),
+            words: entries.iter().map(|e| e.words).sum(),
+            recent_accuracy: entries[entries.len().saturating_sub(DASHBOARD_RUNS)..]
+                .iter()
+                .map(|e| e.accuracy)
+                .collect(),
+            langs,
+        }
+    }
+}
+
+/// Average and best WPM of the runs timed continuously.
+fn speeds(runs: &[&Entry]) -> (f32, f32) {
+    let comparable = runs.iter().filter(|e| e.timing == Timing::Continuous);
+    let best = comparable.clone().map(|e| e.wpm).fold(0.0, f32::max);
+    (average(comparable.map(|e| e.wpm)), best)
+}
+
+fn average(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, n) = values.fold((0.0, 0), |(sum, n), value| (sum + value, n + 1));
+    ternary!(n == 0, 0.0, sum / n as f32)
+}
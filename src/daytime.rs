@@ -0,0 +1,75 @@
+use chrono::{Local, TimeZone, Timelike};
+
+use crate::history::{Entry, Timing};
+
+/// Part of the local day a run was typed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayPart {
+    Night,
+    Morning,
+    Afternoon,
+    Evening,
+}
+
+impl DayPart {
+    pub const ALL: [DayPart; 4] = [
+        DayPart::Morning,
+        DayPart::Afternoon,
+        DayPart::Evening,
+        DayPart::Night,
+    ];
+
+    pub fn from_hour(hour: u8) -> Self {
+        match hour {
+            5..=11 => DayPart::Morning,
+            12..=16 => DayPart::Afternoon,
+            17..=21 => DayPart::Evening,
+            _ => DayPart::Night,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DayPart::Night => "night (22-05)",
+            DayPart::Morning => "morning (05-12)",
+            DayPart::Afternoon => "afternoon (12-17)",
+            DayPart::Evening => "evening (17-22)",
+        }
+    }
+}
+
+/// Local hour of a Unix time.
+pub fn hour_at(timestamp: u64) -> u8 {
+    Local
+        .timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map_or(0, |time| time.hour() as u8)
+}
+
+pub struct DayStats {
+    pub part: DayPart,
+    pub runs: usize,
+    pub wpm: f32,
+    pub accuracy: f32,
+}
+
+/// Average speed and accuracy in each part of the day. Runs timed only
+/// within words aren't comparable and are left out.
+pub fn by_day_part(entries: &[Entry]) -> Vec<DayStats> {
+    DayPart::ALL
+        .iter()
+        .map(|&part| {
+            let runs: Vec<&Entry> = entries
+                .iter()
+                .filter(|e| e.timing == Timing::Continuous && DayPart::from_hour(e.hour) == part)
+                .collect();
+            let n = runs.len().max(1) as f32;
+            DayStats {
+                part,
+                runs: runs.len(),
+                wpm: runs.iter().map(|e| e.wpm).sum::<f32>() / n,
+                accuracy: runs.iter().map(|e| e.accuracy).sum::<f32>() / n,
+            }
+        })
+        .collect()
+}
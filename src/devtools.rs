@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+/// Weight of the newest sample in the smoothed timings.
+const SMOOTHING: f64 = 0.1;
+
+/// Timings of the event loop, shown in the developer overlay toggled with F12.
+#[derive(Default)]
+pub struct DevStats {
+    pub shown: bool,
+    /// Time the last frame took to render and flush.
+    pub frame: Duration,
+    pub frame_avg: Duration,
+    /// Time the last key took to handle.
+    pub event: Duration,
+    pub event_avg: Duration,
+    /// Time from the last key being read to its frame being on screen.
+    pub latency: Duration,
+    pub frames: u64,
+    pub events: u64,
+    /// When the first key not yet drawn was read.
+    pending: Option<Instant>,
+}
+
+impl DevStats {
+    pub fn toggle(&mut self) {
+        self.shown = !self.shown;
+    }
+
+    pub fn record_event(&mut self, read: Instant) {
+        self.event = read.elapsed();
+        self.event_avg = smooth(self.event_avg, self.event, self.events);
+        self.events += 1;
+        self.pending.get_or_insert(read);
+    }
+
+    pub fn record_frame(&mut self, started: Instant) {
+        self.frame = started.elapsed();
+        self.frame_avg = smooth(self.frame_avg, self.frame, self.frames);
+        self.frames += 1;
+        if let Some(read) = self.pending.take() {
+            self.latency = read.elapsed();
+        }
+    }
+}
+
+/// Exponential moving average, starting from the first sample.
+fn smooth(avg: Duration, sample: Duration, samples: u64) -> Duration {
+    if samples == 0 {
+        return sample;
+    }
+    avg.mul_f64(1.0 - SMOOTHING) + sample.mul_f64(SMOOTHING)
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use rand::{seq::SliceRandom, thread_rng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::data_dir;
+
+/// Runs compared against the baseline.
+const RECENT_TESTS: usize = 5;
+const BASELINE_SECONDS: u64 = 30 * 86_400;
+/// Samples a letter or bigram needs in the baseline and in the recent runs.
+const MIN_BASELINE_SAMPLES: u32 = 20;
+const MIN_RECENT_SAMPLES: u32 = 5;
+/// Share by which the recent average must be slower to count as a regression.
+const SLOWDOWN: f32 = 0.15;
+/// Regressions drilled at once, worst first.
+const MAX_DRILLS: usize = 3;
+/// Longer gaps are pauses rather than typing and are not sampled.
+const MAX_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Keystroke intervals of one finished test, stored as a line of `speeds.jsonl`.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Speeds {
+    pub timestamp: u64,
+    /// Letter or bigram to `(total ms, samples)`.
+    pub keys: BTreeMap<String, (u64, u32)>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("speeds.jsonl"))
+}
+
+impl Speeds {
+    /// Samples `ch`, typed `interval` after `previous`, for the letter and the bigram.
+    pub fn record(&mut self, previous: char, ch: char, interval: Duration) {
+        if interval > MAX_INTERVAL {
+            return;
+        }
+        let ms = interval.as_millis() as u64;
+        let ch = ch.to_lowercase().to_string();
+        let bigram = format!("{}{}", previous.to_lowercase(), ch);
+        for key in [ch, bigram] {
+            let sample = self.keys.entry(key).or_default();
+            sample.0 += ms;
+            sample.1 += 1;
+        }
+    }
+
+    pub fn append(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+fn load() -> Vec<Speeds> {
+    let Some(file) = path().and_then(|path| File::open(path).ok()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// A letter or bigram typed slower lately than over the last 30 days.
+pub struct Regression {
+    pub key: String,
+    /// 0.2 means 20% slower than the baseline.
+    pub slowdown: f32,
+}
+
+fn totals(runs: &[Speeds]) -> BTreeMap<&str, (u64, u32)> {
+    let mut totals: BTreeMap<&str, (u64, u32)> = BTreeMap::new();
+    for (key, (ms, samples)) in runs.iter().flat_map(|run| &run.keys) {
+        let total = totals.entry(key).or_default();
+        total.0 += ms;
+        total.1 += samples;
+    }
+    totals
+}
+
+/// Worst regressions of the last runs against the 30 days before them.
+pub fn regressions(now: u64) -> Vec<Regression> {
+    let runs = load();
+    let split = runs.len().saturating_sub(RECENT_TESTS);
+    let (baseline, recent) = runs.split_at(split);
+    let from = now.saturating_sub(BASELINE_SECONDS);
+    let baseline: Vec<Speeds> = baseline
+        .iter()
+        .filter(|run| run.timestamp >= from)
+        .cloned()
+        .collect();
+
+    let before = totals(&baseline);
+    let mut found: Vec<Regression> = totals(recent)
+        .into_iter()
+        .filter_map(|(key, (ms, samples))| {
+            let &(base_ms, base_samples) = before.get(key)?;
+            if samples < MIN_RECENT_SAMPLES || base_samples < MIN_BASELINE_SAMPLES {
+                return None;
+            }
+            let average = ms as f32 / samples as f32;
+            let base = base_ms as f32 / base_samples as f32;
+            let slowdown = average / base.max(1.0) - 1.0;
+            (slowdown > SLOWDOWN).then(|| Regression {
+                key: key.to_string(),
+                slowdown,
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| b.slowdown.total_cmp(&a.slowdown));
+    found.truncate(MAX_DRILLS);
+    found
+}
+
+/// `count` words from `pool` that each practice at least one of `keys`.
+pub fn drill_words(pool: &[&str], keys: &[String], count: usize) -> Vec<String> {
+    let matching: Vec<&str> = pool
+        .iter()
+        .copied()
+        .filter(|word| {
+            let word = word.to_lowercase();
+            keys.iter().any(|key| word.contains(key.as_str()))
+        })
+        .collect();
+    let mut rng = thread_rng();
+    (0..count)
+        .filter_map(|_| matching.choose(&mut rng).map(|word| word.to_string()))
+        .collect()
+}
@@ -0,0 +1,115 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// File format runs are exported in from the results screen.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    /// One row per word, the run's figures repeated on every row.
+    Csv,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// A finished run as written to an export file.
+#[derive(Serialize)]
+pub struct Run {
+    /// Unix time in seconds at which the test finished.
+    pub timestamp: u64,
+    pub lang: String,
+    pub wpm: f32,
+    pub accuracy: f32,
+    pub seconds: f32,
+    /// Mistakes by the key that should have been pressed.
+    pub key_errors: BTreeMap<char, u32>,
+    pub words: Vec<RunWord>,
+}
+
+#[derive(Serialize)]
+pub struct RunWord {
+    pub word: String,
+    pub correct: bool,
+    /// Positions of the mistyped characters.
+    pub wrong_chars: Vec<usize>,
+    /// Test time in seconds at which the word was completed.
+    pub finished_at: f32,
+}
+
+/// Writes `run` to a new file in `dir`, named after the time it finished.
+/// Returns the path written.
+pub fn write(
+    run: &Run,
+    dir: &Path,
+    format: ExportFormat,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    let time = Local
+        .timestamp_opt(run.timestamp as i64, 0)
+        .single()
+        .map_or_else(
+            || run.timestamp.to_string(),
+            |time| time.format("%Y%m%d-%H%M%S").to_string(),
+        );
+    let path = dir.join(format!("ktapper-{}.{}", time, format.extension()));
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(run)?,
+        ExportFormat::Csv => csv(run),
+    };
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+fn csv(run: &Run) -> String {
+    let mut out = String::from(
+        "timestamp,lang,wpm,accuracy,seconds,index,word,correct,wrong_chars,finished_at\n",
+    );
+    for (i, word) in run.words.iter().enumerate() {
+        let wrong_chars = word
+            .wrong_chars
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            out,
+            "{},{},{:.2},{:.2},{:.3},{},{},{},{},{:.3}",
+            run.timestamp,
+            run.lang,
+            run.wpm,
+            run.accuracy,
+            run.seconds,
+            i + 1,
+            field(&word.word),
+            word.correct,
+            wrong_chars,
+            word.finished_at
+        );
+    }
+    out
+}
+
+/// Quotes a field holding commas, quotes or line breaks, as punctuation
+/// and quotes may.
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,67 @@
+use regex::Regex;
+
+use crate::graphemes;
+
+/// Constraint every generated word has to meet: made only of characters from
+/// a set, matching a regular expression, within a length range, or any mix of
+/// these. The character set ignores case, the pattern doesn't.
+#[derive(Default, Clone)]
+pub struct WordFilter {
+    charset: Option<Vec<char>>,
+    pattern: Option<Regex>,
+    /// Shortest and longest word in characters, 0 for no bound.
+    min_length: usize,
+    max_length: usize,
+}
+
+impl WordFilter {
+    pub fn new(
+        charset: Option<&str>,
+        pattern: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let pattern = pattern
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|err| format!("Invalid pattern {}: {}", pattern, err))
+            })
+            .transpose()?;
+        let charset = charset.map(|set| set.to_lowercase().chars().collect());
+        Ok(Self {
+            charset,
+            pattern,
+            ..Self::default()
+        })
+    }
+
+    pub fn with_lengths(self, min_length: usize, max_length: usize) -> Self {
+        Self {
+            min_length,
+            max_length,
+            ..self
+        }
+    }
+
+    pub fn lengths(&self) -> (usize, usize) {
+        (self.min_length, self.max_length)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.charset.is_none()
+            && self.pattern.is_none()
+            && self.min_length == 0
+            && self.max_length == 0
+    }
+
+    pub fn allows(&self, word: &str) -> bool {
+        let length = graphemes::count(word);
+        length >= self.min_length
+            && (self.max_length == 0 || length <= self.max_length)
+            && self
+                .charset
+                .as_ref()
+                .is_none_or(|set| word.to_lowercase().chars().all(|ch| set.contains(&ch)))
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(word))
+    }
+}
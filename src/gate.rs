@@ -0,0 +1,43 @@
+use std::process::ExitCode;
+
+/// Target of a focus gate: ktapper exits successfully only once a finished
+/// test reaches it, so other tools can run it as a lock in front of
+/// something else.
+#[derive(Debug, Clone, Copy)]
+pub struct Gate {
+    pub min_wpm: f32,
+    pub min_accuracy: f32,
+    pub passed: bool,
+}
+
+impl Gate {
+    pub fn new(min_wpm: f32, min_accuracy: f32) -> Self {
+        Self {
+            min_wpm,
+            min_accuracy,
+            passed: false,
+        }
+    }
+
+    /// Checks a finished test against the target. Once passed the gate stays open.
+    pub fn record(&mut self, wpm: f32, accuracy: f32) {
+        self.passed |= wpm >= self.min_wpm && accuracy >= self.min_accuracy;
+    }
+
+    pub fn target(&self) -> String {
+        format!(
+            "{:.0} wpm at {:.0}% accuracy",
+            self.min_wpm, self.min_accuracy
+        )
+    }
+
+    /// 0 when a test met the target, 1 when none did. Invalid arguments exit
+    /// with 2 before any test starts.
+    pub fn exit_code(&self) -> ExitCode {
+        if self.passed {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+}
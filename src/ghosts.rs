@@ -0,0 +1,78 @@
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::data_dir;
+
+/// Progress after one keystroke of a recorded run.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    /// Milliseconds into the test.
+    pub ms: u32,
+    /// Words completed.
+    pub words: u32,
+    /// Characters typed of the word after them.
+    pub typed: u32,
+}
+
+/// The keystroke timeline of the fastest run of one kind of test, raced
+/// against in later ones.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Ghost {
+    pub wpm: f32,
+    /// Unix time in seconds the run finished.
+    pub timestamp: u64,
+    pub strokes: Vec<Stroke>,
+}
+
+impl Ghost {
+    /// Where the run was `elapsed` into the test.
+    pub fn at(&self, elapsed: Duration) -> Option<Stroke> {
+        let ms = elapsed.as_millis().min(u32::MAX as u128) as u32;
+        let done = self.strokes.partition_point(|s| s.ms <= ms);
+        done.checked_sub(1).map(|i| self.strokes[i])
+    }
+}
+
+/// Ghosts by test kind, see [`crate::records::key`].
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Ghosts {
+    #[serde(default)]
+    pub ghosts: BTreeMap<String, Ghost>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("ghosts.json"))
+}
+
+impl Ghosts {
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Keeps `ghost` when it is the first of its kind or faster than the one
+    /// kept. Returns whether it was kept.
+    pub fn record(&mut self, key: String, ghost: Ghost) -> bool {
+        if self
+            .ghosts
+            .get(&key)
+            .is_some_and(|kept| kept.wpm >= ghost.wpm)
+        {
+            return false;
+        }
+        self.ghosts.insert(key, ghost);
+        true
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Keystrokes used to measure the current pace.
+const WINDOW: usize = 10;
+const WARNING_DURATION: Duration = Duration::from_millis(600);
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GovernorMode {
+    /// Keystrokes over the limit are accepted but flagged.
+    #[default]
+    Warn,
+    /// Keystrokes over the limit are ignored.
+    Block,
+}
+
+/// Caps typing pace at `max_wpm` to train accuracy before speed.
+pub struct Governor {
+    pub max_wpm: f32,
+    mode: GovernorMode,
+    recent: VecDeque<Instant>,
+    warned: Option<Instant>,
+}
+
+impl Governor {
+    pub fn new(max_wpm: u32, mode: GovernorMode) -> Option<Self> {
+        (max_wpm > 0).then(|| Self {
+            max_wpm: max_wpm as f32,
+            mode,
+            recent: VecDeque::with_capacity(WINDOW),
+            warned: None,
+        })
+    }
+
+    /// Whether a keystroke at `now` should be accepted.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        if self.pace(now) > self.max_wpm {
+            self.warned = Some(now);
+            if self.mode == GovernorMode::Block {
+                return false;
+            }
+        }
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(now);
+        true
+    }
+
+    /// Pace over the recent window if a key were typed at `now`.
+    fn pace(&self, now: Instant) -> f32 {
+        let Some(first) = self.recent.front() else {
+            return 0.0;
+        };
+        if self.recent.len() < WINDOW {
+            return 0.0;
+        }
+        let minutes = now.duration_since(*first).as_secs_f32() / 60.0;
+        if minutes <= 0.0 {
+            return f32::INFINITY;
+        }
+        self.recent.len() as f32 / 5.0 / minutes
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.warned
+            .is_some_and(|at| at.elapsed() < WARNING_DURATION)
+    }
+
+    pub fn reset(&mut self) {
+        self.recent.clear();
+        self.warned = None;
+    }
+}
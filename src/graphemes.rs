@@ -0,0 +1,44 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How the grapheme being typed compares to the one expected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Correct,
+    /// The start of the expected grapheme, such as a base letter still
+    /// waiting for its combining accent.
+    Partial,
+    Wrong,
+}
+
+/// Positions in a word are counted in user-perceived characters, so a
+/// letter with combining marks or a character typed through an input
+/// method is one position, whatever number of `char`s it takes.
+pub fn count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+pub fn nth(text: &str, index: usize) -> Option<&str> {
+    text.graphemes(true).nth(index)
+}
+
+/// `text` after its first `n` graphemes.
+pub fn skip(text: &str, n: usize) -> &str {
+    let start = text
+        .grapheme_indices(true)
+        .nth(n)
+        .map_or(text.len(), |(i, _)| i);
+    &text[start..]
+}
+
+pub fn judge(expected: Option<&str>, typed: &str) -> Verdict {
+    match expected {
+        Some(expected) if expected == typed => Verdict::Correct,
+        Some(expected) if expected.starts_with(typed) => Verdict::Partial,
+        _ => Verdict::Wrong,
+    }
+}
+
+/// Key counted against a grapheme, its base character.
+pub fn base(grapheme: &str) -> Option<char> {
+    grapheme.chars().next()
+}
@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{TestMode, data_dir},
+    words::WordMode,
+};
+
+/// One finished test, stored as a line of `history.jsonl`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entry {
+    /// Unix time in seconds at which the test finished.
+    pub timestamp: u64,
+    pub lang: String,
+    pub words: usize,
+    pub wpm: f32,
+    pub accuracy: f32,
+    pub seconds: f32,
+    pub mode: WordMode,
+    pub timing: Timing,
+    /// Local hour, 0-23, at which the test finished.
+    pub hour: u8,
+    /// Mistakes by the key that should have been pressed.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub key_errors: BTreeMap<char, u32>,
+    /// Every keystroke per minute, mistakes included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<f32>,
+    /// How even the per-word speed was, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<f32>,
+    /// Kind of test, unknown for runs saved before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<TestMode>,
+}
+
+/// How the time of a test was measured. Runs timed differently are not
+/// comparable and are kept apart in reports.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Timing {
+    #[default]
+    Continuous,
+    /// Only while a word was in progress, pausing between words.
+    Words,
+}
+
+impl Timing {
+    /// Tag shown next to runs that aren't timed continuously.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Timing::Continuous => "",
+            Timing::Words => " (word timing)",
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("history.jsonl"))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Unix time of `at`, which already passed, e.g. the keystroke that
+/// finished a test however long it waited to be processed.
+pub fn timestamp_at(at: Instant) -> u64 {
+    SystemTime::now()
+        .checked_sub(at.elapsed())
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn append(entry: &Entry) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path().ok_or("Could not find project directories")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Replaces every saved run with `entries`.
+pub fn rewrite(entries: &[Entry]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path().ok_or("Could not find project directories")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    fs::write(path, lines)?;
+    Ok(())
+}
+
+/// Every saved run, oldest first. Unreadable lines are skipped.
+pub fn load() -> Vec<Entry> {
+    let Some(file) = path().and_then(|path| File::open(path).ok()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Direction of the latest run's WPM against the average of the ones before it.
+pub fn trend(entries: &[Entry]) -> &'static str {
+    let Some((last, previous)) = entries.split_last() else {
+        return "";
+    };
+    if previous.is_empty() {
+        return "";
+    }
+    let average = previous.iter().map(|e| e.wpm).sum::<f32>() / previous.len() as f32;
+    if last.wpm > average + 1.0 {
+        "↑"
+    } else if last.wpm < average - 1.0 {
+        "↓"
+    } else {
+        "→"
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    cmp::{max, min},
+    time::{Duration, Instant},
+};
+
+use htils::ternary;
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{
+        self, Event, KeyCode, KeyEvent, KeyEventState, MouseButton, MouseEvent, MouseEventKind,
+    },
+    layout::Rect,
+    widgets::TableState,
+};
+
+use crate::{
+    actions::ResultAction,
+    app::{App, AppState},
+    config::{lang_code, next_lang},
+    keys::{Action, Scope},
+    ngrams::SETS,
+    plan::Step,
+    settings::SettingItem,
+    stats::SortColumn,
+    ui::{fits, render, render_dev, render_too_small, render_tour, simplify},
+};
+
+pub(crate) const TICK_RATE: Duration = Duration::from_millis(100);
+/// Tick rate of the low-bandwidth profile, so idle screens redraw less often.
+pub(crate) const LOW_BANDWIDTH_TICK_RATE: Duration = Duration::from_millis(500);
+/// Rows moved by PageUp/PageDown on the history screen.
+pub(crate) const HISTORY_PAGE: u16 = 10;
+
+pub fn run(term: &mut DefaultTerminal, app: &mut App) -> std::io::Result<()> {
+    while !app.exit {
+        app.advance_tour();
+        let started = Instant::now();
+        term.draw(|f| {
+            if fits(f.area(), app) {
+                render(f, app);
+                render_tour(f, app);
+            } else {
+                render_too_small(f, app);
+            }
+            render_dev(f, app);
+            if app.low_bandwidth {
+                simplify(f.buffer_mut());
+            }
+        })?;
+        app.dev.record_frame(started);
+        let tick_rate = ternary!(app.low_bandwidth, LOW_BANDWIDTH_TICK_RATE, TICK_RATE);
+        if !event::poll(tick_rate)? {
+            app.tick();
+            continue;
+        }
+        // Everything already queued is handled before the next frame, so keys
+        // typed while a frame was drawn aren't delayed by it.
+        loop {
+            let event = event::read()?;
+            let at = Instant::now();
+            match event {
+                Event::Key(key) => {
+                    handle_key(app, key, at);
+                    app.dev.record_event(at);
+                }
+                Event::Mouse(mouse) => handle_mouse(app, mouse),
+                // The old settings area would take clicks meant for whatever
+                // is drawn there after the resize.
+                Event::Resize(..) => {
+                    term.autoresize()?;
+                    app.settings_area = Rect::default();
+                }
+                _ => {}
+            }
+            if app.exit || !event::poll(Duration::ZERO)? {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The wheel scrolls the results table and the settings list, and a click
+/// selects a setting.
+pub(crate) fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    match (&mut app.app_state, mouse.kind) {
+        (AppState::Results(table_state), MouseEventKind::ScrollUp) => table_state.select_previous(),
+        (AppState::Results(table_state), MouseEventKind::ScrollDown) => table_state.select_next(),
+        (AppState::Settings, MouseEventKind::ScrollUp) if !app.recording => app.move_setting(false),
+        (AppState::Settings, MouseEventKind::ScrollDown) if !app.recording => {
+            app.move_setting(true)
+        }
+        (AppState::Settings, MouseEventKind::Down(MouseButton::Left)) => {
+            app.click_setting(mouse.column, mouse.row)
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn handle_key(app: &mut App, key: KeyEvent, at: Instant) {
+    if key.code == KeyCode::Tab && app.tour.is_some() {
+        app.end_tour();
+        return;
+    }
+    // An open focus gate lets the user straight through.
+    if matches!(app.app_state, AppState::Results(_)) && app.gate.is_some_and(|gate| gate.passed) {
+        app.exit();
+        return;
+    }
+    let action = app.keys.action(key.code);
+    let scope = match app.app_state {
+        AppState::Menu => Scope::Menu,
+        AppState::Plan(..) => Scope::Plan,
+        AppState::Settings => Scope::Settings,
+        _ => Scope::Global,
+    };
+    let screen_action = app.keys.screen_action(key.code, scope);
+    if key.code == KeyCode::F(12) && action.is_none() {
+        app.dev.toggle();
+        return;
+    }
+    match &mut app.app_state {
+        AppState::Menu => match key.code {
+            KeyCode::Enter => app.restart(),
+            _ if screen_action == Some(Action::Tour) => app.start_tour(),
+            _ if screen_action == Some(Action::Clipboard) => app.clipboard_test(),
+            _ if screen_action == Some(Action::Drill) => app.start_drill(),
+            _ if screen_action == Some(Action::Practice) => app.open_practice(),
+            _ if action == Some(Action::Quit) => app.exit(),
+            _ if action == Some(Action::Stats) => app.open_stats(),
+            _ if action == Some(Action::Settings) => app.open_settings(),
+            _ if action == Some(Action::Plan) => app.open_plan(),
+            _ if action == Some(Action::Zen) => app.start_zen(),
+            KeyCode::Esc => app.exit(),
+            _ => {}
+        },
+        AppState::Preview => match key.code {
+            KeyCode::Enter => app.app_state = AppState::Input,
+            KeyCode::Esc => app.open_menu(),
+            _ if action == Some(Action::Restart) => app.restart(),
+            _ if action == Some(Action::Quit) => app.exit(),
+            _ => {}
+        },
+        AppState::Input => match key.code {
+            _ if action == Some(Action::Pause) => app.pause(),
+            KeyCode::Char(ch) => {
+                app.caps_lock = key.state.contains(KeyEventState::CAPS_LOCK);
+                app.type_char(ch, at)
+            }
+            KeyCode::Enter if app.snippet.is_some() => app.type_char('\n', at),
+            KeyCode::Tab if app.snippet.is_some() => app.type_tab(at),
+            _ => {}
+        },
+        AppState::Pause => {
+            match action {
+                Some(Action::Quit) => app.exit(),
+                Some(Action::Settings) => app.open_settings(),
+                Some(Action::Plan) => app.open_plan(),
+                Some(Action::Zen) => app.start_zen(),
+                Some(Action::Stats) => app.open_stats(),
+                _ => app.resume(), // Any key to resume
+            }
+        }
+        AppState::Results(table_state) => match key.code {
+            KeyCode::Up => table_state.select_previous(),
+            KeyCode::Down => table_state.select_next(),
+            _ => {
+                let results = table_state.clone();
+                let Some(pressed) = app
+                    .result_actions()
+                    .into_iter()
+                    .find(|offered| offered.matches(key.code, action))
+                else {
+                    return;
+                };
+                app.pressed = Some((pressed, at));
+                match pressed {
+                    ResultAction::Restart => {
+                        // A fresh test, rather than the same mistakes again.
+                        if app.is_training_mistakes() {
+                            app.leave_scratch();
+                        }
+                        app.restart()
+                    }
+                    ResultAction::Mistakes => app.train_mistakes(),
+                    ResultAction::Sort => {
+                        if let KeyCode::Char(ch @ '1'..='5') = key.code {
+                            app.sort_results(SortColumn::ALL[ch as usize - '1' as usize])
+                        }
+                    }
+                    ResultAction::History => app.open_history(results),
+                    ResultAction::Stats => app.open_stats(),
+                    ResultAction::Rivals => {
+                        let mut rivals = TableState::default();
+                        rivals.select_first();
+                        app.app_state = AppState::Rivals(rivals, results);
+                    }
+                    ResultAction::Layouts => app.app_state = AppState::Layouts(results),
+                    ResultAction::Export => app.export_run(),
+                    ResultAction::Replay => app.open_replay(results),
+                    ResultAction::Lobby => app.app_state = AppState::Lobby,
+                    ResultAction::Plan => app.open_plan(),
+                    ResultAction::Settings => app.open_settings(),
+                    ResultAction::Menu => app.open_menu(),
+                    ResultAction::Quit => app.exit(),
+                }
+            }
+        },
+        AppState::Rivals(rivals, table_state) => match key.code {
+            KeyCode::Up => rivals.select_previous(),
+            KeyCode::Down => rivals.select_next(),
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Esc => {
+                app.app_state = AppState::Results(std::mem::take(table_state));
+            }
+            _ => {}
+        },
+        AppState::Replay(replay, table_state) => match key.code {
+            KeyCode::Char(' ') => replay.toggle(),
+            KeyCode::Left => replay.seek(false),
+            KeyCode::Right => replay.seek(true),
+            KeyCode::Char(ch @ '1'..='9') => replay.set_speed(ch as u32 - '0' as u32),
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Esc => {
+                app.app_state = AppState::Results(std::mem::take(table_state));
+            }
+            _ => {}
+        },
+        AppState::History(list_state, table_state) => match key.code {
+            KeyCode::Up => list_state.select_previous(),
+            KeyCode::Down => list_state.select_next(),
+            KeyCode::PageUp => list_state.scroll_up_by(HISTORY_PAGE),
+            KeyCode::PageDown => list_state.scroll_down_by(HISTORY_PAGE),
+            KeyCode::Home => list_state.select_first(),
+            KeyCode::End => list_state.select_last(),
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Esc => {
+                app.app_state = AppState::Results(std::mem::take(table_state));
+            }
+            _ => {}
+        },
+        AppState::Layouts(table_state) => match key.code {
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Esc => {
+                app.app_state = AppState::Results(std::mem::take(table_state));
+            }
+            _ => {}
+        },
+        AppState::Plan(list_state, _) => match key.code {
+            KeyCode::Up => list_state.select_previous(),
+            KeyCode::Down => list_state.select_next(),
+            _ if screen_action == Some(Action::AddStep) => {
+                app.plan.push(Step {
+                    label: "custom".to_string(),
+                    lang: lang_code(app.lang),
+                    limit: app.words_limit,
+                    repeat: 1,
+                });
+                list_state.select(Some(app.plan.len() - 1));
+            }
+            _ if key.code == KeyCode::Delete || screen_action == Some(Action::DeleteStep) => {
+                if let Some(i) = list_state.selected().filter(|&i| i < app.plan.len()) {
+                    app.plan.remove(i);
+                }
+            }
+            KeyCode::Left => {
+                if let Some(step) = list_state.selected().and_then(|i| app.plan.get_mut(i)) {
+                    step.limit = max(1, step.limit.saturating_sub(5));
+                }
+            }
+            KeyCode::Right => {
+                if let Some(step) = list_state.selected().and_then(|i| app.plan.get_mut(i)) {
+                    step.limit = min(u16::MAX as usize, step.limit.saturating_add(5));
+                }
+            }
+            _ if screen_action == Some(Action::StepLang) => {
+                if let Some(step) = list_state.selected().and_then(|i| app.plan.get_mut(i)) {
+                    step.lang = lang_code(next_lang(step.lang()));
+                }
+            }
+            _ if screen_action == Some(Action::MoreRepeats) => {
+                if let Some(step) = list_state.selected().and_then(|i| app.plan.get_mut(i)) {
+                    step.repeat = step.repeat.saturating_add(1);
+                }
+            }
+            _ if screen_action == Some(Action::FewerRepeats) => {
+                if let Some(step) = list_state.selected().and_then(|i| app.plan.get_mut(i)) {
+                    step.repeat = max(1, step.repeat - 1);
+                }
+            }
+            KeyCode::Enter => app.start_session(),
+            KeyCode::Esc => app.close_plan(),
+            _ => {}
+        },
+        AppState::Zen(zen) => match key.code {
+            KeyCode::Esc => app.finish_zen(),
+            KeyCode::Enter => zen.push('\n'),
+            KeyCode::Char(ch) => zen.push(ch),
+            KeyCode::Backspace => zen.backspace(),
+            _ => {}
+        },
+        AppState::ZenResults(_) => match key.code {
+            KeyCode::Enter => app.start_zen(),
+            KeyCode::Esc => app.open_menu(),
+            _ if action == Some(Action::Zen) => app.start_zen(),
+            _ if action == Some(Action::Restart) => app.restart(),
+            _ if action == Some(Action::Menu) => app.open_menu(),
+            _ if action == Some(Action::Quit) => app.exit(),
+            _ => {}
+        },
+        AppState::Lobby => match key.code {
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Enter => app.start_race(),
+            KeyCode::Esc => app.open_menu(),
+            _ => {}
+        },
+        AppState::Practice(list_state) => match key.code {
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Up => list_state.select_previous(),
+            KeyCode::Down => list_state.select_next(),
+            KeyCode::Enter => {
+                if let Some(set) = list_state.selected().and_then(|i| SETS.get(i)) {
+                    app.start_ngrams(set);
+                }
+            }
+            KeyCode::Esc => app.app_state = AppState::Menu,
+            _ => {}
+        },
+        AppState::Stats(_) => match key.code {
+            _ if action == Some(Action::Quit) => app.exit(),
+            KeyCode::Esc => app.close_stats(),
+            KeyCode::Tab | KeyCode::Left | KeyCode::Right => app.next_stats_page(),
+            _ => {}
+        },
+        AppState::Report => match key.code {
+            _ if action == Some(Action::Quit) => app.exit(),
+            _ if action == Some(Action::Restart) => app.leave_session(),
+            _ if action == Some(Action::Plan) => {
+                app.session = None;
+                app.open_plan();
+            }
+            _ => {}
+        },
+        AppState::Settings if app.recording => {
+            app.recording = false;
+            if key.code != KeyCode::Backspace {
+                app.record_key(key.code);
+            }
+        }
+        AppState::Settings if app.settings_search.is_some() => match key.code {
+            KeyCode::Up => app.move_setting(false),
+            KeyCode::Down => app.move_setting(true),
+            KeyCode::Enter | KeyCode::Esc => app.settings_search = None,
+            KeyCode::Char(ch) => {
+                app.settings_search.get_or_insert_default().push(ch);
+                app.jump_to_match();
+            }
+            KeyCode::Backspace => {
+                app.settings_search.get_or_insert_default().pop();
+                app.jump_to_match();
+            }
+            _ => {}
+        },
+        AppState::Settings => match key.code {
+            KeyCode::Esc => leave_settings(app),
+            KeyCode::Up => app.move_setting(false),
+            KeyCode::Down => app.move_setting(true),
+            KeyCode::PageUp => app.move_page(false),
+            KeyCode::PageDown => app.move_page(true),
+            KeyCode::Left | KeyCode::Right
+                if matches!(app.selected_setting, SettingItem::Key(_)) =>
+            {
+                app.recording = true;
+            }
+            KeyCode::Left => {
+                let item = app.selected_setting;
+                app.editor.edit(|draft| draft.adjust(item, false));
+            }
+            KeyCode::Right => {
+                let item = app.selected_setting;
+                app.editor.edit(|draft| draft.adjust(item, true));
+            }
+            KeyCode::Char(ch) if app.selected_setting.is_text() => {
+                let item = app.selected_setting;
+                app.editor.edit(|draft| draft.push_char(item, ch));
+            }
+            _ if screen_action == Some(Action::Search) => app.settings_search = Some(String::new()),
+            _ if screen_action == Some(Action::Undo) => app.editor.undo(),
+            _ if screen_action == Some(Action::Redo) => app.editor.redo(),
+            _ if screen_action == Some(Action::SaveDefaults) => close_settings(app, true),
+            KeyCode::Char(ch) => {
+                let item = app.selected_setting;
+                app.editor.edit(|draft| draft.push_char(item, ch));
+            }
+            KeyCode::Backspace => {
+                let item = app.selected_setting;
+                app.editor.edit(|draft| draft.pop_char(item));
+            }
+            KeyCode::Enter => close_settings(app, false),
+            _ => {}
+        },
+    }
+}
+
+/// Applies the edited settings, also as the config file's defaults when
+/// `save` is set, and goes back to the test. Stays open on errors.
+fn close_settings(app: &mut App, save: bool) {
+    let applied = app
+        .apply_settings()
+        .and_then(|()| if save { app.save_settings() } else { Ok(()) });
+    if let Err(err) = applied {
+        app.settings_notice = Some(err.to_string());
+        return;
+    }
+    leave_settings(app);
+}
+
+/// Back to the test paused for the settings, or to a new one when they
+/// changed or were opened from the results of a finished test.
+fn leave_settings(app: &mut App) {
+    if app.settings_changed || app.finished_time().is_some() {
+        app.restart();
+        app.settings_changed = false;
+    } else {
+        app.resume();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_key(app, KeyEvent::from(code), Instant::now());
+    }
+
+    #[test]
+    fn plan_goes_back_to_the_screen_it_was_opened_from() {
+        let mut app = App::default();
+        let mut results = TableState::default();
+        results.select(Some(2));
+        app.app_state = AppState::Results(results);
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.app_state.name(), "Plan");
+        press(&mut app, KeyCode::Esc);
+        // Keys act on the results again rather than on the finished test.
+        press(&mut app, KeyCode::Down);
+        assert!(matches!(&app.app_state, AppState::Results(table) if table.selected() == Some(3)));
+
+        app.app_state = AppState::Pause;
+        press(&mut app, KeyCode::Char('p'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.app_state.name(), "Pause");
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::data_dir;
+
+/// One line of the in-progress test journal.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum JournalEvent {
+    Start {
+        lang: String,
+        limit: usize,
+    },
+    Key {
+        ms: u64,
+        ch: char,
+    },
+    Word {
+        ms: u64,
+        word: String,
+        wrong: Vec<usize>,
+    },
+}
+
+/// Completed words of a test that was interrupted by a crash.
+pub struct Recovered {
+    pub lang: String,
+    pub limit: usize,
+    /// `(word, wrong char indexes, completion time in ms)` in typing order.
+    pub words: Vec<(String, Vec<usize>, u64)>,
+}
+
+/// Completed words written between two syncs of the journal.
+const SYNC_EVERY: usize = 5;
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("journal.jsonl"))
+}
+
+/// Append-only log of the running test. It is synced to disk every
+/// [`SYNC_EVERY`] words and when the test pauses, so a crash loses at most
+/// the last few words in exchange for not waiting on the disk after each one.
+#[derive(Default)]
+pub struct Journal {
+    file: Option<BufWriter<File>>,
+    /// Words written since the last sync.
+    unsynced: usize,
+}
+
+impl Journal {
+    pub fn begin(&mut self, lang: String, limit: usize) {
+        self.file = path().and_then(|path| {
+            fs::create_dir_all(path.parent()?).ok()?;
+            File::create(path).ok().map(BufWriter::new)
+        });
+        self.unsynced = 0;
+        self.write(&JournalEvent::Start { lang, limit });
+    }
+
+    /// Continues a recovered journal instead of starting a new one.
+    pub fn resume(&mut self) {
+        self.file = path()
+            .and_then(|path| OpenOptions::new().append(true).open(path).ok())
+            .map(BufWriter::new);
+        self.unsynced = 0;
+    }
+
+    pub fn key(&mut self, ms: u64, ch: char) {
+        self.write(&JournalEvent::Key { ms, ch });
+    }
+
+    pub fn word(&mut self, ms: u64, word: &str, wrong: Vec<usize>) {
+        self.write(&JournalEvent::Word {
+            ms,
+            word: word.to_string(),
+            wrong,
+        });
+        self.unsynced += 1;
+        if self.unsynced >= SYNC_EVERY {
+            self.sync();
+        }
+    }
+
+    /// Writes everything logged so far to disk.
+    pub fn sync(&mut self) {
+        if let Some(file) = &mut self.file {
+            let _ = file.flush().and_then(|_| file.get_ref().sync_data());
+        }
+        self.unsynced = 0;
+    }
+
+    /// Drops the journal once the test is finished or abandoned.
+    pub fn discard(&mut self) {
+        if self.file.take().is_some()
+            && let Some(path) = path()
+        {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn write(&mut self, event: &JournalEvent) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let written = serde_json::to_writer(&mut *file, event)
+            .and_then(|_| file.write_all(b"\n").map_err(serde_json::Error::io));
+        if written.is_err() {
+            self.file = None;
+        }
+    }
+}
+
+/// Reads a journal left behind by a crashed session, if any.
+pub fn recover() -> Option<Recovered> {
+    let file = File::open(path()?).ok()?;
+    let mut lines = BufReader::new(file).lines().map_while(Result::ok);
+
+    let JournalEvent::Start { lang, limit } = serde_json::from_str(&lines.next()?).ok()? else {
+        return None;
+    };
+    let words: Vec<_> = lines
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .filter_map(|event| match event {
+            JournalEvent::Word { ms, word, wrong } => Some((word, wrong, ms)),
+            _ => None,
+        })
+        .collect();
+
+    (!words.is_empty()).then_some(Recovered { lang, limit, words })
+}
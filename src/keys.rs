@@ -0,0 +1,481 @@
+use ratatui::crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+
+/// Commands that can be rebound from the config or the Settings popup.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Pause,
+    Restart,
+    Quit,
+    Settings,
+    Plan,
+    Zen,
+    Layouts,
+    Menu,
+    Stats,
+    /// Start screen: the guided tour.
+    Tour,
+    /// Start screen: a test of the clipboard text.
+    Clipboard,
+    /// Start screen: a drill of the regressed n-grams.
+    Drill,
+    /// Start screen: the n-gram practice list.
+    Practice,
+    /// Plan screen: adds a step.
+    AddStep,
+    /// Plan screen: removes the selected step.
+    DeleteStep,
+    /// Plan screen: next language of the selected step.
+    StepLang,
+    MoreRepeats,
+    FewerRepeats,
+    /// Settings popup: filters the settings by name.
+    Search,
+    Undo,
+    Redo,
+    /// Settings popup: applies the settings and saves them to the config.
+    SaveDefaults,
+}
+
+/// Screen whose keys an action belongs to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scope {
+    /// Test, pause, results and the start screen.
+    Global,
+    Menu,
+    Plan,
+    Settings,
+}
+
+impl Action {
+    pub const ALL: [Action; 22] = [
+        Action::Pause,
+        Action::Restart,
+        Action::Quit,
+        Action::Settings,
+        Action::Plan,
+        Action::Zen,
+        Action::Layouts,
+        Action::Menu,
+        Action::Stats,
+        Action::Tour,
+        Action::Clipboard,
+        Action::Drill,
+        Action::Practice,
+        Action::AddStep,
+        Action::DeleteStep,
+        Action::StepLang,
+        Action::MoreRepeats,
+        Action::FewerRepeats,
+        Action::Search,
+        Action::Undo,
+        Action::Redo,
+        Action::SaveDefaults,
+    ];
+
+    pub fn scope(self) -> Scope {
+        match self {
+            Action::Tour | Action::Clipboard | Action::Drill | Action::Practice => Scope::Menu,
+            Action::AddStep
+            | Action::DeleteStep
+            | Action::StepLang
+            | Action::MoreRepeats
+            | Action::FewerRepeats => Scope::Plan,
+            Action::Search | Action::Undo | Action::Redo | Action::SaveDefaults => Scope::Settings,
+            _ => Scope::Global,
+        }
+    }
+
+    /// Whether both actions can be pressed on the same screen, so they need
+    /// keys of their own. The start screen takes the global actions too.
+    fn shares_screen(self, other: Action) -> bool {
+        match (self.scope(), other.scope()) {
+            (Scope::Global, Scope::Menu) | (Scope::Menu, Scope::Global) => true,
+            (scope, other) => scope == other,
+        }
+    }
+}
+
+/// Key names per action, e.g. `"q"`, `"esc"` or `"f2"`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Keymap {
+    pub pause: String,
+    pub restart: String,
+    pub quit: String,
+    pub settings: String,
+    pub plan: String,
+    pub zen: String,
+    pub layouts: String,
+    pub menu: String,
+    pub stats: String,
+    pub tour: String,
+    pub clipboard: String,
+    pub drill: String,
+    pub practice: String,
+    pub add_step: String,
+    pub delete_step: String,
+    pub step_lang: String,
+    pub more_repeats: String,
+    pub fewer_repeats: String,
+    pub search: String,
+    pub undo: String,
+    pub redo: String,
+    pub save_defaults: String,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            pause: "esc".to_string(),
+            restart: "r".to_string(),
+            quit: "q".to_string(),
+            settings: "s".to_string(),
+            plan: "p".to_string(),
+            zen: "z".to_string(),
+            layouts: "k".to_string(),
+            menu: "m".to_string(),
+            stats: "a".to_string(),
+            tour: "t".to_string(),
+            clipboard: "c".to_string(),
+            drill: "d".to_string(),
+            practice: "n".to_string(),
+            add_step: "a".to_string(),
+            delete_step: "d".to_string(),
+            step_lang: "l".to_string(),
+            more_repeats: "+".to_string(),
+            fewer_repeats: "-".to_string(),
+            search: "/".to_string(),
+            undo: "u".to_string(),
+            redo: "r".to_string(),
+            save_defaults: "d".to_string(),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn get(&self, action: Action) -> &str {
+        match action {
+            Action::Pause => &self.pause,
+            Action::Restart => &self.restart,
+            Action::Quit => &self.quit,
+            Action::Settings => &self.settings,
+            Action::Plan => &self.plan,
+            Action::Zen => &self.zen,
+            Action::Layouts => &self.layouts,
+            Action::Menu => &self.menu,
+            Action::Stats => &self.stats,
+            Action::Tour => &self.tour,
+            Action::Clipboard => &self.clipboard,
+            Action::Drill => &self.drill,
+            Action::Practice => &self.practice,
+            Action::AddStep => &self.add_step,
+            Action::DeleteStep => &self.delete_step,
+            Action::StepLang => &self.step_lang,
+            Action::MoreRepeats => &self.more_repeats,
+            Action::FewerRepeats => &self.fewer_repeats,
+            Action::Search => &self.search,
+            Action::Undo => &self.undo,
+            Action::Redo => &self.redo,
+            Action::SaveDefaults => &self.save_defaults,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut String {
+        match action {
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::Quit => &mut self.quit,
+            Action::Settings => &mut self.settings,
+            Action::Plan => &mut self.plan,
+            Action::Zen => &mut self.zen,
+            Action::Layouts => &mut self.layouts,
+            Action::Menu => &mut self.menu,
+            Action::Stats => &mut self.stats,
+            Action::Tour => &mut self.tour,
+            Action::Clipboard => &mut self.clipboard,
+            Action::Drill => &mut self.drill,
+            Action::Practice => &mut self.practice,
+            Action::AddStep => &mut self.add_step,
+            Action::DeleteStep => &mut self.delete_step,
+            Action::StepLang => &mut self.step_lang,
+            Action::MoreRepeats => &mut self.more_repeats,
+            Action::FewerRepeats => &mut self.fewer_repeats,
+            Action::Search => &mut self.search,
+            Action::Undo => &mut self.undo,
+            Action::Redo => &mut self.redo,
+            Action::SaveDefaults => &mut self.save_defaults,
+        }
+    }
+
+    /// Global action bound to `code`, if any.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.screen_action(code, Scope::Global)
+    }
+
+    /// Action of the `scope` screen bound to `code`, if any.
+    pub fn screen_action(&self, code: KeyCode, scope: Scope) -> Option<Action> {
+        let name = key_name(code)?;
+        Action::ALL
+            .into_iter()
+            .find(|&action| action.scope() == scope && self.get(action) == name)
+    }
+
+    /// Key as shown in help lines, e.g. `Q` or `Esc`.
+    pub fn label(&self, action: Action) -> String {
+        let name = self.get(action);
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) if name.chars().count() == 1 => first.to_uppercase().to_string(),
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// Binds `code` to `action`, refusing keys that are reserved by the
+    /// screens or already bound to another action of the same screen.
+    pub fn bind(&mut self, action: Action, code: KeyCode) -> Result<(), String> {
+        let name = allowed(action, code)?;
+        if let Some(other) = Action::ALL.into_iter().find(|&other| {
+            other != action && action.shares_screen(other) && self.get(other) == name
+        }) {
+            return Err(format!("{} is already bound to {:?}", name, other));
+        }
+        *self.get_mut(action) = name;
+        Ok(())
+    }
+
+    /// Checks bindings read from the config the way [`bind`](Self::bind)
+    /// checks recorded ones: every key has to exist, be free to bind and
+    /// belong to one action of each screen only.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, action) in Action::ALL.into_iter().enumerate() {
+            let name = self.get(action);
+            let code = parse_key(name).ok_or_else(|| {
+                format!(
+                    "{:?} is bound to \"{}\", which is not a key (keys are named in lowercase, e.g. \"q\", \"esc\" or \"f2\")",
+                    action, name
+                )
+            })?;
+            allowed(action, code).map_err(|err| format!("{} for {:?}: {}", name, action, err))?;
+            if let Some(other) = Action::ALL[i + 1..]
+                .iter()
+                .find(|&&other| action.shares_screen(other) && self.get(other) == name)
+            {
+                return Err(format!(
+                    "{} is bound to both {:?} and {:?}",
+                    name, action, other
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Name of `code` when `action` may be bound to it.
+fn allowed(action: Action, code: KeyCode) -> Result<String, String> {
+    let name = key_name(code).ok_or("This key can't be bound")?;
+    if matches!(
+        code,
+        KeyCode::Enter
+            | KeyCode::Backspace
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Char('1'..='5')
+    ) {
+        return Err(format!("{} is reserved", name));
+    }
+    // Printable keys are typed during the test, so pausing needs another one.
+    if action == Action::Pause && matches!(code, KeyCode::Char(_)) {
+        return Err("Pause needs a non-character key".to_string());
+    }
+    // Keys the screens themselves use to leave, delete and page.
+    let used = match action.scope() {
+        Scope::Global => false,
+        Scope::Menu => code == KeyCode::Esc,
+        Scope::Plan => matches!(code, KeyCode::Esc | KeyCode::Delete),
+        Scope::Settings => matches!(code, KeyCode::Esc | KeyCode::PageUp | KeyCode::PageDown),
+    };
+    if used {
+        return Err(format!("{} is reserved", name));
+    }
+    Ok(name)
+}
+
+/// Key of a name as [`key_name`] writes it.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) => KeyCode::Char(ch),
+        _ => match name {
+            "space" => KeyCode::Char(' '),
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            _ => KeyCode::F(
+                name.strip_prefix('f')?
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=12).contains(n))?,
+            ),
+        },
+    };
+    // Names are matched as written, so "Q" would never be pressed.
+    (key_name(code).as_deref() == Some(name)).then_some(code)
+}
+
+pub fn key_name(code: KeyCode) -> Option<String> {
+    Some(match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(ch) => ch.to_lowercase().to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_keymap_is_valid() {
+        assert_eq!(Keymap::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        for name in ["q", "space", "esc", "tab", "pagedown", "f1", "f12"] {
+            let code = parse_key(name).unwrap();
+            assert_eq!(key_name(code).as_deref(), Some(name));
+        }
+        for name in ["Q", "Esc", "f13", "f0", "ctrl", ""] {
+            assert_eq!(parse_key(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn validate_refuses_unknown_and_reserved_keys() {
+        let keymap = Keymap {
+            quit: "Q".to_string(),
+            ..Keymap::default()
+        };
+        assert!(keymap.validate().unwrap_err().contains("not a key"));
+
+        let keymap = Keymap {
+            menu: "enter".to_string(),
+            ..Keymap::default()
+        };
+        assert!(keymap.validate().unwrap_err().contains("reserved"));
+
+        let keymap = Keymap {
+            layouts: "3".to_string(),
+            ..Keymap::default()
+        };
+        assert!(keymap.validate().unwrap_err().contains("reserved"));
+    }
+
+    #[test]
+    fn pause_needs_a_non_character_key() {
+        let keymap = Keymap {
+            pause: "x".to_string(),
+            ..Keymap::default()
+        };
+        assert!(keymap.validate().unwrap_err().contains("non-character"));
+
+        let keymap = Keymap {
+            pause: "f5".to_string(),
+            ..Keymap::default()
+        };
+        assert_eq!(keymap.validate(), Ok(()));
+    }
+
+    #[test]
+    fn keys_belong_to_one_action() {
+        let keymap = Keymap {
+            zen: "q".to_string(),
+            ..Keymap::default()
+        };
+        assert_eq!(
+            keymap.validate(),
+            Err("q is bound to both Quit and Zen".to_string())
+        );
+
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.bind(Action::Zen, KeyCode::Char('q')),
+            Err("q is already bound to Quit".to_string())
+        );
+        assert_eq!(keymap.bind(Action::Zen, KeyCode::Char('x')), Ok(()));
+        assert_eq!(keymap.action(KeyCode::Char('x')), Some(Action::Zen));
+        assert_eq!(keymap.label(Action::Pause), "Esc");
+    }
+
+    #[test]
+    fn screen_keys_only_clash_on_their_screen() {
+        // Undo and AddStep share a key with Stats, each on its own screen.
+        let keymap = Keymap {
+            undo: "a".to_string(),
+            ..Keymap::default()
+        };
+        assert_eq!(keymap.validate(), Ok(()));
+        assert_eq!(keymap.action(KeyCode::Char('a')), Some(Action::Stats));
+        assert_eq!(
+            keymap.screen_action(KeyCode::Char('a'), Scope::Plan),
+            Some(Action::AddStep)
+        );
+
+        // The start screen also takes the global keys.
+        let keymap = Keymap {
+            tour: "q".to_string(),
+            ..Keymap::default()
+        };
+        assert_eq!(
+            keymap.validate(),
+            Err("q is bound to both Quit and Tour".to_string())
+        );
+
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.bind(Action::Redo, KeyCode::Char('u')),
+            Err("u is already bound to Undo".to_string())
+        );
+        assert!(
+            keymap
+                .bind(Action::DeleteStep, KeyCode::Delete)
+                .unwrap_err()
+                .contains("reserved")
+        );
+        assert_eq!(keymap.bind(Action::Redo, KeyCode::Char('y')), Ok(()));
+    }
+}
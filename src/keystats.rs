@@ -0,0 +1,85 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::data_dir;
+
+/// Keys weak mode favors words with.
+pub const WEAK_KEYS: usize = 5;
+/// Presses of a key before its error rate makes it a weak key.
+const MIN_PRESSES: u64 = 20;
+
+/// Keystroke counts accumulated over every finished test.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct KeyStats {
+    #[serde(default)]
+    pub chars: BTreeMap<char, u64>,
+    #[serde(default)]
+    pub bigrams: BTreeMap<String, u64>,
+    /// Mistakes by the key that should have been pressed.
+    #[serde(default)]
+    pub errors: BTreeMap<char, u64>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("keystats.json"))
+}
+
+impl KeyStats {
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, word: &str) {
+        let chars: Vec<char> = word.to_lowercase().chars().collect();
+        for ch in &chars {
+            *self.chars.entry(*ch).or_default() += 1;
+        }
+        for pair in chars.windows(2) {
+            *self.bigrams.entry(pair.iter().collect()).or_default() += 1;
+        }
+    }
+
+    pub fn record_errors(&mut self, errors: &BTreeMap<char, u32>) {
+        for (&ch, &count) in errors {
+            *self.errors.entry(ch).or_default() += count as u64;
+        }
+    }
+
+    /// Up to `n` keys with the highest error rates, highest first. Keys
+    /// pressed fewer than [`MIN_PRESSES`] times say too little to count.
+    pub fn weakest(&self, n: usize) -> Vec<(char, f32)> {
+        let mut rates: Vec<(char, f32)> = self
+            .errors
+            .keys()
+            .filter(|ch| self.chars.get(ch).copied().unwrap_or_default() >= MIN_PRESSES)
+            .map(|&ch| (ch, self.error_rate(ch)))
+            .filter(|&(_, rate)| rate > 0.0)
+            .collect();
+        rates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        rates.truncate(n);
+        rates
+    }
+
+    /// Share of the presses of `ch` that were mistakes, in percent.
+    pub fn error_rate(&self, ch: char) -> f32 {
+        let errors = self.errors.get(&ch).copied().unwrap_or_default();
+        let presses = self.chars.get(&ch).copied().unwrap_or_default().max(errors);
+        if presses == 0 {
+            return 0.0;
+        }
+        errors as f32 / presses as f32 * 100.0
+    }
+}
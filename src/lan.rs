@@ -0,0 +1,338 @@
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Read, Write},
+    net::{Ipv4Addr, SocketAddrV4, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+/// How often the relay looks for new players and lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Unsent bytes a player may fall behind by before they are dropped.
+const MAX_BACKLOG: usize = 1 << 20;
+/// Bytes of a line a player may send without ending it before they are
+/// dropped.
+const MAX_LINE: usize = 1 << 20;
+/// Crockford's base 32, without letters easily read as digits.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// A LAN race hosted by this instance or joined through its code.
+pub struct Lobby {
+    pub code: String,
+    /// The relay players connect to, when this instance hosts.
+    pub relay: Option<Relay>,
+    /// Words of the race last started, typed by every player.
+    pub words: Vec<String>,
+}
+
+impl Lobby {
+    pub fn is_host(&self) -> bool {
+        self.relay.is_some()
+    }
+}
+
+/// Starts a relay on `port` and returns the lobby hosting it.
+pub fn host(port: u16) -> Result<Lobby, Box<dyn std::error::Error>> {
+    let relay = Relay::start(port)?;
+    Ok(Lobby {
+        code: code(SocketAddrV4::new(lan_ip(), port)),
+        relay: Some(relay),
+        words: Vec::new(),
+    })
+}
+
+pub fn join(code: &str) -> Lobby {
+    Lobby {
+        code: code.to_string(),
+        relay: None,
+        words: Vec::new(),
+    }
+}
+
+/// Address of this machine on the LAN. Connecting a UDP socket sends
+/// nothing, it only picks the interface a packet would leave through.
+fn lan_ip() -> Ipv4Addr {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 168, 0, 1), 9))?;
+            socket.local_addr()
+        })
+        .ok()
+        .and_then(|addr| match addr.ip() {
+            std::net::IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+            _ => None,
+        })
+        .unwrap_or(Ipv4Addr::LOCALHOST)
+}
+
+/// Join code of a host address: its 48 bits in base 32, e.g. "61GK2-0FMB8".
+pub fn code(addr: SocketAddrV4) -> String {
+    let bits = (u32::from(*addr.ip()) as u64) << 16 | addr.port() as u64;
+    let chars: String = (0..10)
+        .rev()
+        .map(|i| ALPHABET[(bits >> (i * 5) & 31) as usize] as char)
+        .collect();
+    format!("{}-{}", &chars[..5], &chars[5..])
+}
+
+/// Address of a join code, ignoring case and dashes and reading I and L as 1
+/// and O as 0.
+pub fn parse_code(code: &str) -> Option<SocketAddrV4> {
+    let mut bits: u64 = 0;
+    let mut digits = 0;
+    for ch in code.chars().filter(|&ch| ch != '-') {
+        let ch = match ch.to_ascii_uppercase() {
+            'I' | 'L' => '1',
+            'O' => '0',
+            ch => ch,
+        };
+        let digit = ALPHABET.iter().position(|&c| c as char == ch)?;
+        bits = bits << 5 | digit as u64;
+        digits += 1;
+    }
+    if digits != 10 || bits >> 48 != 0 {
+        return None;
+    }
+    let ip = Ipv4Addr::from((bits >> 16) as u32);
+    Some(SocketAddrV4::new(ip, bits as u16))
+}
+
+/// Line relay of a hosted race: every line a player sends reaches all the
+/// others. A player joining gets the race last started and the latest
+/// progress of everyone already there. Runs on its own thread until dropped.
+pub struct Relay {
+    shutdown: Arc<AtomicBool>,
+}
+
+struct Player {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    /// Bytes not yet taken by the socket, sent as it drains.
+    outgoing: Vec<u8>,
+    /// Latest progress line, replayed to players joining later.
+    latest: Option<String>,
+}
+
+impl Player {
+    fn send(&mut self, line: &str) {
+        self.outgoing.extend_from_slice(line.as_bytes());
+        self.outgoing.push(b'\n');
+    }
+
+    /// Writes what the socket takes without blocking. Fails once the
+    /// connection is broken or the player stopped reading.
+    fn flush(&mut self) -> std::io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+        if self.outgoing.len() > MAX_BACKLOG {
+            return Err(ErrorKind::TimedOut.into());
+        }
+        Ok(())
+    }
+}
+
+/// Lines and players of a hosted race, kept by the relay thread.
+#[derive(Default)]
+struct Relayed {
+    players: HashMap<usize, Player>,
+    next: usize,
+    /// Latest start line, replayed to players joining later.
+    start: Option<String>,
+}
+
+impl Relay {
+    pub fn start(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        listener.set_nonblocking(true)?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            let mut relayed = Relayed::default();
+            while !stop.load(Ordering::Relaxed) {
+                while let Ok((stream, _)) = listener.accept() {
+                    relayed.admit(stream);
+                }
+                relayed.relay();
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+        Ok(Self { shutdown })
+    }
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Relayed {
+    /// Adds a player, catching them up on the race and everyone's progress.
+    fn admit(&mut self, stream: TcpStream) {
+        if stream.set_nonblocking(true).is_err() {
+            return;
+        }
+        let mut player = Player {
+            stream,
+            pending: Vec::new(),
+            outgoing: Vec::new(),
+            latest: None,
+        };
+        let known = self
+            .start
+            .iter()
+            .chain(self.players.values().filter_map(|p| p.latest.as_ref()));
+        for line in known {
+            player.send(line);
+        }
+        if player.flush().is_ok() {
+            self.players.insert(self.next, player);
+            self.next += 1;
+        }
+    }
+
+    /// Reads what every player sent and passes each complete line on to the
+    /// others, dropping players whose connection closed, who stopped reading
+    /// or whose line never ends.
+    fn relay(&mut self) {
+        let mut buf = [0u8; 4096];
+        let mut lines: Vec<(usize, String)> = Vec::new();
+        let mut gone: Vec<usize> = Vec::new();
+        for (&id, player) in self.players.iter_mut() {
+            loop {
+                match player.stream.read(&mut buf) {
+                    Ok(0) => {
+                        gone.push(id);
+                        break;
+                    }
+                    Ok(n) => {
+                        player.pending.extend_from_slice(&buf[..n]);
+                        // The rest waits for the next round, after the
+                        // complete lines are taken.
+                        if player.pending.len() > MAX_LINE {
+                            break;
+                        }
+                    }
+                    Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(_) => {
+                        gone.push(id);
+                        break;
+                    }
+                }
+            }
+            while let Some(end) = player.pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = player.pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line).trim_end().to_string();
+                if !line.is_empty() {
+                    lines.push((id, line));
+                }
+            }
+            if player.pending.len() > MAX_LINE {
+                gone.push(id);
+            }
+        }
+        for (from, line) in lines {
+            if line.starts_with("start\t") {
+                // Progress of the last race means nothing in the next one.
+                self.start = Some(line.clone());
+                for player in self.players.values_mut() {
+                    player.latest = None;
+                }
+            } else if line.starts_with("progress\t")
+                && let Some(player) = self.players.get_mut(&from)
+            {
+                player.latest = Some(line.clone());
+            }
+            for (&id, player) in self.players.iter_mut() {
+                if id != from {
+                    player.send(&line);
+                }
+            }
+        }
+        for (&id, player) in self.players.iter_mut() {
+            if player.flush().is_err() {
+                gone.push(id);
+            }
+        }
+        for id in gone {
+            self.players.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for addr in [
+            SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 23), 7878),
+            SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
+            SocketAddrV4::new(Ipv4Addr::BROADCAST, u16::MAX),
+        ] {
+            let code = code(addr);
+            assert_eq!(code.len(), 11);
+            assert_eq!(parse_code(&code), Some(addr));
+        }
+    }
+
+    #[test]
+    fn codes_forgive_case_dashes_and_lookalikes() {
+        let addr = SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 1010);
+        let code = code(addr);
+        assert_eq!(parse_code(&code.to_lowercase()), Some(addr));
+        assert_eq!(parse_code(&code.replace('-', "")), Some(addr));
+        let lookalikes = code.replace('0', "O").replace('1', "l");
+        assert_eq!(parse_code(&lookalikes), Some(addr));
+    }
+
+    #[test]
+    fn malformed_codes_are_refused() {
+        assert_eq!(parse_code(""), None);
+        assert_eq!(parse_code("ABCDE-FGH"), None);
+        assert_eq!(parse_code("ABCDE-FGHJKM"), None);
+        // U is not in the alphabet.
+        assert_eq!(parse_code("ABCDE-FGHJU"), None);
+        // More than 48 bits.
+        assert_eq!(parse_code("ZZZZZ-ZZZZZ"), None);
+    }
+
+    #[test]
+    fn players_never_ending_a_line_are_dropped() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut relayed = Relayed::default();
+        relayed.admit(listener.accept().unwrap().0);
+        assert_eq!(relayed.players.len(), 1);
+
+        // The connection stays open, only the line is too long.
+        let writer = thread::spawn(move || {
+            let _ = client.write_all(&vec![b'x'; MAX_LINE + 1]);
+            client
+        });
+        for _ in 0..500 {
+            if relayed.players.is_empty() {
+                break;
+            }
+            relayed.relay();
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert!(relayed.players.is_empty());
+        drop(writer.join());
+    }
+}
@@ -0,0 +1,152 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::keystats::KeyStats;
+
+pub struct KeyboardLayout {
+    pub name: &'static str,
+    rows: [&'static str; 3],
+}
+
+pub const LAYOUTS: [KeyboardLayout; 3] = [
+    KeyboardLayout {
+        name: "QWERTY",
+        rows: ["qwertyuiop", "asdfghjkl;", "zxcvbnm,./"],
+    },
+    KeyboardLayout {
+        name: "Colemak",
+        rows: ["qwfpgjluy;", "arstdhneio", "zxcvbkm,./"],
+    },
+    KeyboardLayout {
+        name: "Dvorak",
+        rows: ["',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"],
+    },
+];
+
+/// Finger used for each of the ten key columns, left pinky to right pinky.
+const FINGERS: [usize; 10] = [0, 1, 2, 3, 3, 4, 4, 5, 6, 7];
+/// Home row column of each finger.
+const HOME: [usize; 8] = [0, 1, 2, 3, 6, 7, 8, 9];
+const HOME_ROW: usize = 1;
+
+pub struct Analysis {
+    /// Average distance in key widths a finger leaves its home key per keystroke.
+    pub travel: f32,
+    /// Share of bigrams typed twice in a row with the same finger.
+    pub same_finger: f32,
+    pub home_row: f32,
+}
+
+impl KeyboardLayout {
+    fn position(&self, ch: char) -> Option<(usize, usize)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| keys.chars().position(|k| k == ch).map(|col| (row, col)))
+    }
+
+    pub fn analyze(&self, stats: &KeyStats) -> Analysis {
+        let (mut total, mut travel, mut home) = (0u64, 0.0, 0u64);
+        for (&ch, &count) in &stats.chars {
+            let Some((row, col)) = self.position(ch) else {
+                continue;
+            };
+            let home_col = HOME[FINGERS[col]];
+            let distance = ((row as f32 - HOME_ROW as f32).powi(2)
+                + (col as f32 - home_col as f32).powi(2))
+            .sqrt();
+            total += count;
+            travel += distance * count as f32;
+            if row == HOME_ROW {
+                home += count;
+            }
+        }
+
+        let (mut bigrams, mut same_finger) = (0u64, 0u64);
+        for (pair, &count) in &stats.bigrams {
+            let mut chars = pair.chars();
+            let (Some(a), Some(b)) = (chars.next(), chars.next()) else {
+                continue;
+            };
+            let (Some((_, col_a)), Some((_, col_b))) = (self.position(a), self.position(b)) else {
+                continue;
+            };
+            bigrams += count;
+            if a != b && FINGERS[col_a] == FINGERS[col_b] {
+                same_finger += count;
+            }
+        }
+
+        let share = |part: u64, whole: u64| match whole {
+            0 => 0.0,
+            _ => part as f32 / whole as f32 * 100.0,
+        };
+        Analysis {
+            travel: if total == 0 {
+                0.0
+            } else {
+                travel / total as f32
+            },
+            same_finger: share(same_finger, bigrams),
+            home_row: share(home, total),
+        }
+    }
+}
+
+/// Key grid of a layout colored by how often each key was typed.
+pub struct Heatmap<'a> {
+    layout: &'a KeyboardLayout,
+    stats: &'a KeyStats,
+}
+
+impl<'a> Heatmap<'a> {
+    pub fn new(layout: &'a KeyboardLayout, stats: &'a KeyStats) -> Self {
+        Self { layout, stats }
+    }
+}
+
+fn heat_color(ratio: f32) -> Color {
+    match ratio {
+        r if r <= 0.0 => Color::DarkGray,
+        r if r < 0.25 => Color::Blue,
+        r if r < 0.5 => Color::Green,
+        r if r < 0.75 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+impl Widget for Heatmap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let max = self.stats.chars.values().copied().max().unwrap_or(0).max(1);
+        for (i, keys) in self
+            .layout
+            .rows
+            .iter()
+            .enumerate()
+            .take(area.height as usize)
+        {
+            let spans: Vec<Span> = std::iter::once(Span::raw(" ".repeat(i)))
+                .chain(keys.chars().map(|key| {
+                    let count = self.stats.chars.get(&key).copied().unwrap_or(0);
+                    Span::styled(
+                        format!(" {} ", key),
+                        Style::new()
+                            .fg(Color::Black)
+                            .bg(heat_color(count as f32 / max as f32)),
+                    )
+                }))
+                .collect();
+            let row = Rect {
+                y: area.y + i as u16,
+                height: 1,
+                ..area
+            };
+            Line::from(spans).render(row, buf);
+        }
+    }
+}
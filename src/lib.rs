@@ -0,0 +1,206 @@
+mod actions;
+mod app;
+mod blocklist;
+mod cache;
+mod calendar;
+mod chart;
+pub mod cli;
+mod clipboard;
+mod clock;
+mod cohort;
+mod config;
+mod corpus;
+mod dashboard;
+mod daytime;
+mod devtools;
+mod drills;
+mod export;
+mod filter;
+mod gate;
+mod ghosts;
+mod governor;
+mod graphemes;
+mod history;
+mod input;
+mod journal;
+mod keys;
+mod keystats;
+mod lan;
+mod layouts;
+mod metrics;
+mod migrate;
+mod modifiers;
+mod monkeytype;
+mod net;
+mod ngrams;
+mod packs;
+mod plan;
+mod profile;
+mod quiet;
+mod quotes;
+mod race;
+mod records;
+mod replay;
+mod report;
+mod rivals;
+mod score;
+mod settings;
+mod snippets;
+mod soak;
+mod sound;
+mod stats;
+mod strip;
+mod telemetry;
+mod theme;
+mod tour;
+mod trend;
+mod ui;
+mod words;
+mod worker;
+mod zen;
+
+use std::{io::stdout, process::ExitCode};
+
+use blocklist::Blocklist;
+use cli::Cli;
+use config::CONFIG;
+use filter::WordFilter;
+use gate::Gate;
+use ratatui::crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+};
+
+pub use app::App;
+pub use config::Config;
+pub use input::run;
+
+/// Runs what the command line asks for: a soak run, a subcommand or the app.
+pub fn start(cli: Cli) -> std::io::Result<ExitCode> {
+    if let Some(tests) = cli.soak {
+        if let Err(err) = soak::run(tests) {
+            eprintln!("Soak failed: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+    // Data files are brought up to this version's format before anything reads them.
+    match migrate::run() {
+        Ok(Some(backup)) => eprintln!(
+            "Data files upgraded, the previous ones are kept in {}",
+            backup.display()
+        ),
+        Ok(None) => {}
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+    match cli.command {
+        Some(command) => {
+            if let Err(err) = cli::execute(command) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        None => show(&cli),
+    }
+}
+
+fn show(cli: &Cli) -> std::io::Result<ExitCode> {
+    if let Some(Err(err)) = CONFIG.score.as_deref().map(score::Formula::parse) {
+        eprintln!("Invalid score in the config: {}", err);
+        std::process::exit(1);
+    }
+    let mut config = CONFIG.clone();
+    cli.apply(&mut config);
+
+    // Unusable bindings would leave actions without a key for the session.
+    if let Err(err) = config.keys.validate() {
+        eprintln!("Invalid [keys] in the config: {}", err);
+        std::process::exit(1);
+    }
+    // Bad word files and filters are reported before the terminal switches screens.
+    let filter = WordFilter::new(config.charset.as_deref(), config.pattern.as_deref())
+        .map(|filter| filter.with_lengths(config.min_length, config.max_length));
+    let files = config
+        .wordlist
+        .as_deref()
+        .map(words::load_wordlist)
+        .transpose()
+        .and_then(|wordlist| {
+            let blocklist = config
+                .blocklist
+                .as_deref()
+                .map(Blocklist::load)
+                .transpose()?;
+            let corpus = config.corpus.as_deref().map(corpus::load).transpose()?;
+            Ok((wordlist, blocklist, corpus, filter?))
+        });
+    let (wordlist, blocklist, corpus, filter) = match files {
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut app = App::from(&config);
+    app.corpus = corpus;
+    let filtered = !filter.is_empty();
+    if wordlist.is_some() || blocklist.is_some() || filtered {
+        app.word_gen.set_wordlist(wordlist);
+        app.word_gen.set_blocklist(blocklist.unwrap_or_default());
+        app.word_gen.set_filter(filter);
+        if filtered && app.word_gen.pool(app.lang).is_empty() {
+            eprintln!("No words in the dictionary match the charset, pattern and lengths");
+            std::process::exit(1);
+        }
+        // Words queued for the line were drawn before any of this applied.
+        app.target.clear();
+        app.new_word();
+    }
+
+    if let Some(port) = cli.host {
+        match lan::host(port) {
+            Ok(lobby) => app.open_lobby(lobby, &format!("127.0.0.1:{}", port)),
+            Err(err) => {
+                eprintln!("Could not host a race on port {}: {}", port, err);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(code) = &cli.join {
+        match lan::parse_code(code) {
+            Some(addr) => app.open_lobby(lan::join(code), &addr.to_string()),
+            None => {
+                eprintln!("Not a race code: {}", code);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if cli.gate {
+        app.gate = Some(Gate::new(config.gate_wpm, config.gate_accuracy));
+        app.restart();
+    }
+
+    let mut term = ratatui::init();
+
+    if config.limit == 0 {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Mouse reporting is turned off again before the shell gets the terminal back.
+    let _ = execute!(stdout(), EnableMouseCapture);
+    let result = run(&mut term, &mut app);
+    let _ = execute!(stdout(), DisableMouseCapture);
+    ratatui::restore();
+
+    let code = app.gate.map_or(ExitCode::SUCCESS, |gate| gate.exit_code());
+    // Dropping the app writes the last usage counts before they are sent.
+    drop(app);
+    // Telemetry never gets in the way of quitting; unsent counts wait for next time.
+    let _ = telemetry::send(&config);
+    result.map(|()| code)
+}
@@ -0,0 +1,293 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clock::wpm, graphemes};
+
+/// A completed word as seen by the metrics.
+pub struct WordSample<'a> {
+    pub word: &'a str,
+    pub wrong: usize,
+    /// Time since the previous word was completed.
+    pub time: Duration,
+}
+
+/// A statistic fed while the test runs and summarized once it is over.
+///
+/// New metrics only need an implementation and a `MetricKind` entry; the
+/// event loop drives every enabled metric through `Metrics`.
+pub trait Metric {
+    fn label(&self) -> &'static str;
+
+    /// Called for every typed character.
+    fn key(&mut self, _correct: bool) {}
+
+    /// Called for every completed word.
+    fn word(&mut self, _word: &WordSample) {}
+
+    /// Displayed value for a test that took `elapsed`.
+    fn finalize(&self, elapsed: Duration) -> String;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricKind {
+    /// Correct characters per minute, five characters per word.
+    Wpm,
+    /// Every keystroke per minute, mistakes included.
+    Raw,
+    Accuracy,
+    /// How even the per-word speed was, 100% being perfectly steady.
+    Consistency,
+    /// Fastest single word.
+    Burst,
+}
+
+impl MetricKind {
+    pub const ALL: [MetricKind; 5] = [
+        MetricKind::Wpm,
+        MetricKind::Raw,
+        MetricKind::Accuracy,
+        MetricKind::Consistency,
+        MetricKind::Burst,
+    ];
+
+    fn build(self) -> Box<dyn Metric> {
+        match self {
+            MetricKind::Wpm => Box::new(Wpm::default()),
+            MetricKind::Raw => Box::new(Raw::default()),
+            MetricKind::Accuracy => Box::new(Accuracy::default()),
+            MetricKind::Consistency => Box::new(Consistency::default()),
+            MetricKind::Burst => Box::new(Burst::default()),
+        }
+    }
+}
+
+pub fn default_metrics() -> Vec<MetricKind> {
+    MetricKind::ALL.to_vec()
+}
+
+/// The metrics enabled in the config, in the order they are displayed.
+#[derive(Default)]
+pub struct Metrics {
+    kinds: Vec<MetricKind>,
+    active: Vec<Box<dyn Metric>>,
+}
+
+impl Metrics {
+    pub fn new(kinds: &[MetricKind]) -> Self {
+        let mut kinds = kinds.to_vec();
+        kinds.dedup();
+        Self {
+            active: kinds.iter().map(|kind| kind.build()).collect(),
+            kinds,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(&self.kinds);
+    }
+
+    pub fn key(&mut self, correct: bool) {
+        for metric in &mut self.active {
+            metric.key(correct);
+        }
+    }
+
+    pub fn word(&mut self, word: &WordSample) {
+        for metric in &mut self.active {
+            metric.word(word);
+        }
+    }
+
+    /// `(label, value)` of every enabled metric.
+    pub fn summary(&self, elapsed: Duration) -> Vec<(&'static str, String)> {
+        self.active
+            .iter()
+            .map(|metric| (metric.label(), metric.finalize(elapsed)))
+            .collect()
+    }
+}
+
+fn word_wpm(word: &WordSample) -> f32 {
+    wpm(graphemes::count(word.word) - word.wrong, word.time)
+}
+
+#[derive(Default)]
+struct Wpm {
+    correct: usize,
+}
+
+impl Metric for Wpm {
+    fn label(&self) -> &'static str {
+        "wpm"
+    }
+
+    fn word(&mut self, word: &WordSample) {
+        self.correct += graphemes::count(word.word) - word.wrong;
+    }
+
+    fn finalize(&self, elapsed: Duration) -> String {
+        format!("{:.0}", wpm(self.correct, elapsed))
+    }
+}
+
+#[derive(Default)]
+struct Raw {
+    keys: usize,
+}
+
+impl Metric for Raw {
+    fn label(&self) -> &'static str {
+        "raw"
+    }
+
+    fn key(&mut self, _correct: bool) {
+        self.keys += 1;
+    }
+
+    fn finalize(&self, elapsed: Duration) -> String {
+        format!("{:.0}", wpm(self.keys, elapsed))
+    }
+}
+
+#[derive(Default)]
+struct Accuracy {
+    chars: usize,
+    wrong: usize,
+}
+
+impl Metric for Accuracy {
+    fn label(&self) -> &'static str {
+        "accuracy"
+    }
+
+    fn word(&mut self, word: &WordSample) {
+        self.chars += graphemes::count(word.word);
+        self.wrong += word.wrong;
+    }
+
+    fn finalize(&self, _elapsed: Duration) -> String {
+        if self.chars == 0 {
+            return "100.00%".to_string();
+        }
+        let correct = self.chars - self.wrong;
+        format!("{:.2}%", correct as f32 / self.chars as f32 * 100.0)
+    }
+}
+
+#[derive(Default)]
+struct Consistency {
+    speeds: Vec<f32>,
+}
+
+impl Metric for Consistency {
+    fn label(&self) -> &'static str {
+        "consistency"
+    }
+
+    fn word(&mut self, word: &WordSample) {
+        // A single-character first word completes the instant the test starts.
+        if !word.time.is_zero() {
+            self.speeds.push(word_wpm(word));
+        }
+    }
+
+    fn finalize(&self, _elapsed: Duration) -> String {
+        consistency(&self.speeds).map_or("-".to_string(), |c| format!("{:.0}%", c))
+    }
+}
+
+/// 100% minus the coefficient of variation of per-word `speeds`, none
+/// without any speed.
+pub fn consistency(speeds: &[f32]) -> Option<f32> {
+    let n = speeds.len() as f32;
+    let mean = speeds.iter().sum::<f32>() / n.max(1.0);
+    if mean <= 0.0 {
+        return None;
+    }
+    let variance = speeds.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / n;
+    let cv = variance.sqrt() / mean;
+    Some(((1.0 - cv) * 100.0).max(0.0))
+}
+
+#[derive(Default)]
+struct Burst {
+    best: f32,
+}
+
+impl Metric for Burst {
+    fn label(&self) -> &'static str {
+        "burst"
+    }
+
+    fn word(&mut self, word: &WordSample) {
+        self.best = self.best.max(word_wpm(word));
+    }
+
+    fn finalize(&self, _elapsed: Duration) -> String {
+        format!("{:.0}", self.best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(word: &str, wrong: usize, millis: u64) -> WordSample<'_> {
+        WordSample {
+            word,
+            wrong,
+            time: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn summary_follows_the_configured_order() {
+        let mut metrics = Metrics::new(&[MetricKind::Accuracy, MetricKind::Wpm, MetricKind::Wpm]);
+        for correct in [true, true, true, true, false] {
+            metrics.key(correct);
+        }
+        metrics.word(&sample("hello", 1, 1000));
+        let summary = metrics.summary(Duration::from_secs(6));
+        assert_eq!(
+            summary,
+            [("accuracy", "80.00%".to_string()), ("wpm", "8".to_string())]
+        );
+    }
+
+    #[test]
+    fn instant_words_are_left_out_of_consistency() {
+        let mut metrics = Metrics::new(&[MetricKind::Consistency, MetricKind::Burst]);
+        metrics.word(&sample("a", 0, 0));
+        metrics.word(&sample("hello", 0, 1000));
+        metrics.word(&sample("hello", 0, 1000));
+        let summary = metrics.summary(Duration::from_secs(2));
+        assert_eq!(summary[0], ("consistency", "100%".to_string()));
+        assert_eq!(summary[1], ("burst", "60".to_string()));
+    }
+
+    #[test]
+    fn accuracy_without_words_is_perfect() {
+        let metrics = Metrics::new(&[MetricKind::Accuracy, MetricKind::Consistency]);
+        assert_eq!(
+            metrics.summary(Duration::ZERO),
+            [
+                ("accuracy", "100.00%".to_string()),
+                ("consistency", "-".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn steady_speed_is_fully_consistent() {
+        assert_eq!(consistency(&[60.0, 60.0, 60.0]), Some(100.0));
+        assert_eq!(
+            consistency(&[40.0, 80.0]),
+            Some((1.0 - 20.0 / 60.0) * 100.0)
+        );
+        assert_eq!(consistency(&[]), None);
+        // Wildly uneven speeds bottom out at zero.
+        assert_eq!(consistency(&[0.0, 0.0, 300.0]), Some(0.0));
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{config::data_dir, daytime, history};
+
+/// Rewrites a data file in a directory from one format to the next.
+type Migration = fn(&Path) -> Result<(), Box<dyn std::error::Error>>;
+
+/// A data file and the forward migrations of its format, the one at index
+/// `n` taking it from format `n` to `n + 1`.
+struct DataFile {
+    name: &'static str,
+    migrations: &'static [Migration],
+}
+
+impl DataFile {
+    /// Format this build reads and writes.
+    fn format(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+}
+
+/// Data files whose format changed, each raised with a migration below.
+const FILES: [DataFile; 1] = [DataFile {
+    name: "history.jsonl",
+    migrations: &[complete_history],
+}];
+
+/// Stamp kept next to the data files, naming the version that wrote them
+/// and the format of each file.
+#[derive(Serialize, Deserialize)]
+struct Stamp {
+    app: String,
+    /// Files missing here were written before their format was stamped.
+    #[serde(default)]
+    formats: BTreeMap<String, u32>,
+}
+
+impl Stamp {
+    fn current() -> Self {
+        Self {
+            app: env!("CARGO_PKG_VERSION").to_string(),
+            formats: FILES
+                .iter()
+                .map(|file| (file.name.to_string(), file.format()))
+                .collect(),
+        }
+    }
+
+    fn format(&self, file: &DataFile) -> u32 {
+        self.formats.get(file.name).copied().unwrap_or_default()
+    }
+}
+
+fn path(dir: &Path) -> PathBuf {
+    dir.join("version.json")
+}
+
+/// Brings the data files up to the formats of this build before anything
+/// reads them, after copying them to a backup directory. Returns the backup
+/// made, if any. Data written in a newer format is left alone and reported as
+/// an error.
+pub fn run() -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    match data_dir() {
+        Some(dir) => migrate(&dir),
+        None => Ok(None),
+    }
+}
+
+fn migrate(dir: &Path) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let stamp = match fs::read_to_string(path(dir)) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|err| format!("Could not read {}: {}", path(dir).display(), err))?,
+        // Files written before stamps existed.
+        Err(_) if has_data(dir) => Stamp {
+            app: "unknown".to_string(),
+            formats: BTreeMap::new(),
+        },
+        Err(_) => return write_stamp(dir).map(|_| None),
+    };
+
+    if let Some(file) = FILES.iter().find(|file| stamp.format(file) > file.format()) {
+        return Err(format!(
+            "{} in {} was written by ktapper {}, newer than this one ({}); update ktapper to keep your stats",
+            file.name,
+            dir.display(),
+            stamp.app,
+            env!("CARGO_PKG_VERSION")
+        )
+        .into());
+    }
+    let outdated: Vec<&DataFile> = FILES
+        .iter()
+        .filter(|file| stamp.format(file) < file.format())
+        .collect();
+    if outdated.is_empty() {
+        if stamp.app != env!("CARGO_PKG_VERSION") {
+            write_stamp(dir)?;
+        }
+        return Ok(None);
+    }
+
+    let backup = back_up(dir, &stamp)?;
+    for file in outdated {
+        for migrate in &file.migrations[stamp.format(file) as usize..] {
+            migrate(dir).map_err(|err| {
+                format!(
+                    "Could not migrate {} ({}), the originals are in {}",
+                    file.name,
+                    err,
+                    backup.display()
+                )
+            })?;
+        }
+    }
+    write_stamp(dir)?;
+    Ok(Some(backup))
+}
+
+fn write_stamp(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    fs::write(path(dir), serde_json::to_string(&Stamp::current())?)?;
+    Ok(())
+}
+
+/// Data files are the files directly in the data directory. Installed packs,
+/// exports and earlier backups live in directories of their own.
+fn data_files(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn has_data(dir: &Path) -> bool {
+    !data_files(dir).is_empty()
+}
+
+/// Copies the data files to `backups/<version>-<time>` in the data directory.
+fn back_up(dir: &Path, stamp: &Stamp) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let backup = dir
+        .join("backups")
+        .join(format!("{}-{}", stamp.app, history::now()));
+    fs::create_dir_all(&backup)?;
+    for file in data_files(dir) {
+        if let Some(name) = file.file_name() {
+            fs::copy(&file, backup.join(name))?;
+        }
+    }
+    Ok(backup)
+}
+
+/// History format 0 to 1: every line has the word mode, timing and local hour
+/// of its run. Older versions left them out, so those runs get the defaults
+/// and the hour of their timestamp in today's time zone. Lines that can't be
+/// read are moved to `history.rejected.jsonl` instead of being skipped on
+/// every load.
+fn complete_history(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let path = dir.join("history.jsonl");
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let mut kept = String::new();
+    let mut rejected = String::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(mut entry)) if entry.contains_key("timestamp") => {
+                let hour = daytime::hour_at(entry["timestamp"].as_u64().unwrap_or_default());
+                entry.entry("mode").or_insert_with(|| "random".into());
+                entry.entry("timing").or_insert_with(|| "continuous".into());
+                let stamped = entry.get("hour").is_some_and(|hour| !hour.is_null());
+                if !stamped {
+                    entry.insert("hour".to_string(), hour.into());
+                }
+                kept.push_str(&serde_json::to_string(&entry)?);
+                kept.push('\n');
+            }
+            _ => {
+                rejected.push_str(line);
+                rejected.push('\n');
+            }
+        }
+    }
+    if !rejected.is_empty() {
+        fs::write(dir.join("history.rejected.jsonl"), rejected)?;
+    }
+    fs::write(path, kept)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Entry;
+
+    /// History as ktapper wrote it before formats were stamped.
+    const OLD_HISTORY: &str = concat!(
+        r#"{"timestamp":1700000000,"lang":"EN","words":25,"wpm":61.5,"accuracy":97.2,"seconds":24.4}"#,
+        "\n",
+        r#"{"timestamp":1700003600,"lang":"RU","words":10,"wpm":40.0,"accuracy":90.0,"seconds":15.0,"mode":"rare","hour":null}"#,
+        "\n",
+        "{\"timestamp\":17000\n",
+    );
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ktapper-migrate-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("history.jsonl"), OLD_HISTORY).unwrap();
+        dir
+    }
+
+    #[test]
+    fn old_history_is_brought_up_to_date() {
+        let dir = fixture("old");
+        // Strict entries can't read the old lines at all.
+        assert!(serde_json::from_str::<Entry>(OLD_HISTORY.lines().next().unwrap()).is_err());
+
+        let backup = migrate(&dir).unwrap().unwrap();
+        let entries: Vec<Entry> = fs::read_to_string(dir.join("history.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].hour, daytime::hour_at(1_700_000_000));
+        assert_eq!(entries[0].timing, history::Timing::Continuous);
+        assert_eq!(entries[1].mode.name(), "rare");
+        assert_eq!(
+            fs::read_to_string(dir.join("history.rejected.jsonl")).unwrap(),
+            "{\"timestamp\":17000\n"
+        );
+        assert_eq!(
+            fs::read_to_string(backup.join("history.jsonl")).unwrap(),
+            OLD_HISTORY
+        );
+
+        // Stamped as current, a second start leaves the files alone.
+        assert!(migrate(&dir).unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn newer_formats_are_refused() {
+        let dir = fixture("newer");
+        let mut stamp = Stamp::current();
+        stamp.formats.insert("history.jsonl".to_string(), 99);
+        fs::write(path(&dir), serde_json::to_string(&stamp).unwrap()).unwrap();
+        let err = migrate(&dir).unwrap_err().to_string();
+        assert!(err.starts_with("history.jsonl in"), "{}", err);
+        assert_eq!(
+            fs::read_to_string(dir.join("history.jsonl")).unwrap(),
+            OLD_HISTORY
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
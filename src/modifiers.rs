@@ -0,0 +1,71 @@
+use rand::{Rng, seq::SliceRandom};
+
+/// Share of generated words replaced by a number.
+const NUMBER_SHARE: f64 = 0.1;
+/// Share of words followed by a comma, semicolon or colon.
+const PAUSE_SHARE: f64 = 0.1;
+/// Share of words ending a sentence.
+const END_SHARE: f64 = 0.1;
+const PAUSES: [char; 4] = [',', ',', ';', ':'];
+/// Periods are the most common sentence ending.
+const ENDS: [char; 5] = ['.', '.', '.', '?', '!'];
+/// Numbers are drawn below this.
+const MAX_NUMBER: u32 = 10_000;
+
+/// Numbers and punctuation sprinkled into generated words, applied after
+/// the word generator and joiners and before words are queued.
+#[derive(Debug, Default, Clone)]
+pub struct Modifiers {
+    pub numbers: bool,
+    /// Commas and sentence endings after words, capitalized sentence starts.
+    pub punctuation: bool,
+    /// Whether the last word left a sentence open, so the next one doesn't
+    /// start with a capital.
+    in_sentence: bool,
+}
+
+impl Modifiers {
+    pub fn new(numbers: bool, punctuation: bool) -> Self {
+        Self {
+            numbers,
+            punctuation,
+            in_sentence: false,
+        }
+    }
+
+    /// The next test starts a new sentence.
+    pub fn reset(&mut self) {
+        self.in_sentence = false;
+    }
+
+    /// Draws from `rng`, so seeded tests get the same numbers and punctuation.
+    pub fn apply(&mut self, word: String, rng: &mut impl Rng) -> String {
+        let mut word = if self.numbers && rng.gen_bool(NUMBER_SHARE) {
+            rng.gen_range(0..MAX_NUMBER).to_string()
+        } else {
+            word
+        };
+        if !self.punctuation {
+            return word;
+        }
+        if !self.in_sentence {
+            word = capitalize(&word);
+        }
+        self.in_sentence = true;
+        if rng.gen_bool(END_SHARE) {
+            word.extend(ENDS.choose(rng));
+            self.in_sentence = false;
+        } else if rng.gen_bool(PAUSE_SHARE) {
+            word.extend(PAUSES.choose(rng));
+        }
+        word
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::TestMode,
+    daytime,
+    history::{self, Entry, Timing},
+    words::WordMode,
+};
+
+/// Language codes and the names monkeytype uses for them.
+const LANGUAGES: [(&str, &str); 7] = [
+    ("EN", "english"),
+    ("RU", "russian"),
+    ("DE", "german"),
+    ("ES", "spanish"),
+    ("FR", "french"),
+    ("JA", "japanese"),
+    ("ZH", "chinese_simplified"),
+];
+
+/// A test result in the schema of monkeytype's exported results.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonkeyResult {
+    pub wpm: f32,
+    #[serde(default)]
+    pub raw_wpm: f32,
+    pub acc: f32,
+    #[serde(default)]
+    pub consistency: f32,
+    /// "time", "words", "quote" or "custom".
+    #[serde(default)]
+    pub mode: String,
+    /// Seconds of a timed test or the word count of the others.
+    #[serde(default)]
+    pub mode2: String,
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+    #[serde(default)]
+    pub test_duration: f32,
+    #[serde(default)]
+    pub language: String,
+}
+
+/// An export file is a list of results, or an API response holding one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExportFile {
+    Results(Vec<MonkeyResult>),
+    Response { data: Vec<MonkeyResult> },
+}
+
+impl From<&Entry> for MonkeyResult {
+    fn from(entry: &Entry) -> Self {
+        let (mode, mode2) = match entry.test.unwrap_or_default() {
+            TestMode::Time => ("time", format!("{:.0}", entry.seconds)),
+            TestMode::Words => ("words", entry.words.to_string()),
+            TestMode::Quote => ("quote", entry.words.to_string()),
+            TestMode::Code | TestMode::Parallel => ("custom", entry.words.to_string()),
+        };
+        let language = LANGUAGES
+            .iter()
+            .find(|(code, _)| *code == entry.lang)
+            .map_or_else(|| entry.lang.to_lowercase(), |(_, name)| name.to_string());
+        Self {
+            wpm: entry.wpm,
+            // Runs saved before raw speed was recorded give their net speed.
+            raw_wpm: entry.raw.unwrap_or(entry.wpm),
+            acc: entry.accuracy,
+            consistency: entry.consistency.unwrap_or_default(),
+            mode: mode.to_string(),
+            mode2,
+            timestamp: entry.timestamp * 1000,
+            test_duration: entry.seconds,
+            language,
+        }
+    }
+}
+
+impl From<&MonkeyResult> for Entry {
+    fn from(result: &MonkeyResult) -> Self {
+        // Word lists like "english_1k" count as their language.
+        let lang = LANGUAGES
+            .iter()
+            .find(|(_, name)| result.language.starts_with(name))
+            .map_or_else(
+                || result.language.to_uppercase(),
+                |(code, _)| code.to_string(),
+            );
+        let test = match result.mode.as_str() {
+            "time" => TestMode::Time,
+            "quote" => TestMode::Quote,
+            _ => TestMode::Words,
+        };
+        Self {
+            timestamp: result.timestamp / 1000,
+            lang,
+            words: (result.wpm * result.test_duration / 60.0).round() as usize,
+            wpm: result.wpm,
+            accuracy: result.acc,
+            seconds: result.test_duration,
+            mode: WordMode::default(),
+            timing: Timing::Continuous,
+            hour: daytime::hour_at(result.timestamp / 1000),
+            key_errors: Default::default(),
+            raw: Some(result.raw_wpm),
+            consistency: Some(result.consistency),
+            test: Some(test),
+        }
+    }
+}
+
+/// Writes every saved run to `path` as monkeytype results. Returns how many
+/// were written.
+pub fn export(path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let results: Vec<MonkeyResult> = history::load().iter().map(MonkeyResult::from).collect();
+    fs::write(path, serde_json::to_string_pretty(&results)?)?;
+    Ok(results.len())
+}
+
+/// Adds the results of the monkeytype export at `path` to the history,
+/// skipping runs finished at the same second as another so importing twice
+/// adds nothing. Returns how many were added.
+pub fn import(path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let file = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|_| "Not a monkeytype results export")?;
+    let results = match file {
+        ExportFile::Results(results) | ExportFile::Response { data: results } => results,
+    };
+    let mut entries = history::load();
+    let mut saved: HashSet<u64> = entries.iter().map(|e| e.timestamp).collect();
+    let before = entries.len();
+    entries.extend(
+        results
+            .iter()
+            .map(Entry::from)
+            .filter(|entry| saved.insert(entry.timestamp)),
+    );
+    let added = entries.len() - before;
+    if added > 0 {
+        // Streaks and recent runs expect the oldest run first.
+        entries.sort_by_key(|e| e.timestamp);
+        history::rewrite(&entries)?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> Entry {
+        Entry {
+            timestamp: 1_760_000_000,
+            lang: "RU".to_string(),
+            words: 50,
+            wpm: 72.5,
+            accuracy: 96.0,
+            seconds: 30.0,
+            mode: WordMode::default(),
+            timing: Timing::Continuous,
+            hour: 9,
+            key_errors: Default::default(),
+            raw: Some(80.0),
+            consistency: Some(75.0),
+            test: Some(TestMode::Time),
+        }
+    }
+
+    #[test]
+    fn entries_export_in_monkeytype_terms() {
+        let result = MonkeyResult::from(&entry());
+        assert_eq!(result.mode, "time");
+        assert_eq!(result.mode2, "30");
+        assert_eq!(result.language, "russian");
+        assert_eq!(result.timestamp, 1_760_000_000_000);
+        assert_eq!(result.raw_wpm, 80.0);
+
+        // Older runs give their net speed as raw and count as word tests.
+        let old = Entry {
+            raw: None,
+            test: None,
+            lang: "XX".to_string(),
+            ..entry()
+        };
+        let result = MonkeyResult::from(&old);
+        assert_eq!(result.raw_wpm, 72.5);
+        assert_eq!(
+            (result.mode.as_str(), result.mode2.as_str()),
+            ("words", "50")
+        );
+        assert_eq!(result.language, "xx");
+    }
+
+    #[test]
+    fn results_import_back() {
+        let entry = Entry::from(&MonkeyResult::from(&entry()));
+        assert_eq!(entry.timestamp, 1_760_000_000);
+        assert_eq!(entry.lang, "RU");
+        assert_eq!(entry.test, Some(TestMode::Time));
+        assert_eq!(entry.consistency, Some(75.0));
+        // Words typed follow from speed and duration.
+        assert_eq!(entry.words, 36);
+    }
+
+    #[test]
+    fn both_export_shapes_parse() {
+        let result =
+            r#"{"wpm":100,"acc":99,"timestamp":1000,"language":"english_1k","mode":"quote"}"#;
+        for text in [
+            format!("[{}]", result),
+            format!(r#"{{"data":[{}]}}"#, result),
+        ] {
+            let (ExportFile::Results(results) | ExportFile::Response { data: results }) =
+                serde_json::from_str(&text).unwrap();
+            let entry = Entry::from(&results[0]);
+            assert_eq!(entry.lang, "EN");
+            assert_eq!(entry.test, Some(TestMode::Quote));
+            assert_eq!(entry.timestamp, 1);
+        }
+    }
+}
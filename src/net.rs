@@ -0,0 +1,230 @@
+use std::{
+    collections::VecDeque,
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const IO_TIMEOUT: Duration = Duration::from_millis(100);
+const MIN_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How long `Drop` waits for the worker before detaching it.
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(200);
+/// Messages kept while offline; the oldest are dropped first.
+const QUEUE_LIMIT: usize = 256;
+/// Sustained outgoing messages per second.
+const SEND_RATE: f32 = 5.0;
+const SEND_BURST: f32 = 10.0;
+
+pub enum NetEvent {
+    Connected,
+    Disconnected,
+    Message(String),
+}
+
+/// Line-based TCP client running on its own thread.
+///
+/// Outgoing lines are queued while offline and sent through a token bucket
+/// once connected; the connection is re-established with exponential backoff.
+/// Nothing here ever blocks the caller.
+pub struct NetClient {
+    outgoing: Sender<String>,
+    incoming: Receiver<NetEvent>,
+    shutdown: Arc<AtomicBool>,
+    done: Receiver<()>,
+    worker: Option<JoinHandle<()>>,
+    connected: bool,
+}
+
+impl NetClient {
+    pub fn connect(addr: &str) -> Self {
+        let (out_tx, out_rx) = mpsc::channel();
+        let (in_tx, in_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let worker = {
+            let addr = addr.to_string();
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || {
+                Worker {
+                    addr,
+                    outgoing: out_rx,
+                    incoming: in_tx,
+                    shutdown,
+                    queue: VecDeque::new(),
+                    tokens: SEND_BURST,
+                    refilled: Instant::now(),
+                }
+                .run();
+                let _ = done_tx.send(());
+            })
+        };
+
+        Self {
+            outgoing: out_tx,
+            incoming: in_rx,
+            shutdown,
+            done: done_rx,
+            worker: Some(worker),
+            connected: false,
+        }
+    }
+
+    pub fn send(&self, line: String) {
+        let _ = self.outgoing.send(line);
+    }
+
+    /// Drains everything received since the last call.
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        while let Ok(event) = self.incoming.try_recv() {
+            match event {
+                NetEvent::Connected => self.connected = true,
+                NetEvent::Disconnected => self.connected = false,
+                NetEvent::Message(line) => messages.push(line),
+            }
+        }
+        messages
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+impl Drop for NetClient {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if self.done.recv_timeout(SHUTDOWN_GRACE).is_ok()
+            && let Some(worker) = self.worker.take()
+        {
+            let _ = worker.join();
+        }
+    }
+}
+
+struct Worker {
+    addr: String,
+    outgoing: Receiver<String>,
+    incoming: Sender<NetEvent>,
+    shutdown: Arc<AtomicBool>,
+    queue: VecDeque<String>,
+    tokens: f32,
+    refilled: Instant,
+}
+
+impl Worker {
+    fn run(&mut self) {
+        let mut backoff = MIN_BACKOFF;
+        while !self.cancelled() {
+            match self.open() {
+                Some(stream) => {
+                    backoff = MIN_BACKOFF;
+                    let _ = self.incoming.send(NetEvent::Connected);
+                    self.serve(stream);
+                    let _ = self.incoming.send(NetEvent::Disconnected);
+                }
+                None => {
+                    self.sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    /// Sleeps in small steps so cancellation is never delayed by a long backoff.
+    fn sleep(&mut self, duration: Duration) {
+        let until = Instant::now() + duration;
+        while !self.cancelled() && Instant::now() < until {
+            self.collect();
+            thread::sleep(IO_TIMEOUT.min(until - Instant::now()));
+        }
+    }
+
+    fn open(&self) -> Option<TcpStream> {
+        let addr = self.addr.to_socket_addrs().ok()?.next()?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).ok()?;
+        stream.set_read_timeout(Some(IO_TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(CONNECT_TIMEOUT)).ok()?;
+        stream.set_nodelay(true).ok()?;
+        Some(stream)
+    }
+
+    /// Moves pending lines from the channel into the bounded offline queue.
+    fn collect(&mut self) {
+        loop {
+            match self.outgoing.try_recv() {
+                Ok(line) => {
+                    if self.queue.len() >= QUEUE_LIMIT {
+                        self.queue.pop_front();
+                    }
+                    self.queue.push_back(line);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.shutdown.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let earned = now.duration_since(self.refilled).as_secs_f32() * SEND_RATE;
+        self.tokens = (self.tokens + earned).min(SEND_BURST);
+        self.refilled = now;
+    }
+
+    /// Exchanges lines until the connection drops or the client shuts down.
+    fn serve(&mut self, mut stream: TcpStream) {
+        let mut buf = [0u8; 4096];
+        let mut pending = Vec::new();
+
+        while !self.cancelled() {
+            self.collect();
+            self.refill();
+            while self.tokens >= 1.0
+                && let Some(line) = self.queue.pop_front()
+            {
+                if stream.write_all(format!("{}\n", line).as_bytes()).is_err() {
+                    self.queue.push_front(line);
+                    return;
+                }
+                self.tokens -= 1.0;
+            }
+
+            match stream.read(&mut buf) {
+                Ok(0) => return,
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = pending.drain(..=end).collect();
+                        let line = String::from_utf8_lossy(&line).trim_end().to_string();
+                        if !line.is_empty() {
+                            let _ = self.incoming.send(NetEvent::Message(line));
+                        }
+                    }
+                }
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                    ) => {}
+                Err(_) => return,
+            }
+        }
+    }
+}
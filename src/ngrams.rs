@@ -0,0 +1,94 @@
+use rand::{Rng, seq::SliceRandom};
+
+/// Times an n-gram is repeated in each word of a drill.
+pub const REPEATS: usize = 3;
+
+/// Letter sequences drilled together, from English text.
+pub struct NgramSet {
+    pub name: &'static str,
+    pub grams: &'static [&'static str],
+}
+
+pub const SETS: [NgramSet; 4] = [
+    NgramSet {
+        name: "Common bigrams",
+        grams: &[
+            "th", "he", "in", "er", "an", "re", "on", "at", "en", "nd", "ti", "es", "or", "te",
+        ],
+    },
+    NgramSet {
+        name: "Common trigrams",
+        grams: &[
+            "the", "and", "ing", "ion", "tio", "ent", "ati", "for", "her", "ter", "hat", "tha",
+            "ere", "ate",
+        ],
+    },
+    NgramSet {
+        name: "Same-finger bigrams",
+        grams: &[
+            "ed", "de", "ce", "ec", "rt", "tr", "un", "nu", "my", "ym", "lo", "ol", "ki", "ik",
+        ],
+    },
+    NgramSet {
+        name: "Word endings",
+        grams: &["ing", "ed", "ly", "ion", "ment", "ness", "est", "er"],
+    },
+];
+
+/// `count` words of the set's n-grams, each repeated [`REPEATS`] times, in
+/// random order without the same one twice in a row.
+pub fn drill(set: &NgramSet, count: usize, rng: &mut impl Rng) -> Vec<String> {
+    let mut words: Vec<String> = Vec::with_capacity(count);
+    let mut last = None;
+    while words.len() < count {
+        let Some(&gram) = set.grams.choose(rng) else {
+            break;
+        };
+        if set.grams.len() > 1 && last == Some(gram) {
+            continue;
+        }
+        last = Some(gram);
+        words.push(gram.repeat(REPEATS));
+    }
+    words
+}
+
+/// Characters typed for one n-gram of a drill and how many were wrong.
+pub struct GramAccuracy {
+    pub gram: &'static str,
+    pub chars: usize,
+    pub wrong: usize,
+}
+
+impl GramAccuracy {
+    pub fn percent(&self) -> f32 {
+        (self.chars - self.wrong) as f32 / self.chars.max(1) as f32 * 100.0
+    }
+}
+
+/// Accuracy of each drilled n-gram over the typed `words` and their wrong
+/// character counts, least accurate first.
+pub fn accuracy<'a>(
+    set: &NgramSet,
+    words: impl Iterator<Item = (&'a str, usize)>,
+) -> Vec<GramAccuracy> {
+    let mut stats: Vec<GramAccuracy> = Vec::new();
+    for (word, wrong) in words {
+        let Some(&gram) = set.grams.iter().find(|gram| gram.repeat(REPEATS) == word) else {
+            continue;
+        };
+        match stats.iter_mut().find(|stats| stats.gram == gram) {
+            Some(stats) => {
+                stats.chars += word.chars().count();
+                stats.wrong += wrong;
+            }
+            None => stats.push(GramAccuracy {
+                gram,
+                chars: word.chars().count(),
+                wrong,
+            }),
+        }
+    }
+    stats.sort_by(|a, b| a.percent().total_cmp(&b.percent()));
+    stats
+}
@@ -0,0 +1,179 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{config::data_dir, quotes::Quote};
+
+/// Kinds of packs, each installed in a directory of its own.
+const KINDS: [&str; 2] = ["words", "quotes"];
+
+/// Entry of the community pack index.
+#[derive(Deserialize)]
+pub struct PackInfo {
+    pub code: String,
+    #[serde(default)]
+    pub name: String,
+    /// `words` for newline-separated wordlists, `quotes` for one quote per
+    /// line, optionally followed by a tab and its author.
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+fn default_kind() -> String {
+    "words".to_string()
+}
+
+#[derive(Deserialize)]
+struct Index {
+    packs: Vec<PackInfo>,
+}
+
+fn dir(kind: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("packs").join(kind))
+}
+
+pub fn fetch_index(url: &str) -> Result<Vec<PackInfo>, Box<dyn std::error::Error>> {
+    let index: Index = serde_json::from_str(&ureq::get(url).call()?.into_string()?)?;
+    Ok(index.packs)
+}
+
+/// Downloads a pack listed in the index and stores it after verifying its checksum.
+pub fn install(code: &str, index_url: &str) -> Result<PackInfo, Box<dyn std::error::Error>> {
+    let pack = fetch_index(index_url)?
+        .into_iter()
+        .find(|pack| pack.code.eq_ignore_ascii_case(code))
+        .ok_or_else(|| format!("No pack \"{}\" in {}", code, index_url))?;
+    // The index is remote, so what it names is checked before it touches a path.
+    if !KINDS.contains(&pack.kind.as_str()) {
+        return Err(format!("Pack {} has unknown kind \"{}\"", pack.code, pack.kind).into());
+    }
+    if !valid_code(&pack.code.to_lowercase()) {
+        return Err(format!("Pack code \"{}\" is not a valid file name", pack.code).into());
+    }
+
+    let mut body = Vec::new();
+    ureq::get(&pack.url)
+        .call()?
+        .into_reader()
+        .read_to_end(&mut body)?;
+
+    let checksum: String = Sha256::digest(&body)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if !checksum.eq_ignore_ascii_case(&pack.sha256) {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            pack.code, pack.sha256, checksum
+        )
+        .into());
+    }
+    let text = String::from_utf8(body)?;
+    if text.lines().all(|line| line.trim().is_empty()) {
+        return Err(format!("Pack {} is empty", pack.code).into());
+    }
+
+    let dir = dir(&pack.kind).ok_or("Could not find project directories")?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(format!("{}.txt", pack.code.to_lowercase())), text)?;
+    Ok(pack)
+}
+
+/// Pack codes become file names, so only `[a-z0-9_-]+` is accepted.
+fn valid_code(code: &str) -> bool {
+    !code.is_empty()
+        && code
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_' || ch == '-')
+}
+
+/// Codes of installed word packs, sorted.
+pub fn installed() -> Vec<String> {
+    installed_kind("words")
+}
+
+/// Codes of installed packs of `kind`, sorted.
+pub fn installed_kind(kind: &str) -> Vec<String> {
+    if !KINDS.contains(&kind) {
+        return Vec::new();
+    }
+    let mut codes: Vec<String> = dir(kind)
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "txt").then(|| path.file_stem()?.to_str().map(str::to_string))?
+        })
+        .collect();
+    codes.sort();
+    codes
+}
+
+static LOADED: Lazy<Mutex<HashMap<String, &'static [&'static str]>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Words of an installed pack. Each pack is read once and kept for the whole
+/// session, like the built-in dictionaries.
+pub fn load(code: &str) -> Option<&'static [&'static str]> {
+    let mut loaded = LOADED.lock().ok()?;
+    if let Some(words) = loaded.get(code) {
+        return Some(words);
+    }
+
+    let text = fs::read_to_string(dir("words")?.join(format!("{}.txt", code))).ok()?;
+    let words: Vec<&'static str> = Box::leak(text.into_boxed_str())
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let words: &'static [&'static str] = Box::leak(words.into_boxed_slice());
+    loaded.insert(code.to_string(), words);
+    Some(words)
+}
+
+static LOADED_QUOTES: Lazy<Mutex<HashMap<String, &'static [Quote]>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Quotes of the installed quote packs for the language `code`, e.g. `de`
+/// or `de-classics`. Read once per session like word packs.
+pub fn quotes(code: &str) -> Vec<&'static Quote> {
+    let prefix = format!("{}-", code.to_lowercase());
+    installed_kind("quotes")
+        .into_iter()
+        .filter(|pack| *pack == code.to_lowercase() || pack.starts_with(&prefix))
+        .filter_map(|pack| load_quotes(&pack))
+        .flatten()
+        .collect()
+}
+
+fn load_quotes(code: &str) -> Option<&'static [Quote]> {
+    let mut loaded = LOADED_QUOTES.lock().ok()?;
+    if let Some(quotes) = loaded.get(code) {
+        return Some(quotes);
+    }
+
+    let text = fs::read_to_string(dir("quotes")?.join(format!("{}.txt", code))).ok()?;
+    let quotes: Vec<Quote> = text
+        .lines()
+        .filter_map(|line| {
+            let (text, author) = line.split_once('\t').unwrap_or((line, ""));
+            let text = text.trim();
+            (!text.is_empty()).then(|| Quote {
+                text: text.to_string(),
+                author: author.trim().to_string(),
+            })
+        })
+        .collect();
+    let quotes: &'static [Quote] = Box::leak(quotes.into_boxed_slice());
+    loaded.insert(code.to_string(), quotes);
+    Some(quotes)
+}
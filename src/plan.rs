@@ -0,0 +1,101 @@
+use random_word::Lang;
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_lang;
+
+/// One entry of a practice plan, repeated `repeat` times when the session runs.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Step {
+    pub label: String,
+    pub lang: String,
+    pub limit: usize,
+    #[serde(default = "default_repeat")]
+    pub repeat: usize,
+}
+
+fn default_repeat() -> usize {
+    1
+}
+
+impl Step {
+    pub fn lang(&self) -> Lang {
+        get_lang(&self.lang).unwrap_or(Lang::En)
+    }
+}
+
+pub struct StepResult {
+    pub label: String,
+    pub lang: Lang,
+    pub words: usize,
+    pub wpm: f32,
+    pub accuracy: f32,
+    pub seconds: f32,
+}
+
+/// A plan being run end-to-end, one test per expanded step.
+pub struct Session {
+    steps: Vec<Step>,
+    current: usize,
+    pub results: Vec<StepResult>,
+    /// Language and limit to restore once the session is over.
+    pub base: (Lang, usize),
+}
+
+impl Session {
+    pub fn new(plan: &[Step], base: (Lang, usize)) -> Option<Self> {
+        let steps: Vec<Step> = plan
+            .iter()
+            .flat_map(|step| std::iter::repeat_n(step.clone(), step.repeat))
+            .collect();
+
+        if steps.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            steps,
+            current: 0,
+            results: Vec::new(),
+            base,
+        })
+    }
+
+    pub fn step(&self) -> Option<&Step> {
+        self.steps.get(self.current)
+    }
+
+    /// Records the finished test and returns the next step, if any.
+    pub fn advance(&mut self, result: StepResult) -> Option<&Step> {
+        self.results.push(result);
+        self.current += 1;
+        self.step()
+    }
+
+    pub fn title(&self) -> String {
+        match self.step() {
+            Some(step) => format!("{} {}/{}", step.label, self.current + 1, self.steps.len()),
+            None => "Session complete".to_string(),
+        }
+    }
+
+    /// Consolidated `(words, wpm, accuracy, seconds)` over all finished steps.
+    pub fn totals(&self) -> (usize, f32, f32, f32) {
+        let words: usize = self.results.iter().map(|r| r.words).sum();
+        let seconds: f32 = self.results.iter().map(|r| r.seconds).sum();
+        if words == 0 {
+            return (0, 0.0, 100.0, seconds);
+        }
+
+        let wpm = self
+            .results
+            .iter()
+            .map(|r| r.wpm * r.seconds / seconds.max(f32::EPSILON))
+            .sum();
+        let accuracy = self
+            .results
+            .iter()
+            .map(|r| r.accuracy * r.words as f32 / words as f32)
+            .sum();
+        (words, wpm, accuracy, seconds)
+    }
+}
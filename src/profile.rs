@@ -0,0 +1,221 @@
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process,
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use tar::EntryType;
+
+use crate::{
+    config::{CONFIG, Config, ConfigManager, data_dir, project_dirs},
+    history,
+};
+
+/// Archive root of the files config paths point to, outside the app's
+/// directories, each kept under the name of its option.
+const FILES: &str = "files";
+/// Directories of the app's directories that belong to a profile, next to
+/// the files directly in them. Backups, exports and the copies of files
+/// config paths point to are left out.
+const DIRS: [&str; 1] = ["packs"];
+
+/// Archive roots and the local directories they map to.
+fn roots() -> Result<Vec<(&'static str, PathBuf)>, Box<dyn std::error::Error>> {
+    let dirs = project_dirs().ok_or("Could not find project directories")?;
+    let mut roots = vec![("config", dirs.config_dir().to_path_buf())];
+    // Some platforms keep config and data in the same directory.
+    if dirs.data_dir() != dirs.config_dir() {
+        roots.push(("data", dirs.data_dir().to_path_buf()));
+    }
+    Ok(roots)
+}
+
+/// Paths of `dir` that belong to a profile.
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        || path.is_dir()
+                            && path
+                                .file_name()
+                                .is_some_and(|name| DIRS.iter().any(|&dir| name == dir))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The config options naming a file, with their values in `config`.
+fn file_options(config: &mut Config) -> [(&'static str, &mut Option<PathBuf>); 3] {
+    [
+        ("wordlist", &mut config.wordlist),
+        ("corpus", &mut config.corpus),
+        ("blocklist", &mut config.blocklist),
+    ]
+}
+
+pub fn export(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+
+    for (root, dir) in roots()? {
+        for path in entries(&dir) {
+            let name = Path::new(root).join(path.file_name().ok_or("Path without a file name")?);
+            if path.is_dir() {
+                archive.append_dir_all(name, &path)?;
+            } else {
+                archive.append_path_with_name(&path, name)?;
+            }
+        }
+    }
+    let mut config = CONFIG.clone();
+    for (option, file) in file_options(&mut config) {
+        let Some(file) = file.as_ref().filter(|file| file.is_file()) else {
+            continue;
+        };
+        let name = file.file_name().ok_or("Config path without a file name")?;
+        archive.append_path_with_name(file, Path::new(FILES).join(option).join(name))?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Replaces the profile with the archive at `path`, returning the backup
+/// made of the files it replaces.
+pub fn import(path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let staging = staging_dir()?;
+    let imported = unpack(path, &staging).and_then(|()| {
+        let backup = back_up()?;
+        install(&staging).map_err(|err| {
+            format!(
+                "Could not import the profile ({}), the previous files are in {}",
+                err,
+                backup.display()
+            )
+        })?;
+        Ok(backup)
+    });
+    let _ = fs::remove_dir_all(&staging);
+    imported
+}
+
+/// Creates a directory to unpack into that only this user can read. It
+/// fails rather than reuse a directory that already exists, which someone
+/// else sharing the temp directory could have placed there.
+fn staging_dir() -> std::io::Result<PathBuf> {
+    let path = env::temp_dir().join(format!(
+        "ktapper-import-{}-{:016x}",
+        process::id(),
+        rand::random::<u64>()
+    ));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&path)?;
+    Ok(path)
+}
+
+/// Copies the current profile to `backups/import-<time>` in the data
+/// directory before an import overwrites it.
+fn back_up() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let backup = data_dir()
+        .ok_or("Could not find project directories")?
+        .join("backups")
+        .join(format!("import-{}", history::now()));
+    for (root, dir) in roots()? {
+        for path in entries(&dir) {
+            let target = backup
+                .join(root)
+                .join(path.file_name().ok_or("Path without a file name")?);
+            if path.is_dir() {
+                copy_dir(&path, &target)?;
+            } else {
+                fs::create_dir_all(backup.join(root))?;
+                fs::copy(&path, target)?;
+            }
+        }
+    }
+    Ok(backup)
+}
+
+/// Unpacks the archive into `staging`. Only plain files and directories
+/// are taken, as links could point anywhere once unpacked.
+fn unpack(path: &Path, staging: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        if !matches!(
+            entry.header().entry_type(),
+            EntryType::Regular | EntryType::Directory
+        ) {
+            return Err(format!(
+                "Refusing link or special file in archive: {}",
+                entry_path.display()
+            )
+            .into());
+        }
+        if !entry.unpack_in(staging)? {
+            return Err(
+                format!("Refusing unsafe path in archive: {}", entry_path.display()).into(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Copies the unpacked roots over the local directories, then moves the
+/// referenced files into the data directory and points the config at them.
+fn install(staging: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    for (name, dir) in roots()? {
+        let unpacked = staging.join(name);
+        if unpacked.is_dir() {
+            copy_dir(&unpacked, &dir)?;
+        }
+    }
+
+    let files = staging.join(FILES);
+    if !files.is_dir() {
+        return Ok(());
+    }
+    let data_dir = project_dirs()
+        .ok_or("Could not find project directories")?
+        .data_dir()
+        .join(FILES);
+    let mut manager = ConfigManager::default();
+    let mut config = manager.reload()?.clone();
+    for (option, file) in file_options(&mut config) {
+        let Some(name) = fs::read_dir(files.join(option))
+            .ok()
+            .and_then(|mut entries| entries.next()?.ok())
+            .map(|entry| entry.file_name())
+        else {
+            continue;
+        };
+        let target = data_dir.join(option).join(&name);
+        fs::create_dir_all(data_dir.join(option))?;
+        fs::copy(files.join(option).join(&name), &target)?;
+        *file = Some(target);
+    }
+    manager.save(config)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
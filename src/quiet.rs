@@ -0,0 +1,63 @@
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// Daily local-time window, written as `"22:00-08:00"`, during which sounds
+/// and notifications are suppressed. Windows may wrap past midnight.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct QuietHours {
+    /// Minutes after midnight.
+    start: u32,
+    end: u32,
+}
+
+impl QuietHours {
+    pub fn contains(&self, minute: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    pub fn is_now(&self) -> bool {
+        let now = Local::now();
+        self.contains(now.hour() * 60 + now.minute())
+    }
+}
+
+fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+impl TryFrom<String> for QuietHours {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || {
+            format!(
+                "invalid quiet hours \"{}\", expected e.g. \"22:00-08:00\"",
+                value
+            )
+        };
+        let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+        Ok(Self {
+            start: parse_time(start).ok_or_else(invalid)?,
+            end: parse_time(end).ok_or_else(invalid)?,
+        })
+    }
+}
+
+impl From<QuietHours> for String {
+    fn from(value: QuietHours) -> Self {
+        format!(
+            "{:02}:{:02}-{:02}:{:02}",
+            value.start / 60,
+            value.start % 60,
+            value.end / 60,
+            value.end % 60
+        )
+    }
+}
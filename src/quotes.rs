@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use htils::ternary;
+use once_cell::sync::Lazy;
+use rand::{seq::SliceRandom, thread_rng};
+use random_word::Lang;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::lang_code, packs};
+
+/// Longest short quote, in characters.
+const SHORT_CHARS: usize = 80;
+/// Longest medium quote, in characters.
+const MEDIUM_CHARS: usize = 200;
+
+#[derive(Deserialize, Debug)]
+pub struct Quote {
+    pub text: String,
+    pub author: String,
+}
+
+/// Bundled quotes by lowercase language code.
+static QUOTES: Lazy<HashMap<String, Vec<Quote>>> =
+    Lazy::new(|| serde_json::from_str(include_str!("../assets/quotes.json")).unwrap_or_default());
+
+/// Length of the quotes served in quote mode.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteLength {
+    #[default]
+    All,
+    /// Up to 80 characters.
+    Short,
+    /// Up to 200 characters.
+    Medium,
+    Long,
+}
+
+impl QuoteLength {
+    pub fn name(self) -> &'static str {
+        match self {
+            QuoteLength::All => "all",
+            QuoteLength::Short => "short",
+            QuoteLength::Medium => "medium",
+            QuoteLength::Long => "long",
+        }
+    }
+
+    pub fn matches(self, quote: &Quote) -> bool {
+        let len = quote.text.chars().count();
+        match self {
+            QuoteLength::All => true,
+            QuoteLength::Short => len <= SHORT_CHARS,
+            QuoteLength::Medium => len > SHORT_CHARS && len <= MEDIUM_CHARS,
+            QuoteLength::Long => len > MEDIUM_CHARS,
+        }
+    }
+}
+
+/// A random quote in `lang`, from the bundled ones and installed quote
+/// packs, or in English for languages without any. Quotes `allowed` rejects
+/// are skipped, and `length` is ignored when no quote of that length is left.
+pub fn random(
+    lang: Lang,
+    length: QuoteLength,
+    allowed: impl Fn(&Quote) -> bool,
+) -> Option<&'static Quote> {
+    let code = lang_code(lang).to_lowercase();
+    let mut quotes: Vec<&'static Quote> = QUOTES.get(&code).into_iter().flatten().collect();
+    quotes.extend(packs::quotes(&code));
+    if quotes.is_empty() {
+        quotes.extend(QUOTES.get("en").into_iter().flatten());
+    }
+    let allowed: Vec<&'static Quote> = quotes.into_iter().filter(|q| allowed(q)).collect();
+    let sized: Vec<&'static Quote> = allowed
+        .iter()
+        .copied()
+        .filter(|q| length.matches(q))
+        .collect();
+    ternary!(sized.is_empty(), allowed, sized)
+        .choose(&mut thread_rng())
+        .copied()
+}
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{BorderType, Widget},
+};
+
+use crate::theme::Theme;
+
+/// A single race participant: the local player, a bot, a remote peer or a ghost.
+pub struct Racer {
+    pub name: String,
+    pub words: usize,
+    pub wpm: f32,
+    pub place: Option<usize>,
+    /// Kept in the race but left out of it, like the ghost of a test kind
+    /// without a recorded run.
+    pub hidden: bool,
+}
+
+/// Progress update produced by any participant source and applied on tick.
+pub enum RaceEvent {
+    Progress {
+        racer: usize,
+        words: usize,
+        wpm: f32,
+    },
+}
+
+/// Wire format shared by networked races: `progress\t<name>\t<words>\t<wpm>`.
+pub fn encode_progress(name: &str, words: usize, wpm: f32) -> String {
+    format!("progress\t{}\t{}\t{:.1}", name, words, wpm)
+}
+
+pub fn decode_progress(line: &str) -> Option<(String, usize, f32)> {
+    let mut parts = line.split('\t');
+    if parts.next()? != "progress" {
+        return None;
+    }
+    let name = parts.next()?.to_string();
+    let words = parts.next()?.parse().ok()?;
+    let wpm = parts.next()?.parse().ok()?;
+    Some((name, words, wpm))
+}
+
+/// Wire format of a LAN race starting: `start\t<seed>\t<lang>\t<words>`,
+/// the words separated by spaces, so every player types the host's words.
+pub fn encode_start(seed: &str, lang: &str, words: &[String]) -> String {
+    format!("start\t{}\t{}\t{}", seed, lang, words.join(" "))
+}
+
+pub fn decode_start(line: &str) -> Option<(String, String, Vec<String>)> {
+    let mut parts = line.splitn(4, '\t');
+    if parts.next()? != "start" {
+        return None;
+    }
+    let seed = parts.next()?.to_string();
+    let lang = parts.next()?.to_string();
+    let words: Vec<String> = parts
+        .next()?
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    (!words.is_empty()).then_some((seed, lang, words))
+}
+
+pub struct Race {
+    pub racers: Vec<Racer>,
+    pub target: usize,
+    finished: usize,
+}
+
+/// Index of the local player in `Race::racers`.
+pub const PLAYER: usize = 0;
+
+impl Race {
+    pub fn new(player: &str, target: usize) -> Self {
+        let mut race = Self {
+            racers: Vec::new(),
+            target,
+            finished: 0,
+        };
+        race.join(player);
+        race
+    }
+
+    pub fn join(&mut self, name: &str) -> usize {
+        self.racers.push(Racer {
+            name: name.to_string(),
+            words: 0,
+            wpm: 0.0,
+            place: None,
+            hidden: false,
+        });
+        self.racers.len() - 1
+    }
+
+    pub fn reset(&mut self, target: usize) {
+        self.target = target;
+        self.finished = 0;
+        for racer in &mut self.racers {
+            racer.words = 0;
+            racer.wpm = 0.0;
+            racer.place = None;
+        }
+    }
+
+    /// Racers taking part, hidden ones left out.
+    pub fn shown(&self) -> usize {
+        self.racers.iter().filter(|r| !r.hidden).count()
+    }
+
+    pub fn is_solo(&self) -> bool {
+        self.shown() < 2
+    }
+
+    pub fn apply(&mut self, event: RaceEvent) {
+        match event {
+            RaceEvent::Progress { racer, words, wpm } => {
+                let Some(r) = self.racers.get_mut(racer) else {
+                    return;
+                };
+                if r.place.is_some() || r.hidden {
+                    return;
+                }
+                r.words = words.min(self.target);
+                r.wpm = wpm;
+                if r.words >= self.target {
+                    self.finished += 1;
+                    r.place = Some(self.finished);
+                }
+            }
+        }
+    }
+
+    fn progress(&self, racer: &Racer) -> f32 {
+        if self.target == 0 {
+            return 0.0;
+        }
+        racer.words as f32 / self.target as f32
+    }
+}
+
+/// Simulated opponent typing at a constant pace.
+pub struct Bot {
+    pub racer: usize,
+    pub wpm: f32,
+}
+
+impl Bot {
+    pub fn poll(&self, elapsed: Duration) -> RaceEvent {
+        RaceEvent::Progress {
+            racer: self.racer,
+            words: (self.wpm * elapsed.as_secs_f32() / 60.0) as usize,
+            wpm: self.wpm,
+        }
+    }
+}
+
+pub struct RaceHud<'a> {
+    race: &'a Race,
+    status: Option<&'a str>,
+    theme: Theme,
+}
+
+impl<'a> RaceHud<'a> {
+    pub fn new(race: &'a Race) -> Self {
+        Self {
+            race,
+            status: None,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn status(mut self, status: Option<&'a str>) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl Widget for RaceHud<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let title = match self.status {
+            Some(status) => format!("Race ({})", status),
+            None => "Race".to_string(),
+        };
+        let block = self
+            .theme
+            .block()
+            .title(title)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let name_width = self
+            .race
+            .racers
+            .iter()
+            .filter(|r| !r.hidden)
+            .map(|r| r.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (row, (i, racer)) in self
+            .race
+            .racers
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| !r.hidden)
+            .take(inner.height as usize)
+            .enumerate()
+        {
+            let row = Rect {
+                y: inner.y + row as u16,
+                height: 1,
+                ..inner
+            };
+
+            let place = racer.place.map(|p| format!(" #{}", p)).unwrap_or_default();
+            let stats = format!(" {:>4.0} wpm{}", racer.wpm, place);
+            let bar_width =
+                (inner.width as usize).saturating_sub(name_width + 1 + stats.chars().count());
+            let filled =
+                ((self.race.progress(racer) * bar_width as f32).round() as usize).min(bar_width);
+
+            let color = if i == PLAYER {
+                self.theme.highlight
+            } else {
+                Color::Yellow
+            };
+
+            Line::from(vec![
+                Span::raw(format!("{:<width$} ", racer.name, width = name_width)),
+                Span::styled("█".repeat(filled), Style::new().fg(color)),
+                Span::styled(
+                    "░".repeat(bar_width - filled),
+                    Style::new().fg(self.theme.dim),
+                ),
+                Span::raw(stats),
+            ])
+            .render(row, buf);
+        }
+    }
+}
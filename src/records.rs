@@ -0,0 +1,69 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::data_dir;
+
+/// Best speed and accuracy reached in one kind of test.
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub struct Record {
+    pub wpm: f32,
+    pub accuracy: f32,
+}
+
+/// Previous bests a run went past. `None` for the ones it didn't beat.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NewBest {
+    pub wpm: Option<f32>,
+    pub accuracy: Option<f32>,
+}
+
+/// Personal bests by test kind, see [`key`].
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Records {
+    #[serde(default)]
+    pub records: BTreeMap<String, Record>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("records.json"))
+}
+
+/// Kind of test records are kept apart by, e.g. "time/EN/30".
+pub fn key(mode: &str, lang: &str, limit: &str) -> String {
+    format!("{}/{}/{}", mode, lang, limit)
+}
+
+impl Records {
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Keeps the run's speed and accuracy where they are bests. Returns the
+    /// bests it beat, or `None` when it beat none or is the first of its kind.
+    pub fn record(&mut self, key: String, wpm: f32, accuracy: f32) -> Option<NewBest> {
+        let Some(record) = self.records.get_mut(&key) else {
+            self.records.insert(key, Record { wpm, accuracy });
+            return None;
+        };
+        let beaten = NewBest {
+            wpm: (wpm > record.wpm).then_some(record.wpm),
+            accuracy: (accuracy > record.accuracy).then_some(record.accuracy),
+        };
+        record.wpm = record.wpm.max(wpm);
+        record.accuracy = record.accuracy.max(accuracy);
+        (beaten != NewBest::default()).then_some(beaten)
+    }
+}
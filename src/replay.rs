@@ -0,0 +1,90 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+/// Playback speeds, switched with their number keys.
+pub const SPEEDS: [u32; 3] = [1, 2, 4];
+/// How far Left and Right move the playback.
+const SEEK: Duration = Duration::from_secs(1);
+
+/// The input of a test right after one of its keystrokes.
+pub struct Frame {
+    /// Test time of the keystroke.
+    pub at: Duration,
+    /// Index of the word being typed.
+    pub word: usize,
+    pub target: String,
+    pub input: String,
+    pub wrong: HashSet<usize>,
+}
+
+/// Playback of the frames of the last test, advanced by ticks.
+pub struct Replay {
+    /// Test time shown.
+    pub position: Duration,
+    pub end: Duration,
+    pub speed: u32,
+    pub playing: bool,
+    /// When the position last moved along, unset while paused.
+    last: Option<Instant>,
+}
+
+impl Replay {
+    pub fn new(end: Duration) -> Self {
+        Self {
+            position: Duration::ZERO,
+            end,
+            speed: SPEEDS[0],
+            playing: true,
+            last: None,
+        }
+    }
+
+    /// Moves the position along by the time since the last advance, at the
+    /// playback speed, stopping at the end.
+    pub fn advance(&mut self, now: Instant) {
+        if !self.playing {
+            return;
+        }
+        if let Some(last) = self.last {
+            self.position += now.saturating_duration_since(last) * self.speed;
+        }
+        self.last = Some(now);
+        if self.position >= self.end {
+            self.position = self.end;
+            self.playing = false;
+            self.last = None;
+        }
+    }
+
+    /// Pauses or resumes, starting over once the end was reached.
+    pub fn toggle(&mut self) {
+        if !self.playing && self.position >= self.end {
+            self.position = Duration::ZERO;
+        }
+        self.playing = !self.playing;
+        self.last = None;
+    }
+
+    pub fn seek(&mut self, forward: bool) {
+        self.position = if forward {
+            (self.position + SEEK).min(self.end)
+        } else {
+            self.position.saturating_sub(SEEK)
+        };
+    }
+
+    /// Playback speed `speed`, ignored unless it's one of [`SPEEDS`].
+    pub fn set_speed(&mut self, speed: u32) {
+        if SPEEDS.contains(&speed) {
+            self.speed = speed;
+        }
+    }
+
+    /// The last frame at or before the position, none before the first key.
+    pub fn frame<'a>(&self, frames: &'a [Frame]) -> Option<&'a Frame> {
+        let shown = frames.partition_point(|frame| frame.at <= self.position);
+        shown.checked_sub(1).map(|i| &frames[i])
+    }
+}
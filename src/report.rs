@@ -0,0 +1,247 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::Serialize;
+
+use crate::{
+    history::{self, Entry, Timing},
+    words::WordMode,
+};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+/// Width of the longest bar in the daily chart.
+const CHART_WIDTH: usize = 40;
+
+/// Parses durations like `30d`, `2w` or `12h` into seconds.
+pub fn parse_since(since: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let split = since.len() - since.chars().last().map_or(0, char::len_utf8);
+    let (amount, unit) = since.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid period \"{}\", expected e.g. 30d", since))?;
+    let unit = match unit {
+        "h" => 3600,
+        "d" => SECONDS_PER_DAY,
+        "w" => 7 * SECONDS_PER_DAY,
+        _ => return Err(format!("Unknown unit in \"{}\", use h, d or w", since).into()),
+    };
+    Ok(amount * unit)
+}
+
+/// Markdown summary of the runs saved in the last `period` seconds.
+pub fn generate(since: &str, period: u64) -> String {
+    let from = history::now().saturating_sub(period);
+    let entries: Vec<Entry> = history::load()
+        .into_iter()
+        .filter(|e| e.timestamp >= from)
+        .collect();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# ktapper training report\n");
+    let _ = writeln!(
+        out,
+        "Period: last {} ({} to {})\n",
+        since,
+        date(from),
+        date(history::now())
+    );
+    if entries.is_empty() {
+        let _ = writeln!(out, "No saved runs in this period.");
+        return out;
+    }
+
+    let seconds: f32 = entries.iter().map(|e| e.seconds).sum();
+    let best = entries.iter().map(|e| e.wpm).fold(0.0, f32::max);
+    let _ = writeln!(out, "## Summary\n");
+    let _ = writeln!(
+        out,
+        "| Runs | Words | Time | Avg WPM | Best WPM | Avg accuracy |"
+    );
+    let _ = writeln!(out, "|---:|---:|---:|---:|---:|---:|");
+    let _ = writeln!(
+        out,
+        "| {} | {} | {} | {:.1} | {:.1} | {:.1}% |\n",
+        entries.len(),
+        entries.iter().map(|e| e.words).sum::<usize>(),
+        duration(seconds),
+        average(&entries, |e| e.wpm),
+        best,
+        average(&entries, |e| e.accuracy)
+    );
+
+    // Progress compares the first half of the period's runs with the second.
+    if entries.len() >= 2 {
+        let (early, late) = entries.split_at(entries.len() / 2);
+        let _ = writeln!(
+            out,
+            "Progress: {:.1} → {:.1} WPM, {:.1}% → {:.1}% accuracy (first half vs second half of runs)\n",
+            average(early, |e| e.wpm),
+            average(late, |e| e.wpm),
+            average(early, |e| e.accuracy),
+            average(late, |e| e.accuracy)
+        );
+    }
+
+    let mut by_lang: BTreeMap<String, Vec<Entry>> = BTreeMap::new();
+    for entry in &entries {
+        by_lang
+            .entry(format!("{}{}", entry.lang, entry.timing.tag()))
+            .or_default()
+            .push(entry.clone());
+    }
+    let _ = writeln!(out, "## By language\n");
+    let _ = writeln!(out, "| Language | Runs | Avg WPM | Avg accuracy |");
+    let _ = writeln!(out, "|---|---:|---:|---:|");
+    for (lang, runs) in &by_lang {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {:.1} | {:.1}% |",
+            lang,
+            runs.len(),
+            average(runs, |e| e.wpm),
+            average(runs, |e| e.accuracy)
+        );
+    }
+
+    let mut by_day: BTreeMap<u64, Vec<Entry>> = BTreeMap::new();
+    for entry in &entries {
+        by_day
+            .entry(entry.timestamp / SECONDS_PER_DAY)
+            .or_default()
+            .push(entry.clone());
+    }
+    let _ = writeln!(out, "\n## Daily average WPM\n");
+    let _ = writeln!(out, "```");
+    let top = by_day
+        .values()
+        .map(|runs| average(runs, |e| e.wpm))
+        .fold(0.0, f32::max)
+        .max(1.0);
+    for (day, runs) in &by_day {
+        let wpm = average(runs, |e| e.wpm);
+        let bar = ((wpm / top) * CHART_WIDTH as f32).round() as usize;
+        let _ = writeln!(
+            out,
+            "{} {:<width$} {:>5.1} ({} runs)",
+            date(day * SECONDS_PER_DAY),
+            "#".repeat(bar),
+            wpm,
+            runs.len(),
+            width = CHART_WIDTH
+        );
+    }
+    let _ = writeln!(out, "```");
+    out
+}
+
+/// Test lengths runs are grouped by in aggregate exports, by largest word count.
+const LENGTHS: [(usize, &str); 5] = [
+    (10, "1-10"),
+    (25, "11-25"),
+    (50, "26-50"),
+    (100, "51-100"),
+    (usize::MAX, "100+"),
+];
+
+/// Runs of one week, language, word mode and test length, with nothing that
+/// identifies a single run.
+#[derive(Serialize)]
+pub struct Bucket {
+    /// Monday the week starts on.
+    week: String,
+    lang: String,
+    mode: WordMode,
+    /// Range of words per test.
+    length: &'static str,
+    timing: Timing,
+    runs: usize,
+    minutes: f32,
+    avg_wpm: f32,
+    best_wpm: f32,
+    avg_accuracy: f32,
+}
+
+/// The whole history reduced to weekly buckets, safe to share publicly.
+pub fn aggregate() -> Vec<Bucket> {
+    let mut groups: BTreeMap<(u64, String, String, usize, String), Vec<Entry>> = BTreeMap::new();
+    for entry in history::load() {
+        let day = entry.timestamp / SECONDS_PER_DAY;
+        // Day 0 of the Unix epoch was a Thursday.
+        let monday = day - (day + 3) % 7;
+        let length = LENGTHS
+            .iter()
+            .position(|&(max, _)| entry.words <= max)
+            .unwrap_or(LENGTHS.len() - 1);
+        let key = (
+            monday,
+            entry.lang.clone(),
+            format!("{:?}", entry.mode),
+            length,
+            format!("{:?}", entry.timing),
+        );
+        groups.entry(key).or_default().push(entry);
+    }
+
+    groups
+        .into_iter()
+        .map(|((monday, lang, _, length, _), runs)| Bucket {
+            week: date(monday * SECONDS_PER_DAY),
+            lang,
+            mode: runs[0].mode,
+            length: LENGTHS[length].1,
+            timing: runs[0].timing,
+            runs: runs.len(),
+            minutes: round(runs.iter().map(|e| e.seconds).sum::<f32>() / 60.0),
+            avg_wpm: round(average(&runs, |e| e.wpm)),
+            best_wpm: round(runs.iter().map(|e| e.wpm).fold(0.0, f32::max)),
+            avg_accuracy: round(average(&runs, |e| e.accuracy)),
+        })
+        .collect()
+}
+
+/// One decimal is plenty for shared aggregates.
+fn round(value: f32) -> f32 {
+    (value * 10.0).round() / 10.0
+}
+
+fn average(entries: &[Entry], value: impl Fn(&Entry) -> f32) -> f32 {
+    if entries.is_empty() {
+        return 0.0;
+    }
+    entries.iter().map(value).sum::<f32>() / entries.len() as f32
+}
+
+fn duration(seconds: f32) -> String {
+    let seconds = seconds as u64;
+    format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC for a Unix timestamp.
+pub fn date_time(timestamp: u64) -> String {
+    let minutes = timestamp % SECONDS_PER_DAY / 60;
+    format!(
+        "{} {:02}:{:02}",
+        date(timestamp),
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// `YYYY-MM-DD` in UTC for a Unix timestamp.
+fn date(timestamp: u64) -> String {
+    // Civil-from-days conversion, counting from 0000-03-01.
+    let days = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,104 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::data_dir,
+    race::{PLAYER, Race, Racer},
+};
+
+/// Head-to-head record against one networked opponent.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Rival {
+    pub races: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// Their fastest race against you.
+    pub best_wpm: f32,
+    /// Your fastest race against them.
+    pub your_best_wpm: f32,
+    /// Unix time in seconds of the last race.
+    pub last: u64,
+}
+
+/// Opponents met in networked races, by nickname.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Rivals {
+    #[serde(default)]
+    pub rivals: BTreeMap<String, Rival>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("rivals.json"))
+}
+
+/// Whether `a` is ahead of `b`: finishing earlier, finishing at all, or
+/// having typed more words when neither finished. `None` for a tie.
+fn ahead(a: &Racer, b: &Racer) -> Option<bool> {
+    match (a.place, b.place) {
+        (Some(a), Some(b)) => Some(a < b),
+        (Some(_), None) => Some(true),
+        (None, Some(_)) => Some(false),
+        (None, None) => (a.words != b.words).then_some(a.words > b.words),
+    }
+}
+
+impl Rivals {
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Records the finished race against every opponent in `opponents` (indices
+    /// into `race.racers`) that took part in it. Returns whether anything changed.
+    pub fn record(
+        &mut self,
+        race: &Race,
+        opponents: impl IntoIterator<Item = usize>,
+        wpm: f32,
+        timestamp: u64,
+    ) -> bool {
+        let player = &race.racers[PLAYER];
+        let mut changed = false;
+        for racer in opponents.into_iter().filter_map(|i| race.racers.get(i)) {
+            // Peers that are connected but didn't type in this race.
+            if racer.words == 0 && racer.place.is_none() {
+                continue;
+            }
+            let rival = self.rivals.entry(racer.name.clone()).or_default();
+            rival.races += 1;
+            match ahead(player, racer) {
+                Some(true) => rival.wins += 1,
+                Some(false) => rival.losses += 1,
+                None => {}
+            }
+            rival.best_wpm = rival.best_wpm.max(racer.wpm);
+            rival.your_best_wpm = rival.your_best_wpm.max(wpm);
+            rival.last = timestamp;
+            changed = true;
+        }
+        changed
+    }
+
+    /// Rivals with the most races first.
+    pub fn ranked(&self) -> Vec<(&str, &Rival)> {
+        let mut ranked: Vec<(&str, &Rival)> = self
+            .rivals
+            .iter()
+            .map(|(name, rival)| (name.as_str(), rival))
+            .collect();
+        ranked.sort_by(|a, b| b.1.races.cmp(&a.1.races).then(b.1.last.cmp(&a.1.last)));
+        ranked
+    }
+}
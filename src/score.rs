@@ -0,0 +1,370 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::data_dir;
+
+/// Runs kept on the leaderboard, enough to reorder them when the formula
+/// changes.
+const KEPT: usize = 100;
+/// Places shown on the leaderboard.
+pub const SHOWN: usize = 10;
+
+/// Values of a run a score formula can use.
+pub const VARIABLES: [&str; 5] = ["wpm", "acc", "errors", "seconds", "words"];
+
+/// A finished run as seen by a score formula.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Run {
+    pub wpm: f32,
+    /// Accuracy from 0.0 to 1.0, so powers of it weigh mistakes in.
+    pub acc: f32,
+    /// Mistyped characters.
+    pub errors: f32,
+    pub seconds: f32,
+    pub words: f32,
+}
+
+impl Run {
+    fn get(&self, variable: &str) -> f32 {
+        match variable {
+            "wpm" => self.wpm,
+            "acc" => self.acc,
+            "errors" => self.errors,
+            "seconds" => self.seconds,
+            _ => self.words,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f32),
+    Variable(&'static str),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+/// Headline score of a run, e.g. `wpm * acc^2 - errors`: numbers, the
+/// [`VARIABLES`], `+ - * / ^` and parentheses, `^` binding tightest and
+/// grouping to the right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+        };
+        let expr = parser.sum()?;
+        match parser.peek() {
+            None => Ok(Self { expr }),
+            Some(token) => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    /// Score of `run`. Division by zero and other undefined results give 0.
+    pub fn eval(&self, run: &Run) -> f32 {
+        let score = eval(&self.expr, run);
+        if score.is_finite() { score } else { 0.0 }
+    }
+}
+
+/// Score of `run` by `formula`, or its speed without one.
+pub fn score(formula: Option<&Formula>, run: &Run) -> f32 {
+    formula.map_or(run.wpm, |formula| formula.eval(run))
+}
+
+fn eval(expr: &Expr, run: &Run) -> f32 {
+    match expr {
+        Expr::Number(n) => *n,
+        Expr::Variable(name) => run.get(name),
+        Expr::Negate(expr) => -eval(expr, run),
+        Expr::Binary(left, op, right) => {
+            let (left, right) = (eval(left, run), eval(right, run));
+            match op {
+                '+' => left + right,
+                '-' => left - right,
+                '*' => left * right,
+                '/' => left / right,
+                _ => left.powf(right),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Variable(&'static str),
+    Op(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Variable(name) => write!(f, "{}", name),
+            Token::Op(op) => write!(f, "'{}'", op),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if "+-*/^()".contains(ch) {
+            tokens.push(Token::Op(ch));
+            chars.next();
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let mut number = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_digit() || **ch == '.') {
+                number.push(ch);
+                chars.next();
+            }
+            let n = number
+                .parse()
+                .map_err(|_| format!("{} is not a number", number))?;
+            tokens.push(Token::Number(n));
+        } else if ch.is_ascii_alphabetic() {
+            let mut name = String::new();
+            while let Some(&ch) = chars
+                .peek()
+                .filter(|ch| ch.is_ascii_alphanumeric() || **ch == '_')
+            {
+                name.push(ch);
+                chars.next();
+            }
+            let variable = VARIABLES
+                .iter()
+                .find(|&&variable| variable == name)
+                .ok_or_else(|| {
+                    format!("unknown variable {} (use {})", name, VARIABLES.join(", "))
+                })?;
+            tokens.push(Token::Variable(variable));
+        } else {
+            return Err(format!("unexpected '{}'", ch));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, one method per precedence level.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    /// Takes the next token if it is one of `ops`.
+    fn op(&mut self, ops: &str) -> Option<char> {
+        match self.peek() {
+            Some(&Token::Op(op)) if ops.contains(op) => {
+                self.next += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.op("+-") {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.op("*/") {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.op("-").is_some() {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.op("^").is_some() {
+            // -2 in `acc^-2` is an exponent, so the right side may be negated.
+            return Ok(Expr::Binary(Box::new(base), '^', Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self.peek().cloned();
+        self.next += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Variable(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Op('(')) => {
+                let expr = self.sum()?;
+                self.op(")").ok_or("missing ')'")?;
+                Ok(expr)
+            }
+            Some(token) => Err(format!("unexpected {}", token)),
+            None => Err("unexpected end of formula".to_string()),
+        }
+    }
+}
+
+/// Best runs by score, ranked by the formula in use when they are shown.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Leaderboard {
+    #[serde(default)]
+    pub runs: Vec<Run>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("leaderboard.json"))
+}
+
+impl Leaderboard {
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// The runs with their scores, highest first. Ties go to the earlier run,
+    /// which got there first.
+    pub fn ranked(&self, formula: Option<&Formula>) -> Vec<(f32, Run)> {
+        let mut ranked: Vec<(f32, Run)> = self
+            .runs
+            .iter()
+            .map(|run| (score(formula, run), *run))
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked
+    }
+
+    /// Adds `run`, returning its place when it made the [`SHOWN`] places.
+    pub fn record(&mut self, run: Run, formula: Option<&Formula>) -> Option<usize> {
+        let new = score(formula, &run);
+        self.runs = self
+            .ranked(formula)
+            .into_iter()
+            .map(|(_, run)| run)
+            .collect();
+        let place = self
+            .runs
+            .iter()
+            .position(|other| score(formula, other) < new)
+            .unwrap_or(self.runs.len());
+        self.runs.insert(place, run);
+        self.runs.truncate(KEPT);
+        (place < SHOWN).then_some(place + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run() -> Run {
+        Run {
+            wpm: 80.0,
+            acc: 0.5,
+            errors: 4.0,
+            seconds: 30.0,
+            words: 40.0,
+        }
+    }
+
+    fn score_of(text: &str) -> f32 {
+        Formula::parse(text).unwrap().eval(&run())
+    }
+
+    #[test]
+    fn follows_precedence() {
+        assert_eq!(score_of("wpm * acc^2 - errors"), 16.0);
+        assert_eq!(score_of("1 + 2 * 3"), 7.0);
+        assert_eq!(score_of("(1 + 2) * 3"), 9.0);
+        assert_eq!(score_of("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(score_of("-2 ^ 2"), -4.0);
+        assert_eq!(score_of("acc ^ -1"), 2.0);
+        assert_eq!(score_of("10 - 4 - 3"), 3.0);
+        assert_eq!(score_of("words / seconds * .5"), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn undefined_results_score_zero() {
+        assert_eq!(score_of("wpm / 0"), 0.0);
+        assert_eq!(score_of("(0 - 1) ^ 0.5"), 0.0);
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        let err = |text| Formula::parse(text).unwrap_err();
+        assert!(err("wpm * speed").starts_with("unknown variable speed"));
+        assert_eq!(err("wpm *"), "unexpected end of formula");
+        assert_eq!(err("(wpm"), "missing ')'");
+        assert_eq!(err("wpm acc"), "unexpected acc");
+        assert_eq!(err("wpm % 2"), "unexpected '%'");
+        assert_eq!(err("1.2.3"), "1.2.3 is not a number");
+        assert_eq!(err(""), "unexpected end of formula");
+    }
+
+    #[test]
+    fn leaderboard_places_runs_by_the_formula() {
+        let run = |wpm, acc| Run {
+            wpm,
+            acc,
+            ..Run::default()
+        };
+        let formula = Formula::parse("wpm * acc^2").unwrap();
+        let mut board = Leaderboard::default();
+        assert_eq!(board.record(run(100.0, 0.8), Some(&formula)), Some(1));
+        assert_eq!(board.record(run(90.0, 1.0), Some(&formula)), Some(1));
+        // A tie goes below the run that got there first.
+        assert_eq!(board.record(run(90.0, 1.0), Some(&formula)), Some(2));
+        // Without a formula the same runs go by speed.
+        let speeds: Vec<f32> = board.ranked(None).iter().map(|(s, _)| *s).collect();
+        assert_eq!(speeds, [100.0, 90.0, 90.0]);
+    }
+
+    #[test]
+    fn leaderboard_keeps_the_best_runs() {
+        let mut board = Leaderboard::default();
+        for wpm in 0..KEPT + 5 {
+            board.record(
+                Run {
+                    wpm: wpm as f32,
+                    ..Run::default()
+                },
+                None,
+            );
+        }
+        assert_eq!(board.runs.len(), KEPT);
+        assert_eq!(board.runs[0].wpm, (KEPT + 4) as f32);
+        assert_eq!(board.record(Run::default(), None), None);
+    }
+}
@@ -0,0 +1,536 @@
+use std::cmp::{max, min};
+
+use htils::ternary;
+use random_word::Lang;
+
+use crate::{
+    config::{Cue, TestMode, next_lang, prev_lang},
+    keys::{Action, Keymap},
+    packs, sound,
+    theme::Preset,
+    words::WordMode,
+};
+
+/// Pace caret targets stepped through with Left/Right, in WPM.
+const PACE_STEP: u32 = 5;
+const MAX_PACE: u32 = 300;
+const MAX_REVEAL: u32 = 20;
+/// Longest word length bound offered.
+const MAX_WORD_LENGTH: usize = 20;
+/// Volume steps, in percent.
+const VOLUME_STEP: u32 = 10;
+/// Durations stepped through with Left/Right, in seconds. Others can be typed.
+const DURATIONS: [u64; 5] = [15, 30, 60, 120, 300];
+const PRESETS: [Preset; 4] = [
+    Preset::Dark,
+    Preset::Light,
+    Preset::Gruvbox,
+    Preset::Solarized,
+];
+
+/// Titles of the settings pages, in the order of [`SettingItem::page`].
+pub const PAGES: [&str; 4] = ["Test", "Words", "Feedback", "Keys"];
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum SettingItem {
+    #[default]
+    Lang,
+    Mode,
+    Limit,
+    Duration,
+    WordMode,
+    Curve,
+    Pack,
+    /// Path of a word file typed instead of the dictionary, edited as text.
+    Wordlist,
+    /// Shortest generated word, 0 for any.
+    MinLength,
+    /// Longest generated word, 0 for any.
+    MaxLength,
+    Numbers,
+    Punctuation,
+    Cue,
+    Sound,
+    Volume,
+    Cohort,
+    /// Racing the replay of the fastest run.
+    Ghost,
+    Rollover,
+    Strict,
+    Pace,
+    /// Characters shown ahead in shadow typing.
+    Reveal,
+    /// Next words shown under the current one.
+    Upcoming,
+    Theme,
+    /// Key bound to an action, changed with the key recorder.
+    Key(Action),
+}
+
+impl SettingItem {
+    pub const ALL: [SettingItem; 45] = [
+        SettingItem::Lang,
+        SettingItem::Mode,
+        SettingItem::Limit,
+        SettingItem::Duration,
+        SettingItem::WordMode,
+        SettingItem::Curve,
+        SettingItem::Pack,
+        SettingItem::Wordlist,
+        SettingItem::MinLength,
+        SettingItem::MaxLength,
+        SettingItem::Numbers,
+        SettingItem::Punctuation,
+        SettingItem::Cue,
+        SettingItem::Sound,
+        SettingItem::Volume,
+        SettingItem::Cohort,
+        SettingItem::Ghost,
+        SettingItem::Rollover,
+        SettingItem::Strict,
+        SettingItem::Pace,
+        SettingItem::Reveal,
+        SettingItem::Upcoming,
+        SettingItem::Theme,
+        SettingItem::Key(Action::Pause),
+        SettingItem::Key(Action::Restart),
+        SettingItem::Key(Action::Quit),
+        SettingItem::Key(Action::Settings),
+        SettingItem::Key(Action::Plan),
+        SettingItem::Key(Action::Zen),
+        SettingItem::Key(Action::Layouts),
+        SettingItem::Key(Action::Menu),
+        SettingItem::Key(Action::Stats),
+        SettingItem::Key(Action::Tour),
+        SettingItem::Key(Action::Clipboard),
+        SettingItem::Key(Action::Drill),
+        SettingItem::Key(Action::Practice),
+        SettingItem::Key(Action::AddStep),
+        SettingItem::Key(Action::DeleteStep),
+        SettingItem::Key(Action::StepLang),
+        SettingItem::Key(Action::MoreRepeats),
+        SettingItem::Key(Action::FewerRepeats),
+        SettingItem::Key(Action::Search),
+        SettingItem::Key(Action::Undo),
+        SettingItem::Key(Action::Redo),
+        SettingItem::Key(Action::SaveDefaults),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingItem::Key(action) => match action {
+                Action::Pause => "Key: pause",
+                Action::Restart => "Key: restart",
+                Action::Quit => "Key: quit",
+                Action::Settings => "Key: settings",
+                Action::Plan => "Key: plan",
+                Action::Zen => "Key: zen",
+                Action::Layouts => "Key: layouts",
+                Action::Menu => "Key: menu",
+                Action::Stats => "Key: stats",
+                Action::Tour => "Key: tour (start)",
+                Action::Clipboard => "Key: clipboard (start)",
+                Action::Drill => "Key: drill (start)",
+                Action::Practice => "Key: practice (start)",
+                Action::AddStep => "Key: add step (plan)",
+                Action::DeleteStep => "Key: delete step (plan)",
+                Action::StepLang => "Key: step language (plan)",
+                Action::MoreRepeats => "Key: more repeats (plan)",
+                Action::FewerRepeats => "Key: fewer repeats (plan)",
+                Action::Search => "Key: search (settings)",
+                Action::Undo => "Key: undo (settings)",
+                Action::Redo => "Key: redo (settings)",
+                Action::SaveDefaults => "Key: save default (settings)",
+            },
+            SettingItem::Lang => "Language",
+            SettingItem::Mode => "Test mode",
+            SettingItem::Limit => "Words limit",
+            SettingItem::Duration => "Duration",
+            SettingItem::WordMode => "Word mode",
+            SettingItem::Curve => "Difficulty curve",
+            SettingItem::Pack => "Word pack",
+            SettingItem::Wordlist => "Wordlist",
+            SettingItem::MinLength => "Min word length",
+            SettingItem::MaxLength => "Max word length",
+            SettingItem::Numbers => "Numbers",
+            SettingItem::Punctuation => "Punctuation",
+            SettingItem::Cue => "Completion cue",
+            SettingItem::Sound => "Key sounds",
+            SettingItem::Volume => "Volume",
+            SettingItem::Cohort => "Cohort comparison",
+            SettingItem::Ghost => "Ghost race",
+            SettingItem::Rollover => "Forgive rollover",
+            SettingItem::Strict => "Strict mode",
+            SettingItem::Pace => "Pace caret",
+            SettingItem::Reveal => "Shadow reveal",
+            SettingItem::Upcoming => "Upcoming words",
+            SettingItem::Theme => "Theme",
+        }
+    }
+
+    /// Index into [`PAGES`] of the page the item is on.
+    pub fn page(self) -> usize {
+        match self {
+            SettingItem::Lang | SettingItem::Mode | SettingItem::Limit | SettingItem::Duration => 0,
+            SettingItem::WordMode
+            | SettingItem::Curve
+            | SettingItem::Pack
+            | SettingItem::Wordlist
+            | SettingItem::MinLength
+            | SettingItem::MaxLength
+            | SettingItem::Numbers
+            | SettingItem::Punctuation => 1,
+            SettingItem::Cue
+            | SettingItem::Sound
+            | SettingItem::Volume
+            | SettingItem::Cohort
+            | SettingItem::Ghost
+            | SettingItem::Rollover
+            | SettingItem::Strict
+            | SettingItem::Pace
+            | SettingItem::Reveal
+            | SettingItem::Upcoming
+            | SettingItem::Theme => 2,
+            SettingItem::Key(_) => 3,
+        }
+    }
+
+    /// Items on the page at `page`.
+    pub fn on_page(page: usize) -> Vec<SettingItem> {
+        Self::ALL
+            .into_iter()
+            .filter(|item| item.page() == page)
+            .collect()
+    }
+
+    /// Items edited by typing any character, so letters don't act as shortcuts.
+    pub fn is_text(self) -> bool {
+        self == SettingItem::Wordlist
+    }
+
+    /// Items whose name contains `query`, ignoring case.
+    pub fn matching(query: &str) -> Vec<SettingItem> {
+        let query = query.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .filter(|item| item.name().to_lowercase().contains(&query))
+            .collect()
+    }
+}
+
+/// Unsaved values edited in the settings popup.
+#[derive(Clone, PartialEq)]
+pub struct Draft {
+    pub lang: Lang,
+    pub mode: TestMode,
+    pub limit: String,
+    /// Seconds of a timed test.
+    pub duration: String,
+    pub word_mode: WordMode,
+    pub curve: f32,
+    pub cue: Cue,
+    pub sound: bool,
+    /// 0-100.
+    pub volume: u32,
+    pub cohort: bool,
+    pub ghost: bool,
+    /// Installed word pack replacing the language's dictionary.
+    pub pack: Option<String>,
+    /// Wordlist path, empty for none.
+    pub wordlist: String,
+    pub forgive_rollover: bool,
+    pub strict: bool,
+    /// Word length bounds, 0 for none.
+    pub min_length: usize,
+    pub max_length: usize,
+    pub numbers: bool,
+    pub punctuation: bool,
+    /// Target WPM of the pace caret, 0 when it's off.
+    pub pace: u32,
+    /// Characters shown from the caret on, 0 when shadow typing is off.
+    pub reveal: u32,
+    pub upcoming: bool,
+    pub theme: Preset,
+    pub keys: Keymap,
+}
+
+impl Default for Draft {
+    fn default() -> Self {
+        Self {
+            lang: Lang::En,
+            mode: TestMode::default(),
+            limit: "50".to_string(),
+            duration: "60".to_string(),
+            word_mode: WordMode::Random,
+            curve: 1.0,
+            cue: Cue::default(),
+            sound: false,
+            volume: 50,
+            cohort: true,
+            ghost: false,
+            pack: None,
+            wordlist: String::new(),
+            forgive_rollover: false,
+            strict: false,
+            min_length: 0,
+            max_length: 0,
+            numbers: false,
+            punctuation: false,
+            pace: 0,
+            reveal: 0,
+            upcoming: true,
+            theme: Preset::default(),
+            keys: Keymap::default(),
+        }
+    }
+}
+
+impl Draft {
+    pub fn value(&self, item: SettingItem) -> String {
+        match item {
+            SettingItem::Lang => format!("{:?}", self.lang),
+            SettingItem::Mode => format!("{:?}", self.mode),
+            SettingItem::Limit => self.limit.clone(),
+            SettingItem::Duration => format!("{}s", self.duration),
+            SettingItem::WordMode => format!("{:?}", self.word_mode),
+            SettingItem::Curve => format!("{:.1}", self.curve),
+            SettingItem::Cue => format!("{:?}", self.cue),
+            SettingItem::Sound if !sound::AVAILABLE => "Not in this build".to_string(),
+            SettingItem::Sound => on_off(self.sound),
+            SettingItem::Volume => format!("{}%", self.volume),
+            SettingItem::Cohort => on_off(self.cohort),
+            SettingItem::Ghost => on_off(self.ghost),
+            SettingItem::Pack => self.pack.clone().unwrap_or_else(|| "None".to_string()),
+            SettingItem::Wordlist if self.wordlist.is_empty() => "None".to_string(),
+            SettingItem::Wordlist => self.wordlist.clone(),
+            SettingItem::Rollover => on_off(self.forgive_rollover),
+            SettingItem::Strict => on_off(self.strict),
+            SettingItem::MinLength | SettingItem::MaxLength => {
+                let length = ternary!(
+                    item == SettingItem::MinLength,
+                    self.min_length,
+                    self.max_length
+                );
+                ternary!(length == 0, "Any".to_string(), format!("{} chars", length))
+            }
+            SettingItem::Numbers => on_off(self.numbers),
+            SettingItem::Punctuation => on_off(self.punctuation),
+            SettingItem::Pace if self.pace == 0 => "Off".to_string(),
+            SettingItem::Pace => format!("{} wpm", self.pace),
+            SettingItem::Reveal if self.reveal == 0 => "Off".to_string(),
+            SettingItem::Reveal => format!("{} chars", self.reveal),
+            SettingItem::Upcoming => on_off(self.upcoming),
+            SettingItem::Theme => format!("{:?}", self.theme),
+            SettingItem::Key(action) => self.keys.label(action),
+        }
+    }
+
+    /// Steps the item's value, as done with Left/Right.
+    pub fn adjust(&mut self, item: SettingItem, forward: bool) {
+        match item {
+            SettingItem::Lang => {
+                self.lang = if forward {
+                    next_lang(self.lang)
+                } else {
+                    prev_lang(self.lang)
+                }
+            }
+            SettingItem::Limit => {
+                let limit: usize = self.limit.parse().unwrap_or(0);
+                let limit = if forward {
+                    min(u16::MAX as usize, limit.saturating_add(1))
+                } else {
+                    max(1, limit.saturating_sub(1))
+                };
+                self.limit = limit.to_string();
+            }
+            SettingItem::Mode => {
+                self.mode = match (self.mode, forward) {
+                    (TestMode::Words, true) | (TestMode::Quote, false) => TestMode::Time,
+                    (TestMode::Time, true) | (TestMode::Parallel, false) => TestMode::Quote,
+                    (TestMode::Quote, true) | (TestMode::Code, false) => TestMode::Parallel,
+                    (TestMode::Parallel, true) | (TestMode::Words, false) => TestMode::Code,
+                    (TestMode::Code, true) | (TestMode::Time, false) => TestMode::Words,
+                }
+            }
+            SettingItem::Duration => {
+                let seconds: u64 = self.duration.parse().unwrap_or(0);
+                let next = if forward {
+                    DURATIONS.into_iter().find(|&d| d > seconds)
+                } else {
+                    DURATIONS.into_iter().rev().find(|&d| d < seconds)
+                };
+                if let Some(next) = next {
+                    self.duration = next.to_string();
+                }
+            }
+            SettingItem::WordMode => {
+                self.word_mode = match (self.word_mode, forward) {
+                    (WordMode::Random, true) | (WordMode::Rare, false) => WordMode::Curve,
+                    (WordMode::Curve, true) | (WordMode::Adaptive, false) => WordMode::Rare,
+                    (WordMode::Rare, true) | (WordMode::Weak, false) => WordMode::Adaptive,
+                    (WordMode::Adaptive, true) | (WordMode::Random, false) => WordMode::Weak,
+                    (WordMode::Weak, true) | (WordMode::Curve, false) => WordMode::Random,
+                }
+            }
+            SettingItem::Curve => {
+                let step = if forward { 0.1 } else { -0.1 };
+                self.curve = (self.curve + step).clamp(0.1, 5.0);
+            }
+            SettingItem::Cue => {
+                self.cue = match (self.cue, forward) {
+                    (Cue::Off, true) | (Cue::Bell, false) => Cue::Pulse,
+                    (Cue::Pulse, true) | (Cue::Off, false) => Cue::Bell,
+                    (Cue::Bell, true) | (Cue::Pulse, false) => Cue::Off,
+                }
+            }
+            SettingItem::Sound if !sound::AVAILABLE => {}
+            SettingItem::Sound => self.sound = !self.sound,
+            SettingItem::Volume => {
+                self.volume = if forward {
+                    min(100, self.volume + VOLUME_STEP)
+                } else {
+                    self.volume.saturating_sub(VOLUME_STEP)
+                }
+            }
+            SettingItem::Cohort => self.cohort = !self.cohort,
+            SettingItem::Ghost => self.ghost = !self.ghost,
+            SettingItem::Upcoming => self.upcoming = !self.upcoming,
+            SettingItem::Pack => {
+                // Cycles through "None" followed by every installed pack.
+                let mut options: Vec<Option<String>> = vec![None];
+                options.extend(packs::installed().into_iter().map(Some));
+                let i = options.iter().position(|p| *p == self.pack).unwrap_or(0);
+                let next = if forward {
+                    (i + 1) % options.len()
+                } else {
+                    (i + options.len() - 1) % options.len()
+                };
+                self.pack = options.swap_remove(next);
+            }
+            SettingItem::Rollover => self.forgive_rollover = !self.forgive_rollover,
+            SettingItem::Strict => self.strict = !self.strict,
+            SettingItem::MinLength => {
+                self.min_length = step_length(self.min_length, forward);
+                // Raising the minimum past the maximum pushes the maximum along.
+                if self.max_length != 0 {
+                    self.max_length = self.max_length.max(self.min_length);
+                }
+            }
+            SettingItem::MaxLength => {
+                self.max_length = step_length(self.max_length, forward);
+                if self.max_length != 0 {
+                    self.min_length = self.min_length.min(self.max_length);
+                }
+            }
+            SettingItem::Numbers => self.numbers = !self.numbers,
+            SettingItem::Punctuation => self.punctuation = !self.punctuation,
+            SettingItem::Theme => {
+                let i = PRESETS.iter().position(|&p| p == self.theme).unwrap_or(0);
+                let step = ternary!(forward, 1, PRESETS.len() - 1);
+                self.theme = PRESETS[(i + step) % PRESETS.len()];
+            }
+            SettingItem::Pace => {
+                self.pace = if forward {
+                    min(MAX_PACE, self.pace + PACE_STEP)
+                } else {
+                    self.pace.saturating_sub(PACE_STEP)
+                }
+            }
+            SettingItem::Reveal => {
+                self.reveal = if forward {
+                    min(MAX_REVEAL, self.reveal + 1)
+                } else {
+                    self.reveal.saturating_sub(1)
+                }
+            }
+            // Keys are changed by recording, see `App::record_key`.
+            SettingItem::Key(_) => {}
+            SettingItem::Wordlist => {}
+        }
+    }
+
+    /// Text of the items edited by typing.
+    fn text(&mut self, item: SettingItem) -> Option<&mut String> {
+        match item {
+            SettingItem::Limit => Some(&mut self.limit),
+            SettingItem::Duration => Some(&mut self.duration),
+            SettingItem::Wordlist => Some(&mut self.wordlist),
+            _ => None,
+        }
+    }
+
+    pub fn push_char(&mut self, item: SettingItem, ch: char) {
+        if !item.is_text() && !ch.is_ascii_digit() {
+            return;
+        }
+        if let Some(text) = self.text(item) {
+            text.push(ch);
+        }
+    }
+
+    pub fn pop_char(&mut self, item: SettingItem) {
+        if let Some(text) = self.text(item) {
+            text.pop();
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_string()
+}
+
+/// Draft being edited, the saved values it started from and its edit history.
+#[derive(Default)]
+pub struct Editor {
+    pub draft: Draft,
+    saved: Draft,
+    undo: Vec<Draft>,
+    redo: Vec<Draft>,
+}
+
+impl Editor {
+    pub fn new(saved: Draft) -> Self {
+        Self {
+            draft: saved.clone(),
+            saved,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Applies an edit, recording it for undo only if something changed.
+    pub fn edit(&mut self, edit: impl FnOnce(&mut Draft)) {
+        let before = self.draft.clone();
+        edit(&mut self.draft);
+        if self.draft != before {
+            self.undo.push(before);
+            self.redo.clear();
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(previous) = self.undo.pop() {
+            self.redo.push(std::mem::replace(&mut self.draft, previous));
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(next) = self.redo.pop() {
+            self.undo.push(std::mem::replace(&mut self.draft, next));
+        }
+    }
+
+    pub fn is_modified(&self, item: SettingItem) -> bool {
+        self.draft.value(item) != self.saved.value(item)
+    }
+}
+
+/// Next word length bound, 0 standing for "Any".
+fn step_length(length: usize, forward: bool) -> usize {
+    if forward {
+        min(MAX_WORD_LENGTH, length + 1)
+    } else {
+        length.saturating_sub(1)
+    }
+}
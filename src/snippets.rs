@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+
+/// Columns a Tab indents by, and the width tabs in snippets are expanded to.
+pub const TAB_WIDTH: usize = 4;
+
+#[derive(Deserialize, Debug)]
+pub struct Snippet {
+    pub title: String,
+    pub code: String,
+}
+
+/// Bundled snippets by language name.
+static SNIPPETS: Lazy<HashMap<String, Vec<Snippet>>> =
+    Lazy::new(|| serde_json::from_str(include_str!("../assets/snippets.json")).unwrap_or_default());
+
+/// Programming language of the snippets typed in code mode.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeLang {
+    #[default]
+    Rust,
+    Python,
+    Js,
+}
+
+impl CodeLang {
+    pub const ALL: [CodeLang; 3] = [CodeLang::Rust, CodeLang::Python, CodeLang::Js];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CodeLang::Rust => "rust",
+            CodeLang::Python => "python",
+            CodeLang::Js => "js",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CodeLang::Rust => "Rust",
+            CodeLang::Python => "Python",
+            CodeLang::Js => "JS",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A random bundled snippet in `lang`.
+pub fn random(lang: CodeLang, rng: &mut impl Rng) -> Option<&'static Snippet> {
+    SNIPPETS.get(lang.name())?.choose(rng)
+}
+
+/// Lines typed for `code`, each ending with the line break that Enter types
+/// except the last. Tabs become spaces, trailing whitespace and Windows line
+/// endings are dropped, and blank lines are skipped, so only whitespace that
+/// can be seen is typed.
+pub fn lines(code: &str) -> Vec<String> {
+    let lines: Vec<String> = code
+        .lines()
+        .map(|line| expand_tabs(line.trim_end()))
+        .filter(|line| !line.is_empty())
+        .collect();
+    let last = lines.len().saturating_sub(1);
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| if i < last { line + "\n" } else { line })
+        .collect()
+}
+
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::new();
+    for ch in line.chars() {
+        if ch == '\t' {
+            let width = TAB_WIDTH - expanded.chars().count() % TAB_WIDTH;
+            expanded.push_str(&" ".repeat(width));
+        } else {
+            expanded.push(ch);
+        }
+    }
+    expanded
+}
+
+/// Spaces a Tab types at `column` of `line`: the indentation left up to the
+/// next tab stop, or none when no indentation is expected there.
+pub fn tab_spaces(line: &str, column: usize) -> usize {
+    let indent = line
+        .chars()
+        .skip(column)
+        .take_while(|&ch| ch == ' ')
+        .count();
+    indent.min(TAB_WIDTH - column % TAB_WIDTH)
+}
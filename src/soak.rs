@@ -0,0 +1,117 @@
+use std::{fs, time::Instant};
+
+use rand::{Rng, thread_rng};
+
+use crate::{
+    app::{App, AppState},
+    config::{Config, DATA_DIR, data_dir},
+};
+
+const WORDS_PER_TEST: usize = 50;
+/// Chance of the synthetic typist hitting a wrong key.
+const TYPO_RATE: f64 = 0.03;
+/// Resident memory may grow this much past the first checkpoint before the
+/// run is reported as leaking.
+const MAX_GROWTH: f32 = 1.5;
+const CHECKPOINTS: usize = 10;
+
+/// Runs `tests` simulated tests back-to-back in a scratch data directory and
+/// prints memory and timing checkpoints, failing if memory keeps growing or
+/// history entries go missing.
+pub fn run(tests: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("ktapper-soak-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    DATA_DIR
+        .set(dir.clone())
+        .map_err(|_| "Data directory already set")?;
+
+    let result = soak(tests);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn soak(tests: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config {
+        limit: WORDS_PER_TEST,
+        min_words: 0,
+        min_seconds: 0.0,
+        ..Config::default()
+    };
+    let mut app = App::from(&config);
+    let mut rng = thread_rng();
+    let every = (tests / CHECKPOINTS).max(1);
+    let mut baseline = None;
+    let started = Instant::now();
+    let mut batch = Instant::now();
+
+    println!("test\trss_kb\tms_per_test");
+    for test in 1..=tests {
+        app.restart();
+        while matches!(app.app_state, AppState::Input) {
+            let expected = app
+                .current_word
+                .chars()
+                .nth(app.input.chars().count())
+                .unwrap_or(' ');
+            let ch = if rng.gen_bool(TYPO_RATE) {
+                '#'
+            } else {
+                expected
+            };
+            app.type_char(ch, Instant::now());
+        }
+
+        if test % every == 0 || test == tests {
+            // Measured with the writes done, so a backlog doesn't look like a leak.
+            app.io.flush();
+            let rss = rss_kb();
+            let per_test = batch.elapsed().as_secs_f32() * 1000.0 / every as f32;
+            batch = Instant::now();
+            println!(
+                "{}\t{}\t{:.2}",
+                test,
+                rss.map(|kb| kb.to_string()).unwrap_or("n/a".to_string()),
+                per_test
+            );
+            baseline = baseline.or(rss);
+            if let (Some(first), Some(now)) = (baseline, rss)
+                && now as f32 > first as f32 * MAX_GROWTH
+            {
+                return Err(format!("Memory grew from {} KB to {} KB", first, now).into());
+            }
+        }
+    }
+
+    // Dropping the app waits for the I/O worker to flush every queued write.
+    let flush = Instant::now();
+    drop(app);
+    let flush = flush.elapsed();
+
+    let saved = fs::read_to_string(data_dir().ok_or("No data directory")?.join("history.jsonl"))?
+        .lines()
+        .count();
+    println!(
+        "{} tests in {:.1}s, history flushed in {}ms, {} entries saved",
+        tests,
+        started.elapsed().as_secs_f32(),
+        flush.as_millis(),
+        saved
+    );
+    if saved != tests {
+        return Err(format!("Expected {} history entries, found {}", tests, saved).into());
+    }
+    Ok(())
+}
+
+/// Resident set size, where the platform exposes it.
+fn rss_kb() -> Option<u64> {
+    fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
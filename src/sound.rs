@@ -0,0 +1,66 @@
+#[cfg(feature = "sound")]
+use std::{
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+/// Whether this build can play sounds, see the `sound` cargo feature.
+pub const AVAILABLE: bool = cfg!(feature = "sound");
+
+/// Feedback played on a keystroke.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sound {
+    Click,
+    Error,
+}
+
+#[cfg(feature = "sound")]
+impl Sound {
+    fn tone(self) -> (f32, Duration) {
+        match self {
+            Sound::Click => (1200.0, Duration::from_millis(12)),
+            Sound::Error => (220.0, Duration::from_millis(60)),
+        }
+    }
+}
+
+/// Plays sounds on a thread of its own, opened on the first one, so the
+/// input loop never waits for the audio device. Without the `sound` feature
+/// nothing is played.
+#[derive(Default)]
+pub struct Player {
+    #[cfg(feature = "sound")]
+    sounds: Option<Sender<(Sound, f32)>>,
+}
+
+impl Player {
+    /// `volume` goes from 0.0 (silent) to 1.0.
+    #[cfg(feature = "sound")]
+    pub fn play(&mut self, sound: Sound, volume: f32) {
+        let sounds = self.sounds.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel::<(Sound, f32)>();
+            thread::spawn(move || {
+                use rodio::{OutputStream, Source, source::SineWave};
+
+                // Without an audio device sounds are dropped.
+                let Ok((_stream, handle)) = OutputStream::try_default() else {
+                    while rx.recv().is_ok() {}
+                    return;
+                };
+                while let Ok((sound, volume)) = rx.recv() {
+                    let (frequency, length) = sound.tone();
+                    let tone = SineWave::new(frequency)
+                        .take_duration(length)
+                        .amplify(volume.clamp(0.0, 1.0));
+                    let _ = handle.play_raw(tone);
+                }
+            });
+            tx
+        });
+        let _ = sounds.send((sound, volume));
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn play(&mut self, _sound: Sound, _volume: f32) {}
+}
@@ -0,0 +1,362 @@
+use std::{iter, ops::Range, time::Duration};
+
+use htils::ternary;
+use random_word::Lang;
+use ratatui::style::Color;
+
+use crate::{
+    app::App,
+    clock::wpm,
+    graphemes, metrics,
+    score::{self, Run},
+};
+
+impl App {
+    pub(crate) fn live_wpm(&self) -> f32 {
+        let correct_chars: usize = self
+            .words
+            .iter()
+            .map(|w| graphemes::count(&w.word) - w.wrong_chars.len())
+            .sum();
+
+        wpm(correct_chars, self.elapsed())
+    }
+
+    /// `(net, raw)` speed of the running test, counting the word being typed.
+    /// Net only counts correct characters, raw counts every keystroke.
+    pub(crate) fn live_speeds(&self) -> (f32, f32) {
+        let elapsed = self.elapsed();
+        (
+            wpm(self.correct_chars(), elapsed),
+            wpm(self.keystrokes, elapsed),
+        )
+    }
+
+    /// Consistency of the completed words' speeds, each timed from when the
+    /// word before it was completed.
+    pub(crate) fn word_consistency(&self) -> Option<f32> {
+        let mut start = Duration::ZERO;
+        let mut speeds = Vec::with_capacity(self.words.len());
+        for word in &self.words {
+            let time = word.finished_at.saturating_sub(start);
+            start = word.finished_at;
+            // A single-character first word completes the instant the test starts.
+            if !time.is_zero() {
+                let correct = graphemes::count(&word.word) - word.wrong_chars.len();
+                speeds.push(wpm(correct, time));
+            }
+        }
+        metrics::consistency(&speeds)
+    }
+
+    /// Correct characters so far, counting the word being typed.
+    pub(crate) fn correct_chars(&self) -> usize {
+        // Strict mode marks the position it waits at before anything is typed there.
+        let typing = graphemes::count(&self.input).saturating_sub(self.wrong_input_chars.len());
+        self.words
+            .iter()
+            .map(|w| graphemes::count(&w.word) - w.wrong_chars.len())
+            .sum::<usize>()
+            + typing
+    }
+
+    /// Accuracy of the running test, counting the word being typed.
+    pub(crate) fn live_accuracy(&self) -> f32 {
+        let typed: usize = self
+            .words
+            .iter()
+            .map(|w| graphemes::count(&w.word))
+            .sum::<usize>()
+            + graphemes::count(&self.input);
+        let wrong: usize = self
+            .words
+            .iter()
+            .map(|w| w.wrong_chars.len())
+            .sum::<usize>()
+            + self.wrong_input_chars.len();
+        if typed == 0 {
+            return 100.0;
+        }
+        (typed - wrong) as f32 / typed as f32 * 100.0
+    }
+
+    pub(crate) fn accuracy_color(&self, accuracy: f32) -> Color {
+        let [good, warning] = self.accuracy_thresholds;
+        if accuracy >= good {
+            self.theme.correct
+        } else if accuracy >= warning {
+            Color::Yellow
+        } else {
+            self.theme.wrong
+        }
+    }
+
+    pub(crate) fn calculate_accuracy(&mut self) -> f32 {
+        let total_typed_chars: usize = self.words.iter().map(|w| graphemes::count(&w.word)).sum();
+        let total_wrong_chars: usize = self.words.iter().map(|w| w.wrong_chars.len()).sum();
+
+        if total_typed_chars == 0 {
+            return 100.0;
+        }
+
+        let correct_chars = total_typed_chars - total_wrong_chars;
+
+        (correct_chars as f32 / total_typed_chars as f32) * 100.0
+    }
+
+    /// Places the finished test on the leaderboard, keeping its score and
+    /// place for the results.
+    pub(crate) fn rank_run(&mut self) {
+        let run = Run {
+            wpm: self.live_wpm(),
+            acc: self.calculate_accuracy() / 100.0,
+            errors: self
+                .words
+                .iter()
+                .map(|w| w.wrong_chars.len())
+                .sum::<usize>() as f32,
+            seconds: self.elapsed().as_secs_f32(),
+            words: self.words.len() as f32,
+        };
+        let place = self.leaderboard.record(run, self.score.as_ref());
+        self.scored = Some((score::score(self.score.as_ref(), &run), place));
+    }
+
+    /// Score and leaderboard place of the last saved run, as shown after
+    /// its speed.
+    pub(crate) fn score_label(&self) -> String {
+        let Some((score, place)) = self.scored else {
+            return String::new();
+        };
+        let score = self
+            .score
+            .as_ref()
+            .map(|_| format!(" | score {:.1}", score))
+            .unwrap_or_default();
+        let place = place
+            .map(|place| format!(" | #{} on the leaderboard", place))
+            .unwrap_or_default();
+        format!("{}{}", score, place)
+    }
+
+    /// Characters typed at the pace target by now, counted like WPM without
+    /// the spaces between words. `None` while the pace caret is off.
+    pub(crate) fn pace_chars(&self) -> Option<usize> {
+        if self.pace_wpm == 0 || !self.clock.is_started() {
+            return None;
+        }
+        let minutes = self.elapsed().as_secs_f32() / 60.0;
+        Some((self.pace_wpm as f32 * 5.0 * minutes) as usize)
+    }
+
+    /// Characters the pace target is ahead of the caret, negative when behind.
+    pub(crate) fn pace_lead(&self) -> Option<isize> {
+        let typed: usize = self
+            .words
+            .iter()
+            .map(|w| graphemes::count(&w.word))
+            .sum::<usize>()
+            + graphemes::count(&self.input);
+        self.pace_chars().map(|pace| pace as isize - typed as isize)
+    }
+
+    /// Where the pace caret is: the index of the word in the test and of the
+    /// character in it. `None` past the words generated so far.
+    pub(crate) fn pace_mark(&self) -> Option<(usize, usize)> {
+        let mut left = self.pace_chars()?;
+        let lengths = self
+            .words
+            .iter()
+            .map(|w| w.word.as_str())
+            .chain(iter::once(self.current_word.as_str()))
+            .chain(self.target.iter().map(String::as_str))
+            .map(graphemes::count);
+        for (i, len) in lengths.enumerate() {
+            if left < len {
+                return Some((i, left));
+            }
+            left -= len;
+        }
+        None
+    }
+
+    /// Where the ghost is, like [`Self::pace_mark`].
+    pub(crate) fn ghost_mark(&self) -> Option<(usize, usize)> {
+        let stroke = self.ghost.as_ref()?.at(self.elapsed())?;
+        Some((stroke.words as usize, stroke.typed as usize))
+    }
+
+    /// Words typed cleanly in a row, up to the last completed one.
+    pub(crate) fn streak(&self) -> usize {
+        self.words
+            .iter()
+            .rev()
+            .take_while(|w| w.wrong_chars.is_empty())
+            .count()
+    }
+
+    /// Keys mistyped in the test, most mistakes first.
+    pub(crate) fn worst_keys(&self) -> Vec<(char, u32)> {
+        let mut keys: Vec<(char, u32)> = self
+            .key_errors
+            .iter()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(&ch, &count)| (ch, count))
+            .collect();
+        keys.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        keys
+    }
+
+    /// Sorts by `column`, pressing the active column again flips the order.
+    pub(crate) fn sort_results(&mut self, column: SortColumn) {
+        let (active, descending) = self.results_sort;
+        self.results_sort = (column, active == column && !descending);
+    }
+
+    pub(crate) fn word_rows(&self) -> Vec<WordRow> {
+        let mut previous = Duration::ZERO;
+        let mut rows: Vec<WordRow> = self
+            .words
+            .iter()
+            .enumerate()
+            .map(|(index, w)| {
+                let time = w.finished_at.saturating_sub(previous);
+                previous = w.finished_at;
+                let correct_chars = graphemes::count(&w.word) - w.wrong_chars.len();
+                WordRow {
+                    index,
+                    time,
+                    wpm: wpm(correct_chars, time),
+                    errors: w.wrong_chars.len(),
+                }
+            })
+            .collect();
+
+        let (column, descending) = self.results_sort;
+        rows.sort_by(|a, b| {
+            let ordering = match column {
+                SortColumn::Index => a.index.cmp(&b.index),
+                SortColumn::Word => self.words[a.index].word.cmp(&self.words[b.index].word),
+                SortColumn::Time => a.time.cmp(&b.time),
+                SortColumn::Wpm => a.wpm.total_cmp(&b.wpm),
+                SortColumn::Errors => a.errors.cmp(&b.errors),
+            };
+            ternary!(descending, ordering.reverse(), ordering)
+        });
+        rows
+    }
+
+    /// `(language, words, wpm, accuracy)` for each language typed in the test,
+    /// timing every word from the completion of the one before it.
+    pub(crate) fn language_stats(&self) -> Vec<(Lang, usize, f32, f32)> {
+        let mut previous = Duration::ZERO;
+        let mut totals: Vec<(Lang, usize, usize, usize, Duration)> = Vec::new();
+        for word in &self.words {
+            let time = word.finished_at.saturating_sub(previous);
+            previous = word.finished_at;
+            let i = match totals.iter().position(|t| t.0 == word.lang) {
+                Some(i) => i,
+                None => {
+                    totals.push((word.lang, 0, 0, 0, Duration::ZERO));
+                    totals.len() - 1
+                }
+            };
+            let total = &mut totals[i];
+            total.1 += 1;
+            total.2 += graphemes::count(&word.word);
+            total.3 += word.wrong_chars.len();
+            total.4 += time;
+        }
+
+        totals
+            .into_iter()
+            .map(|(lang, words, chars, wrong, time)| {
+                let correct = chars - wrong;
+                (
+                    lang,
+                    words,
+                    wpm(correct, time),
+                    ternary!(chars > 0, correct as f32 / chars as f32 * 100.0, 100.0),
+                )
+            })
+            .collect()
+    }
+
+    /// Splits the finished words into `count` consecutive segments.
+    pub(crate) fn segment_stats(&self, count: usize) -> Vec<SegmentStats> {
+        let size = self.words.len().div_ceil(count.max(1)).max(1);
+        let mut segment_start = Duration::ZERO;
+
+        self.words
+            .chunks(size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let chars: usize = chunk.iter().map(|w| graphemes::count(&w.word)).sum();
+                let wrong: usize = chunk.iter().map(|w| w.wrong_chars.len()).sum();
+                let finished_at = chunk.last().map(|w| w.finished_at).unwrap_or_default();
+                let time = finished_at - segment_start;
+                segment_start = finished_at;
+
+                SegmentStats {
+                    words: i * size..i * size + chunk.len(),
+                    avg_len: chars as f32 / chunk.len() as f32,
+                    wpm: wpm(chars - wrong, time),
+                    accuracy: ternary!(
+                        chars > 0,
+                        (chars - wrong) as f32 / chars as f32 * 100.0,
+                        100.0
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Per-word statistics shown as a row of the results table.
+pub(crate) struct WordRow {
+    pub(crate) index: usize,
+    pub(crate) time: Duration,
+    pub(crate) wpm: f32,
+    pub(crate) errors: usize,
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
+pub(crate) enum SortColumn {
+    #[default]
+    Index,
+    Word,
+    Time,
+    Wpm,
+    Errors,
+}
+
+impl SortColumn {
+    pub(crate) const ALL: [SortColumn; 5] = [
+        SortColumn::Index,
+        SortColumn::Word,
+        SortColumn::Time,
+        SortColumn::Wpm,
+        SortColumn::Errors,
+    ];
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            SortColumn::Index => "#",
+            SortColumn::Word => "word",
+            SortColumn::Time => "time",
+            SortColumn::Wpm => "wpm",
+            SortColumn::Errors => "errors",
+        }
+    }
+}
+
+pub(crate) struct SegmentStats {
+    pub(crate) words: Range<usize>,
+    pub(crate) avg_len: f32,
+    pub(crate) wpm: f32,
+    pub(crate) accuracy: f32,
+}
+
+pub(crate) const CURVE_SEGMENTS: usize = 4;
+/// Keys listed under the results, worst first.
+pub(crate) const WORST_KEYS: usize = 10;
@@ -0,0 +1,40 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+/// One cell per completed word, colored by how many characters were mistyped,
+/// filling the area row by row. Only the most recent words are shown when
+/// they don't fit.
+pub struct HeatStrip<'a> {
+    errors: &'a [usize],
+}
+
+impl<'a> HeatStrip<'a> {
+    pub fn new(errors: &'a [usize]) -> Self {
+        Self { errors }
+    }
+}
+
+impl Widget for HeatStrip<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let cells = area.width as usize * area.height as usize;
+        let shown = &self.errors[self.errors.len().saturating_sub(cells)..];
+        for (i, errors) in shown.iter().enumerate() {
+            let color = match errors {
+                0 => Color::Green,
+                1 => Color::Yellow,
+                _ => Color::Red,
+            };
+            let (row, column) = (i / area.width as usize, i % area.width as usize);
+            buf[(area.x + column as u16, area.y + row as u16)]
+                .set_symbol("▀")
+                .set_style(Style::new().fg(color));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, data_dir};
+
+/// How long sending the report may hold up quitting.
+const SEND_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often each mode and feature was used in a finished test, kept
+/// locally and only sent with `telemetry = true`. Never holds typed text.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Usage {
+    #[serde(default)]
+    pub counts: BTreeMap<String, u64>,
+}
+
+/// Exactly what is sent: the app version and the counts, nothing else.
+#[derive(Serialize)]
+pub struct Report<'a> {
+    pub version: &'static str,
+    pub counts: &'a BTreeMap<String, u64>,
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("usage.json"))
+}
+
+impl Usage {
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path().ok_or("Could not find project directories")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn count(&mut self, name: &str) {
+        *self.counts.entry(name.to_string()).or_default() += 1;
+    }
+
+    pub fn report(&self) -> Report<'_> {
+        Report {
+            version: env!("CARGO_PKG_VERSION"),
+            counts: &self.counts,
+        }
+    }
+}
+
+/// Sends the counts gathered since the last report when telemetry is on,
+/// starting over once they are delivered. Failures keep them for next time.
+pub fn send(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let (true, Some(url)) = (config.telemetry, &config.telemetry_url) else {
+        return Ok(());
+    };
+    let usage = Usage::load();
+    if usage.counts.is_empty() {
+        return Ok(());
+    }
+    ureq::post(url)
+        .timeout(SEND_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(&serde_json::to_string(&usage.report())?)?;
+    Usage::default().save()
+}
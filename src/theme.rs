@@ -0,0 +1,145 @@
+use std::str::FromStr;
+
+use ratatui::{
+    style::{Color, Style},
+    widgets::Block,
+};
+use serde::{Deserialize, Serialize};
+
+/// Built-in color schemes, the base of the `[theme]` section.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Dark,
+    Light,
+    Gruvbox,
+    Solarized,
+}
+
+/// A color written as a name ("green", "lightblue") or as "#rrggbb".
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct ThemeColor(Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Color::from_str(&value).map(ThemeColor).map_err(|_| {
+            format!(
+                "invalid color \"{}\", expected e.g. \"green\" or \"#b8bb26\"",
+                value
+            )
+        })
+    }
+}
+
+impl From<ThemeColor> for String {
+    fn from(value: ThemeColor) -> Self {
+        value.0.to_string()
+    }
+}
+
+/// `[theme]` section: a preset with optional overrides for single colors.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub preset: Preset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correct: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrong: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<ThemeColor>,
+}
+
+/// Colors every screen is drawn with.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub correct: Color,
+    pub wrong: Color,
+    pub border: Color,
+    pub title: Color,
+    /// Selected rows and the player's own progress.
+    pub highlight: Color,
+    /// Secondary text: upcoming words, hints and labels.
+    pub dim: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Preset::Dark.theme()
+    }
+}
+
+impl Preset {
+    pub fn theme(self) -> Theme {
+        match self {
+            Preset::Dark => Theme {
+                correct: Color::Green,
+                wrong: Color::Red,
+                border: Color::Reset,
+                title: Color::Reset,
+                highlight: Color::Cyan,
+                dim: Color::DarkGray,
+            },
+            Preset::Light => Theme {
+                correct: Color::Rgb(0x2e, 0x7d, 0x32),
+                wrong: Color::Rgb(0xc6, 0x28, 0x28),
+                border: Color::Rgb(0x61, 0x61, 0x61),
+                title: Color::Rgb(0x21, 0x21, 0x21),
+                highlight: Color::Rgb(0x15, 0x65, 0xc0),
+                dim: Color::Rgb(0x9e, 0x9e, 0x9e),
+            },
+            Preset::Gruvbox => Theme {
+                correct: Color::Rgb(0xb8, 0xbb, 0x26),
+                wrong: Color::Rgb(0xfb, 0x49, 0x34),
+                border: Color::Rgb(0xa8, 0x99, 0x84),
+                title: Color::Rgb(0xfa, 0xbd, 0x2f),
+                highlight: Color::Rgb(0x83, 0xa5, 0x98),
+                dim: Color::Rgb(0x66, 0x5c, 0x54),
+            },
+            Preset::Solarized => Theme {
+                correct: Color::Rgb(0x85, 0x99, 0x00),
+                wrong: Color::Rgb(0xdc, 0x32, 0x2f),
+                border: Color::Rgb(0x58, 0x6e, 0x75),
+                title: Color::Rgb(0xb5, 0x89, 0x00),
+                highlight: Color::Rgb(0x26, 0x8b, 0xd2),
+                dim: Color::Rgb(0x65, 0x7b, 0x83),
+            },
+        }
+    }
+}
+
+impl ThemeConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn resolve(&self) -> Theme {
+        let base = self.preset.theme();
+        let pick = |color: Option<ThemeColor>, default: Color| color.map_or(default, |c| c.0);
+        Theme {
+            correct: pick(self.correct, base.correct),
+            wrong: pick(self.wrong, base.wrong),
+            border: pick(self.border, base.border),
+            title: pick(self.title, base.title),
+            highlight: pick(self.highlight, base.highlight),
+            dim: base.dim,
+        }
+    }
+}
+
+impl Theme {
+    /// Bordered block in the theme's border and title colors.
+    pub fn block(&self) -> Block<'static> {
+        Block::bordered()
+            .border_style(Style::new().fg(self.border))
+            .title_style(Style::new().fg(self.title))
+    }
+}
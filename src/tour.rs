@@ -0,0 +1,115 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::config::data_dir;
+
+/// Words in the short test typed during the tour.
+pub const TOUR_WORDS: usize = 5;
+const STEPS: usize = 6;
+
+fn marker() -> Option<std::path::PathBuf> {
+    data_dir().map(|dir| dir.join("tour_done"))
+}
+
+/// Whether the tour was finished or skipped before.
+pub fn seen() -> bool {
+    marker().is_some_and(|path| path.exists())
+}
+
+pub fn mark_seen() -> std::io::Result<()> {
+    let path = marker().ok_or(std::io::ErrorKind::NotFound)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, "")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Step {
+    Start,
+    Type,
+    Pause,
+    Resume,
+    Results,
+    Settings,
+}
+
+/// Guided walkthrough drawn over the real screens, advancing as the user
+/// performs each step.
+pub struct Tour {
+    pub step: Step,
+    /// Words limit to restore once the tour is over.
+    pub base_limit: usize,
+}
+
+impl Tour {
+    pub fn new(base_limit: usize) -> Self {
+        Self {
+            step: Step::Start,
+            base_limit,
+        }
+    }
+
+    pub fn number(&self) -> usize {
+        match self.step {
+            Step::Start => 1,
+            Step::Type => 2,
+            Step::Pause => 3,
+            Step::Resume => 4,
+            Step::Results => 5,
+            Step::Settings => 6,
+        }
+    }
+}
+
+/// Banner explaining the current step, with its hotkeys highlighted.
+pub struct TourBanner<'a> {
+    number: usize,
+    text: Vec<Span<'a>>,
+}
+
+impl<'a> TourBanner<'a> {
+    /// `parts` alternates plain text and hotkeys, starting with text.
+    pub fn new(tour: &Tour, parts: Vec<String>) -> Self {
+        let hotkey = Style::new()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let text = parts
+            .into_iter()
+            .enumerate()
+            .map(|(i, part)| {
+                if i % 2 == 1 {
+                    Span::styled(format!(" {} ", part), hotkey)
+                } else {
+                    Span::raw(part)
+                }
+            })
+            .collect();
+        Self {
+            number: tour.number(),
+            text,
+        }
+    }
+}
+
+impl Widget for TourBanner<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(format!("Tour {}/{}", self.number, STEPS))
+            .title_bottom(Line::from(" Tab to skip ").alignment(Alignment::Right))
+            .border_type(BorderType::Double)
+            .border_style(Style::new().fg(Color::Yellow));
+        Clear.render(area, buf);
+        Paragraph::new(Line::from(self.text))
+            .block(block)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::history::Entry;
+
+/// Runs the accuracy trend is fitted over.
+pub const TREND_RUNS: usize = 20;
+/// Fewer runs than this say too little about a trend.
+const MIN_RUNS: usize = 8;
+/// How many standard errors below zero the slope has to be. Close to the 5%
+/// one-sided t value for the sample sizes used here.
+const T_CRITICAL: f32 = 1.75;
+/// Recent tests this much longer on average than earlier ones are named as a
+/// likely cause.
+const LONGER_TESTS: f32 = 1.25;
+
+/// A significant drop in accuracy over the last runs.
+pub struct TrendAlert {
+    pub runs: usize,
+    /// Change in accuracy per run, in percentage points.
+    pub slope: f32,
+    /// What changed in the later runs compared to the earlier ones.
+    pub factors: Vec<String>,
+}
+
+/// Fits a line through the accuracy of the last [`TREND_RUNS`] runs and
+/// returns an alert when it falls with significance.
+pub fn accuracy_alert(entries: &[Entry]) -> Option<TrendAlert> {
+    let runs = &entries[entries.len().saturating_sub(TREND_RUNS)..];
+    if runs.len() < MIN_RUNS {
+        return None;
+    }
+    let accuracy: Vec<f32> = runs.iter().map(|e| e.accuracy).collect();
+    let (slope, t) = regression(&accuracy);
+    if t > -T_CRITICAL {
+        return None;
+    }
+    Some(TrendAlert {
+        runs: runs.len(),
+        slope,
+        factors: factors(runs),
+    })
+}
+
+/// Least squares slope of `values` against their index, with its t statistic.
+fn regression(values: &[f32]) -> (f32, f32) {
+    let n = values.len() as f32;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f32>() / n;
+    let sxx: f32 = (0..values.len()).map(|x| (x as f32 - mean_x).powi(2)).sum();
+    let sxy: f32 = values
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (x as f32 - mean_x) * (y - mean_y))
+        .sum();
+    let slope = sxy / sxx;
+    let residuals: f32 = values
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (y - mean_y - slope * (x as f32 - mean_x)).powi(2))
+        .sum();
+    let error = (residuals / (n - 2.0) / sxx).sqrt();
+    // A perfectly straight line has no error, so any drop is significant.
+    let t = if error > 0.0 {
+        slope / error
+    } else {
+        slope.signum() * f32::INFINITY
+    };
+    (slope, t)
+}
+
+/// Languages, word modes and timing new in the later half of `runs`, and
+/// tests grown longer.
+fn factors(runs: &[Entry]) -> Vec<String> {
+    let (earlier, later) = runs.split_at(runs.len() / 2);
+    let mut factors = Vec::new();
+
+    let mut langs: Vec<&str> = later
+        .iter()
+        .map(|e| e.lang.as_str())
+        .filter(|lang| !earlier.iter().any(|e| e.lang == *lang))
+        .collect();
+    langs.sort();
+    langs.dedup();
+    factors.extend(langs.iter().map(|lang| format!("new language {}", lang)));
+
+    let mut modes: Vec<&'static str> = later
+        .iter()
+        .map(|e| e.mode)
+        .filter(|mode| !earlier.iter().any(|e| e.mode == *mode))
+        .map(|mode| mode.name())
+        .collect();
+    modes.sort();
+    modes.dedup();
+    factors.extend(modes.iter().map(|mode| format!("new word mode {}", mode)));
+
+    if later
+        .iter()
+        .any(|e| earlier.iter().all(|p| p.timing != e.timing))
+    {
+        factors.push("word timing".to_string());
+    }
+
+    let words =
+        |runs: &[Entry]| runs.iter().map(|e| e.words).sum::<usize>() as f32 / runs.len() as f32;
+    let (before, after) = (words(earlier), words(later));
+    if after > before * LONGER_TESTS {
+        factors.push(format!(
+            "longer tests ({:.0} to {:.0} words)",
+            before, after
+        ));
+    }
+    factors
+}
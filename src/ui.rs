@@ -0,0 +1,2353 @@
+use std::{
+    cmp::{max, min},
+    collections::HashSet,
+    iter,
+    time::Instant,
+};
+
+use chrono::Datelike;
+use htils::ternary;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Flex, Layout},
+    prelude::*,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        BorderType, Borders, Cell, Clear, LineGauge, List, ListItem, ListState, Padding, Paragraph,
+        Row, Table, Widget, Wrap,
+    },
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    actions::{PRESS_FLASH, ResultAction},
+    app::{App, AppState, RECENT_RUNS, STATS_PAGES, UPCOMING_WORDS},
+    calendar::{self, LEVELS, WEEKS},
+    chart::{AccuracyChart, WpmChart},
+    clock, cohort,
+    config::lang_code,
+    graphemes,
+    history::{self},
+    keys::{Action, Keymap},
+    layouts::{Heatmap, LAYOUTS},
+    ngrams::SETS,
+    race::{PLAYER, RaceHud},
+    records::NewBest,
+    replay::SPEEDS,
+    report,
+    settings::{PAGES, SettingItem},
+    stats::{CURVE_SEGMENTS, SortColumn, WORST_KEYS},
+    strip::HeatStrip,
+    theme::Theme,
+    tour::{Step as TourStep, TourBanner},
+    words::{self, WordMode},
+    zen::Zen,
+};
+
+/// Rows of the longest settings page.
+fn settings_rows() -> u16 {
+    (0..PAGES.len())
+        .map(|page| SettingItem::on_page(page).len())
+        .max()
+        .unwrap_or_default() as u16
+}
+
+pub(crate) fn render_settings(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let area = frame.area();
+
+    let rows_height = settings_rows();
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(min(rows_height + 8, area.height)),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+    let popup_area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Length(50),
+            Constraint::Percentage(30),
+        ])
+        .split(popup_layout[1])[1];
+
+    let page = app.selected_setting.page();
+    let title = match &app.settings_search {
+        Some(query) => format!("Settings /{}", query),
+        None => format!("Settings - {} ({}/{})", PAGES[page], page + 1, PAGES.len()),
+    };
+    let block = theme.block().title(title).border_type(BorderType::Rounded);
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(block, popup_area);
+
+    let settings_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(3),
+        ])
+        .split(popup_area);
+
+    // Search looks through every page; otherwise only the selected item's page shows.
+    let visible = match app.settings_search {
+        Some(_) => app.visible_settings(),
+        None => SettingItem::on_page(page),
+    };
+    let rows: Vec<ListItem> = if visible.is_empty() {
+        vec![ListItem::new(
+            Line::from("No matching settings").alignment(Alignment::Center),
+        )]
+    } else {
+        visible
+            .iter()
+            .map(|&item| {
+                let style = if item == app.selected_setting {
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                let marker = ternary!(app.editor.is_modified(item), "*", " ");
+                let value = if app.recording && item == app.selected_setting {
+                    "press a key".to_string()
+                } else {
+                    app.editor.draft.value(item)
+                };
+                let line = if item.is_text() {
+                    // Paths show their end, where the file name is.
+                    let width = (settings_layout[0].width as usize).saturating_sub(24);
+                    let skip = value.chars().count().saturating_sub(width);
+                    let value: String = value.chars().skip(skip).collect();
+                    format!("{}{:<19}[ {} ]", marker, item.name(), value)
+                } else {
+                    format!("{}{:<19}< {} >", marker, item.name(), value)
+                };
+                ListItem::new(Line::styled(line, style))
+            })
+            .collect()
+    };
+    // Only scrolls when search results outgrow the popup.
+    let mut list_state = ListState::default()
+        .with_offset(app.settings_offset)
+        .with_selected(
+            visible
+                .iter()
+                .position(|&item| item == app.selected_setting),
+        );
+    frame.render_stateful_widget(List::new(rows), settings_layout[0], &mut list_state);
+    app.settings_offset = list_state.offset();
+    app.settings_area = settings_layout[0];
+
+    let notice = match (&app.settings_notice, app.selected_setting) {
+        (Some(notice), _) => Line::styled(notice.as_str(), Style::new().fg(theme.wrong)),
+        (None, SettingItem::Key(_)) if app.recording => Line::from("Backspace to cancel"),
+        (None, SettingItem::Key(_)) => Line::from("Left/Right to record a new key"),
+        (None, item) if item.is_text() => Line::from("Type a path, empty for none"),
+        _ => Line::default(),
+    };
+    frame.render_widget(
+        Paragraph::new(notice).alignment(Alignment::Center),
+        settings_layout[1],
+    );
+
+    let key = |action| app.keys.label(action);
+    let help = if app.settings_search.is_some() {
+        "Type to filter | Enter to jump | Esc to cancel".to_string()
+    } else {
+        format!(
+            "Enter to apply | Esc to discard | {} to search\n{} to save as default | PgUp/PgDn page\n{} to undo | {} to redo | * modified",
+            key(Action::Search),
+            key(Action::SaveDefaults),
+            key(Action::Undo),
+            key(Action::Redo)
+        )
+    };
+    let help_text = Paragraph::new(help).alignment(Alignment::Center);
+    frame.render_widget(help_text, settings_layout[2]);
+}
+
+pub(crate) fn render_plan(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(3)])
+        .split(frame.area());
+
+    let key = |action| app.keys.label(action);
+    Line::from(format!(
+        "{} add | {} delete | Left/Right limit | {} language | {}/{} repeat | Enter start | Esc back",
+        key(Action::AddStep),
+        key(Action::DeleteStep),
+        key(Action::StepLang),
+        key(Action::MoreRepeats),
+        key(Action::FewerRepeats)
+    ))
+    .render(chunks[1], frame.buffer_mut());
+
+    let block = theme.block().title("Plan").border_type(BorderType::Rounded);
+
+    if app.plan.is_empty() {
+        let empty = Paragraph::new(format!(
+            "No steps yet, press {} to add one",
+            app.keys.label(Action::AddStep)
+        ))
+        .block(block)
+        .alignment(Alignment::Center);
+        frame.render_widget(empty, chunks[0]);
+        return;
+    }
+
+    let items: Vec<Line> = app
+        .plan
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            Line::from(format!(
+                "{}. {} - {} {} words x{}",
+                i + 1,
+                step.label,
+                step.lang.to_uppercase(),
+                step.limit,
+                step.repeat
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_symbol("> ")
+        .highlight_style(
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if let AppState::Plan(list_state, _) = &mut app.app_state {
+        frame.render_stateful_widget(list, chunks[0], list_state);
+    }
+}
+
+pub(crate) fn render_practice(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(3)])
+        .split(frame.area());
+
+    Line::from(format!(
+        "Up/Down select | Enter start {} words | Esc back",
+        app.words_limit
+    ))
+    .render(chunks[1], frame.buffer_mut());
+
+    let items: Vec<Line> = SETS
+        .iter()
+        .map(|set| {
+            Line::from(vec![
+                Span::raw(format!("{:<22}", set.name)),
+                Span::styled(set.grams.join(" "), Style::new().fg(theme.dim)),
+            ])
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            theme
+                .block()
+                .title("Practice - n-gram drills")
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_symbol("> ")
+        .highlight_style(
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if let AppState::Practice(list_state) = &mut app.app_state {
+        frame.render_stateful_widget(list, chunks[0], list_state);
+    }
+}
+
+pub(crate) fn render_history(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(3)])
+        .split(frame.area());
+
+    Line::from("Up/Down scroll | PgUp/PgDn page | Home/End | Esc back")
+        .render(chunks[1], frame.buffer_mut());
+
+    let block = theme
+        .block()
+        .title(format!("History ({} runs)", app.history.len()))
+        .border_type(BorderType::Rounded);
+
+    if app.history.is_empty() {
+        let empty = Paragraph::new("No saved runs yet")
+            .block(block)
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, chunks[0]);
+        return;
+    }
+
+    let items: Vec<Line> = app
+        .history
+        .iter()
+        .map(|entry| {
+            Line::from(format!(
+                "{}  {:>4.0} wpm  {:>6.2}%  {:<5} {:>4} words  {:>6.1}s{}",
+                report::date_time(entry.timestamp),
+                entry.wpm,
+                entry.accuracy,
+                entry.lang,
+                entry.words,
+                entry.seconds,
+                entry.timing.tag()
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_symbol("> ")
+        .highlight_style(
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if let AppState::History(list_state, _) = &mut app.app_state {
+        frame.render_stateful_widget(list, chunks[0], list_state);
+    }
+}
+
+/// Join code and players of a LAN race, until the host starts it.
+pub(crate) fn render_lobby(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let Some(lobby) = &app.lobby else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(3)])
+        .split(frame.area());
+
+    let help = if lobby.is_host() {
+        format!(
+            "Enter start a {}-word {} race | Esc leave",
+            app.words_limit,
+            lang_code(app.lang)
+        )
+    } else {
+        "Waiting for the host to start the race | Esc leave".to_string()
+    };
+    Line::from(help).render(chunks[1], frame.buffer_mut());
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Join code ", Style::new().fg(theme.dim)),
+            Span::styled(
+                lobby.code.as_str(),
+                Style::new()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::styled(
+            ternary!(
+                lobby.is_host(),
+                "Players join with ktapper --join <code>",
+                "Joined the race with this code"
+            ),
+            Style::new().fg(theme.dim),
+        ),
+        Line::default(),
+        Line::from(format!("{} (you)", app.nickname)),
+    ];
+    let mut peers: Vec<&String> = app.peers.keys().collect();
+    peers.sort();
+    lines.extend(peers.into_iter().map(|name| Line::from(name.as_str())));
+
+    let title = match app.net_status() {
+        Some(status) => format!("LAN race ({})", status),
+        None => "LAN race".to_string(),
+    };
+    let players = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .block(theme.block().title(title).border_type(BorderType::Rounded));
+    frame.render_widget(players, chunks[0]);
+}
+
+/// The input of the last run as it was at the playback position, with the
+/// word it was typing and the next ones.
+pub(crate) fn render_replay(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let AppState::Replay(replay, _) = &app.app_state else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    // Before the first keystroke the first word waits untouched.
+    let shown = replay.frame(&app.frames);
+    let (word, target) = match (shown, app.frames.first()) {
+        (Some(shown), _) => (shown.word, shown.target.as_str()),
+        (None, Some(first)) => (0, first.target.as_str()),
+        (None, None) => (0, ""),
+    };
+    let (input, wrong) = match shown {
+        Some(shown) => (shown.input.as_str(), shown.wrong.clone()),
+        None => ("", HashSet::new()),
+    };
+    let mut upcoming: Vec<&str> = Vec::new();
+    let mut last = word;
+    for later in app.frames.iter().filter(|later| later.word > word) {
+        if later.word != last && upcoming.len() < UPCOMING_WORDS {
+            upcoming.push(&later.target);
+            last = later.word;
+        }
+    }
+    let word_display = Paragraph::new(vec![
+        Line::from(visible(target)),
+        Line::styled(
+            upcoming.join(" "),
+            Style::new().fg(theme.dim).remove_modifier(Modifier::BOLD),
+        ),
+    ])
+    .alignment(Alignment::Center)
+    .style(Style::new().add_modifier(Modifier::BOLD));
+    frame.render_widget(word_display, chunks[1]);
+
+    let mut styled_input = stylize(input, &wrong, &theme);
+    styled_input.push(Span::styled(
+        visible(graphemes::skip(target, graphemes::count(input))),
+        Style::new().fg(theme.dim),
+    ));
+    let input_block = theme
+        .block()
+        .border_type(BorderType::Rounded)
+        .title(format!("Replay - word {}", word + 1));
+    frame.render_widget(
+        Paragraph::new(Line::from(styled_input)).block(input_block),
+        chunks[2],
+    );
+
+    let ratio = ternary!(
+        replay.end.is_zero(),
+        1.0,
+        replay.position.as_secs_f64() / replay.end.as_secs_f64()
+    );
+    let label = format!(
+        "{} / {} {}",
+        clock::format_time(replay.position),
+        clock::format_time(replay.end),
+        ternary!(
+            replay.playing,
+            format!("{}x", replay.speed),
+            "Paused".to_string()
+        )
+    );
+    frame.render_widget(
+        LineGauge::default()
+            .filled_style(Style::new().fg(theme.highlight))
+            .unfilled_style(Style::new().fg(theme.dim))
+            .label(label)
+            .ratio(ratio.clamp(0.0, 1.0)),
+        chunks[3],
+    );
+
+    let speeds: Vec<String> = SPEEDS.iter().map(u32::to_string).collect();
+    Line::from(format!(
+        "Space pause | Left/Right seek | {} speed | Esc back to results",
+        speeds.join("/")
+    ))
+    .render(chunks[5], frame.buffer_mut());
+}
+
+pub(crate) fn render_rivals(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(3)])
+        .split(frame.area());
+
+    Line::from("Up/Down scroll | Esc back").render(chunks[1], frame.buffer_mut());
+
+    let ranked = app.rivals.ranked();
+    let block = theme
+        .block()
+        .title(format!("Rivals ({})", ranked.len()))
+        .border_type(BorderType::Rounded);
+
+    if ranked.is_empty() {
+        let empty = Paragraph::new("No networked races yet")
+            .block(block)
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, chunks[0]);
+        return;
+    }
+
+    let rows: Vec<Row> = ranked
+        .iter()
+        .map(|(name, rival)| {
+            let record = format!("{}-{}", rival.wins, rival.losses);
+            let color = match rival.wins.cmp(&rival.losses) {
+                std::cmp::Ordering::Greater => theme.correct,
+                std::cmp::Ordering::Less => theme.wrong,
+                std::cmp::Ordering::Equal => Color::Reset,
+            };
+            Row::new(vec![
+                Cell::from(name.to_string()),
+                Cell::from(record).style(Style::new().fg(color)),
+                Cell::from(rival.races.to_string()),
+                Cell::from(format!("{:.0}", rival.best_wpm)),
+                Cell::from(format!("{:.0}", rival.your_best_wpm)),
+                Cell::from(report::date_time(rival.last)),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(17),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "rival",
+            "won-lost",
+            "races",
+            "their best",
+            "your best",
+            "last race",
+        ])
+        .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .highlight_symbol("> ")
+    .row_highlight_style(
+        Style::default()
+            .fg(theme.highlight)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    if let AppState::Rivals(table_state, _) = &mut app.app_state {
+        frame.render_stateful_widget(table, chunks[0], table_state);
+    }
+}
+
+/// Width of the stats screen's panels.
+const STATS_WIDTH: u16 = 72;
+
+pub(crate) fn render_stats(frame: &mut Frame, app: &App) {
+    let [area, footer] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(frame.area());
+    let tabs: Vec<Span> = STATS_PAGES
+        .iter()
+        .enumerate()
+        .map(|(page, name)| {
+            let style = ternary!(
+                page == app.stats_page,
+                Style::new()
+                    .fg(app.theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+                Style::new().fg(app.theme.dim)
+            );
+            Span::styled(format!(" {} ", name), style)
+        })
+        .collect();
+    let [tabs_area, area] =
+        Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+    Line::from(tabs)
+        .centered()
+        .render(tabs_area, frame.buffer_mut());
+    Line::from("Tab next page | Esc back").render(footer, frame.buffer_mut());
+
+    match app.stats_page {
+        0 => render_dashboard(frame, app, area),
+        _ => render_activity(frame, app, area),
+    }
+}
+
+/// Totals of every saved run, the accuracy of the last ones and a row per
+/// language.
+fn render_dashboard(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let dashboard = &app.dashboard;
+    let [area] = Layout::horizontal([Constraint::Length(STATS_WIDTH)])
+        .flex(Flex::Center)
+        .areas(area);
+    let langs_height = dashboard.langs.len().min(MAX_STATS_LANGS) as u16 + 3;
+    let [totals_area, chart_area, langs_area] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Fill(1),
+        Constraint::Length(langs_height),
+    ])
+    .areas(area);
+
+    let label = Style::new().fg(theme.dim);
+    let totals = vec![
+        Line::from(vec![
+            Span::styled("Runs ", label),
+            Span::raw(format!("{:<8}", dashboard.runs)),
+            Span::styled("Average ", label),
+            Span::raw(format!("{:<10}", format!("{:.0} wpm", dashboard.wpm))),
+            Span::styled("Best ", label),
+            Span::raw(format!("{:<10}", format!("{:.0} wpm", dashboard.best))),
+            Span::styled("Accuracy ", label),
+            Span::raw(format!("{:.1}%", dashboard.accuracy)),
+        ]),
+        Line::from(vec![
+            Span::styled("Time typed ", label),
+            Span::raw(format!("{:<14}", clock::format_time(dashboard.time))),
+            Span::styled("Words ", label),
+            Span::raw(dashboard.words.to_string()),
+        ]),
+    ];
+    frame.render_widget(
+        Paragraph::new(totals).block(
+            theme
+                .block()
+                .title("All runs")
+                .border_type(BorderType::Rounded)
+                .padding(Padding::horizontal(1)),
+        ),
+        totals_area,
+    );
+
+    AccuracyChart::new(&dashboard.recent_accuracy, theme).render(chart_area, frame.buffer_mut());
+
+    let rows = dashboard.langs.iter().take(MAX_STATS_LANGS).map(|lang| {
+        Row::new([
+            lang.lang.clone(),
+            lang.runs.to_string(),
+            format!("{:.0}", lang.wpm),
+            format!("{:.0}", lang.best),
+            format!("{:.1}%", lang.accuracy),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(6),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(["language", "runs", "wpm", "best", "acc"])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .block(
+        theme
+            .block()
+            .title("By language")
+            .border_type(BorderType::Rounded),
+    );
+    frame.render_widget(table, langs_area);
+}
+
+/// Runs by time of day and the activity calendar.
+fn render_activity(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let alert = app.trend.as_ref().map(|trend| {
+        let mut lines = vec![Line::styled(
+            format!(
+                "Accuracy is falling by {:.2} points per run over the last {} runs",
+                -trend.slope, trend.runs
+            ),
+            Style::new().fg(theme.wrong).add_modifier(Modifier::BOLD),
+        )];
+        if !trend.factors.is_empty() {
+            lines.push(Line::from(format!(
+                "Likely factors: {}",
+                trend.factors.join(", ")
+            )));
+        }
+        lines
+    });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(alert.as_ref().map_or(0, |lines| lines.len() as u16)),
+        ])
+        .split(area);
+
+    if let Some(lines) = alert {
+        frame.render_widget(
+            Paragraph::new(lines).alignment(Alignment::Center),
+            chunks[1],
+        );
+    }
+    let block = theme
+        .block()
+        .title("Stats by time of day")
+        .border_type(BorderType::Rounded);
+    let [area] = Layout::horizontal([Constraint::Length(STATS_WIDTH)])
+        .flex(Flex::Center)
+        .areas(chunks[0]);
+    let [area, calendar_area] =
+        Layout::vertical([Constraint::Length(7), Constraint::Length(10)]).areas(area);
+    render_calendar(frame, app, calendar_area);
+
+    let top = app
+        .day_stats
+        .iter()
+        .map(|s| s.wpm)
+        .fold(0.0, f32::max)
+        .max(1.0);
+    let rows = app.day_stats.iter().map(|s| {
+        if s.runs == 0 {
+            return Row::new([s.part.label().to_string(), "0".to_string()])
+                .style(Style::new().fg(theme.dim));
+        }
+        let bar = "█".repeat((s.wpm / top * 20.0).round() as usize);
+        Row::new([
+            s.part.label().to_string(),
+            s.runs.to_string(),
+            format!("{:.0}", s.wpm),
+            format!("{:.1}%", s.accuracy),
+            bar,
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(18),
+            Constraint::Length(6),
+            Constraint::Length(5),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["time of day", "runs", "wpm", "acc", ""])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .block(block);
+    frame.render_widget(table, area);
+}
+
+/// Tests per day as a grid of weeks, shaded by how many were typed.
+fn render_calendar(frame: &mut Frame, app: &App, area: Rect) {
+    let (calendar, theme) = (&app.calendar, app.theme);
+    const WEEKDAYS: [&str; 7] = ["Mon", "", "Wed", "", "Fri", "", "Sun"];
+    const SHADES: [&str; LEVELS + 1] = ["· ", "░░", "▒▒", "▓▓", "██"];
+
+    // Month names above the first week starting in each month.
+    let mut months = String::new();
+    let mut shown = None;
+    for (week, start) in calendar.starts.iter().enumerate() {
+        let column = 4 + 2 * week;
+        if shown != Some(start.month()) && months.len() <= column {
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&start.format("%b").to_string());
+            shown = Some(start.month());
+        }
+    }
+    let mut lines = vec![Line::styled(months, Style::new().fg(theme.dim))];
+    for (weekday, label) in WEEKDAYS.iter().enumerate() {
+        let mut spans = vec![Span::styled(
+            format!("{:<4}", label),
+            Style::new().fg(theme.dim),
+        )];
+        for week in &calendar.days {
+            let Some(tests) = week[weekday] else {
+                continue;
+            };
+            let level = calendar.level(tests);
+            let color = ternary!(level == 0, theme.dim, theme.correct);
+            spans.push(Span::styled(SHADES[level], Style::new().fg(color)));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let total: u32 = calendar.days.iter().flatten().flatten().sum();
+    let block = theme
+        .block()
+        .title(format!(
+            "Activity - {} tests in {} weeks, {}",
+            total,
+            WEEKS,
+            app.streak.label(calendar::today())
+        ))
+        .border_type(BorderType::Rounded);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub(crate) fn render_report(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    Line::from(format!(
+        "{} Restart | {} Plan | {} Exit",
+        app.keys.label(Action::Restart),
+        app.keys.label(Action::Plan),
+        app.keys.label(Action::Quit)
+    ))
+    .render(chunks[2], frame.buffer_mut());
+
+    let Some(session) = &app.session else {
+        return;
+    };
+
+    let items: Vec<Line> = session
+        .results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            Line::from(format!(
+                "{}. {} - {} {} words, {:.0} wpm, {:.2}%, {}s",
+                i + 1,
+                r.label,
+                lang_code(r.lang),
+                r.words,
+                r.wpm,
+                r.accuracy,
+                r.seconds
+            ))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        theme
+            .block()
+            .title("Session report")
+            .border_type(BorderType::Rounded),
+    );
+    frame.render_widget(list, chunks[0]);
+
+    let (words, wpm, accuracy, seconds) = session.totals();
+    let totals = Paragraph::new(format!(
+        "{} tests, {} words, {:.0} wpm, Accuracy: {:.2}%, time elapsed: {:.1}s",
+        session.results.len(),
+        words,
+        wpm,
+        accuracy,
+        seconds
+    ))
+    .block(theme.block())
+    .alignment(Alignment::Center);
+    frame.render_widget(totals, chunks[1]);
+}
+
+pub(crate) fn render_tour(frame: &mut Frame, app: &App) {
+    let Some(tour) = &app.tour else {
+        return;
+    };
+    let key = |action| app.keys.label(action);
+    let parts: Vec<String> = match tour.step {
+        TourStep::Start => vec![
+            "Welcome to ktapper! Press ".into(),
+            "Enter".into(),
+            " to start a short test".into(),
+        ],
+        TourStep::Type => vec![
+            "Type the bold word above. The clock starts on your first key and words advance on their own".into(),
+        ],
+        TourStep::Pause => vec![
+            "Need a break? Press ".into(),
+            key(Action::Pause),
+            " to pause the test".into(),
+        ],
+        TourStep::Resume => vec![
+            "The clock is stopped. Press ".into(),
+            "any key".into(),
+            " to resume and finish the remaining words".into(),
+        ],
+        TourStep::Results => vec![
+            "Your speed, accuracy and per-word times. ".into(),
+            "1-5".into(),
+            " sorts the table. Now press ".into(),
+            key(Action::Settings),
+            " to open settings".into(),
+        ],
+        TourStep::Settings => vec![
+            "Move with ".into(),
+            "Up/Down".into(),
+            ", change values with ".into(),
+            "Left/Right".into(),
+            ", then ".into(),
+            "Enter".into(),
+            " to save or ".into(),
+            "Esc".into(),
+            " to leave".into(),
+        ],
+    };
+
+    let area = frame.area();
+    let height = 4.min(area.height);
+    let banner = Rect {
+        y: area.height.saturating_sub(height + 3),
+        height,
+        ..area
+    };
+    frame.render_widget(TourBanner::new(tour, parts), banner);
+}
+
+/// Developer overlay toggled with F12: event loop timings, the current
+/// state and how many words are queued.
+pub(crate) fn render_dev(frame: &mut Frame, app: &App) {
+    if !app.dev.shown {
+        return;
+    }
+    let dev = &app.dev;
+    let ms = |d: std::time::Duration| format!("{:.2} ms", d.as_secs_f64() * 1000.0);
+    let mut lines = vec![
+        format!("state    {}", app.app_state.name()),
+        format!("frame    {} (avg {})", ms(dev.frame), ms(dev.frame_avg)),
+        format!("event    {} (avg {})", ms(dev.event), ms(dev.event_avg)),
+        format!("latency  {}", ms(dev.latency)),
+        format!("counts   {} frames, {} events", dev.frames, dev.events),
+        format!("queue    {} words", app.target.len()),
+    ];
+    if !app.wrong_input_chars.is_empty() {
+        let mut wrong: Vec<&usize> = app.wrong_input_chars.iter().collect();
+        wrong.sort();
+        let wrong: Vec<String> = wrong.iter().map(|i| i.to_string()).collect();
+        lines.push(format!("wrong    {}", wrong.join(" ")));
+    }
+
+    let area = frame.area();
+    let width = (lines.iter().map(|l| l.len()).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect {
+        x: area.width - width,
+        y: 0,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, overlay);
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    frame.render_widget(
+        Paragraph::new(text).block(
+            app.theme
+                .block()
+                .title("Dev (F12)")
+                .padding(Padding::horizontal(1)),
+        ),
+        overlay,
+    );
+}
+
+pub(crate) fn render_preview(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(4),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    let words: Vec<&str> = std::iter::once(app.current_word.as_str())
+        .chain(app.target.iter().map(String::as_str))
+        .collect();
+    let text = Paragraph::new(words.join(" "))
+        .block(
+            theme
+                .block()
+                .title(format!(
+                    "Preview - {}",
+                    app.pack.as_deref().unwrap_or_default()
+                ))
+                .border_type(BorderType::Rounded),
+        )
+        .wrap(Wrap { trim: true });
+    frame.render_widget(text, chunks[0]);
+
+    let chars: usize = words.iter().map(|w| w.chars().count()).sum::<usize>() + words.len() - 1;
+    let average_wpm = if app.recent.is_empty() {
+        None
+    } else {
+        Some(app.recent.iter().map(|e| e.wpm).sum::<f32>() / app.recent.len() as f32)
+    };
+    let duration = average_wpm
+        .filter(|&wpm| wpm > 0.0)
+        .map(|wpm| {
+            let seconds = (chars as f32 / 5.0 / wpm * 60.0).round() as u32;
+            format!(
+                "~{}:{:02} at your {:.0} wpm average",
+                seconds / 60,
+                seconds % 60,
+                wpm
+            )
+        })
+        .unwrap_or_else(|| "no saved runs to estimate duration".to_string());
+    let difficulty = words::estimate(&words);
+    let label = match difficulty.score {
+        1..=3 => "easy",
+        4..=6 => "medium",
+        _ => "hard",
+    };
+    let stats = Paragraph::new(vec![
+        Line::from(format!(
+            "{} words, {} characters, {}",
+            words.len(),
+            chars,
+            duration
+        )),
+        Line::from(format!(
+            "Difficulty {}/10 ({}): avg length {:.1}, {:.0}% rare chars, {:.0}% punctuation",
+            difficulty.score,
+            label,
+            difficulty.avg_len,
+            difficulty.rare * 100.0,
+            difficulty.punctuation * 100.0
+        )),
+    ])
+    .alignment(Alignment::Center);
+    frame.render_widget(stats, chunks[1]);
+
+    Line::from(format!(
+        "Enter Begin | {} New text | Esc Menu | {} Exit",
+        app.keys.label(Action::Restart),
+        app.keys.label(Action::Quit)
+    ))
+    .render(chunks[2], frame.buffer_mut());
+}
+
+pub(crate) fn render_menu(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Length(RECENT_RUNS as u16 + 3),
+            Constraint::Fill(1),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    Line::from("ktapper")
+        .style(Style::new().add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .render(chunks[1], frame.buffer_mut());
+    // Weak mode names the keys it's practicing.
+    let weak_keys: String = app
+        .word_gen
+        .weak_keys()
+        .iter()
+        .filter(|_| app.word_gen.mode == WordMode::Weak)
+        .map(|&(ch, _)| format!(" {}", key_label(ch)))
+        .collect();
+    Line::from(format!(
+        "{} | {} | {:?}{}",
+        lang_code(app.lang),
+        app.length_label(),
+        app.word_gen.mode,
+        weak_keys
+    ))
+    .alignment(Alignment::Center)
+    .render(chunks[2], frame.buffer_mut());
+
+    let [runs_area] = Layout::horizontal([Constraint::Length(44)])
+        .flex(Flex::Center)
+        .areas(chunks[3]);
+    let block = theme
+        .block()
+        .title(
+            format!("Last runs {}", history::trend(&app.recent))
+                .trim_end()
+                .to_string(),
+        )
+        .border_type(BorderType::Rounded);
+    if app.recent.is_empty() {
+        Paragraph::new("No saved runs yet")
+            .alignment(Alignment::Center)
+            .block(block)
+            .render(runs_area, frame.buffer_mut());
+    } else {
+        let rows = app.recent.iter().rev().map(|entry| {
+            Row::new([
+                format!("{:.0} wpm", entry.wpm),
+                format!("{:.1}%", entry.accuracy),
+                format!("{} words", entry.words),
+                entry.lang.clone(),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(11),
+                Constraint::Length(4),
+            ],
+        )
+        .header(Row::new(["speed", "acc", "length", "lang"]).style(Style::new().fg(theme.dim)))
+        .block(block);
+        frame.render_widget(table, runs_area);
+    }
+
+    let mut notes = Vec::new();
+    if !app.regressions.is_empty() {
+        let keys: Vec<String> = app
+            .regressions
+            .iter()
+            .map(|r| format!("{} +{:.0}%", r.key, r.slowdown * 100.0))
+            .collect();
+        notes.push(Line::styled(
+            format!(
+                "Slower than your 30-day baseline: {} - {} to drill",
+                keys.join(", "),
+                app.keys.label(Action::Drill)
+            ),
+            Style::new().fg(Color::Yellow),
+        ));
+    }
+    if let Some(notice) = &app.menu_notice {
+        notes.push(Line::styled(notice.as_str(), Style::new().fg(theme.wrong)));
+    }
+    Paragraph::new(notes)
+        .alignment(Alignment::Center)
+        .render(chunks[4], frame.buffer_mut());
+
+    Line::from(format!(
+        "Enter Start | {} Clipboard | {} Practice | {} Stats | {} Settings | {} Plan | {} Zen | {} Tour | {} Exit",
+        app.keys.label(Action::Clipboard),
+        app.keys.label(Action::Practice),
+        app.keys.label(Action::Stats),
+        app.keys.label(Action::Settings),
+        app.keys.label(Action::Plan),
+        app.keys.label(Action::Zen),
+        app.keys.label(Action::Tour),
+        app.keys.label(Action::Quit)
+    ))
+    .render(chunks[5], frame.buffer_mut());
+}
+
+pub(crate) fn render_zen(frame: &mut Frame, zen: &Zen, keys: &Keymap, theme: Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    let finished = zen.is_finished();
+    let block = theme
+        .block()
+        .title(ternary!(finished, "Zen - finished", "Zen"))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(chunks[0]);
+    let scroll = zen.rows(inner.width).saturating_sub(inner.height);
+    let text = Paragraph::new(format!("{}{}", zen.text, ternary!(finished, "", "_")))
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(text, chunks[0]);
+
+    let seconds = zen.elapsed().as_secs();
+    let counts = format!(
+        "{} words | {} chars | {}:{:02}",
+        zen.words(),
+        zen.chars(),
+        seconds / 60,
+        seconds % 60
+    );
+    if !finished {
+        Line::from(counts)
+            .alignment(Alignment::Center)
+            .render(chunks[1], frame.buffer_mut());
+        Line::from("Esc to finish").render(chunks[2], frame.buffer_mut());
+        return;
+    }
+
+    // Nothing was there to type, so there is no accuracy to show.
+    Line::from(vec![
+        Span::styled(
+            format!("raw {:.0} wpm", zen.raw_wpm()),
+            Style::new().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(
+            " | {} | {} keystrokes | {} corrections",
+            counts, zen.keystrokes, zen.corrections
+        )),
+    ])
+    .alignment(Alignment::Center)
+    .render(chunks[1], frame.buffer_mut());
+    Line::from(format!(
+        "Enter Write again | {} Restart | {} Menu | {} Exit",
+        keys.label(Action::Restart),
+        keys.label(Action::Menu),
+        keys.label(Action::Quit)
+    ))
+    .render(chunks[2], frame.buffer_mut());
+}
+
+pub(crate) fn render_layouts(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let mut constraints = vec![Constraint::Length(2)];
+    constraints.extend(LAYOUTS.iter().map(|_| Constraint::Length(5)));
+    constraints.extend([Constraint::Fill(1), Constraint::Length(3)]);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(frame.area());
+
+    let keystrokes: u64 = app.key_stats.chars.values().sum();
+    let heading = if keystrokes == 0 {
+        "No keystrokes recorded yet, finish a test first".to_string()
+    } else {
+        format!(
+            "Layout comparison based on {} recorded keystrokes",
+            keystrokes
+        )
+    };
+    Line::from(heading)
+        .alignment(Alignment::Center)
+        .render(chunks[0], frame.buffer_mut());
+
+    let analyses: Vec<_> = LAYOUTS
+        .iter()
+        .map(|layout| layout.analyze(&app.key_stats))
+        .collect();
+    let best = analyses
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.travel.total_cmp(&b.1.travel))
+        .map(|(i, _)| i);
+
+    for (i, (layout, analysis)) in LAYOUTS.iter().zip(&analyses).enumerate() {
+        let title = ternary!(
+            keystrokes > 0 && best == Some(i),
+            format!("{} (least travel)", layout.name),
+            layout.name.to_string()
+        );
+        let block = theme.block().title(title).border_type(BorderType::Rounded);
+        let inner = block.inner(chunks[i + 1]);
+        frame.render_widget(block, chunks[i + 1]);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(34), Constraint::Fill(1)])
+            .split(inner);
+        frame.render_widget(Heatmap::new(layout, &app.key_stats), columns[0]);
+
+        let metrics = Paragraph::new(vec![
+            Line::from(format!(
+                "Finger travel: {:.2} keys/keystroke",
+                analysis.travel
+            )),
+            Line::from(format!("Same-finger bigrams: {:.1}%", analysis.same_finger)),
+            Line::from(format!("Home row usage: {:.1}%", analysis.home_row)),
+        ]);
+        frame.render_widget(metrics, columns[1]);
+    }
+
+    Line::from(format!(
+        "Esc back to results | {} Exit",
+        app.keys.label(Action::Quit)
+    ))
+    .render(chunks[LAYOUTS.len() + 2], frame.buffer_mut());
+}
+
+/// Drops text modifiers and backgrounds, keeping only foreground colors so
+/// mistakes stay visible with fewer escape sequences per frame.
+pub(crate) fn simplify(buf: &mut Buffer) {
+    for cell in buf.content.iter_mut() {
+        cell.modifier = Modifier::empty();
+        cell.bg = Color::Reset;
+    }
+}
+
+/// Layout picked from the terminal width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LayoutMode {
+    /// Up to 60 columns: a single column without borders on the sides.
+    Compact,
+    Medium,
+    /// From 120 columns: live stats in a sidebar next to the test.
+    Wide,
+}
+
+impl LayoutMode {
+    pub(crate) fn from_width(width: u16) -> Self {
+        match width {
+            ..=60 => LayoutMode::Compact,
+            120.. => LayoutMode::Wide,
+            _ => LayoutMode::Medium,
+        }
+    }
+
+    /// Borders of the test and results blocks, whose sides cost two columns.
+    pub(crate) fn borders(self) -> Borders {
+        ternary!(
+            self == LayoutMode::Compact,
+            Borders::TOP | Borders::BOTTOM,
+            Borders::ALL
+        )
+    }
+}
+
+/// Languages listed on the stats overview, most typed first.
+const MAX_STATS_LANGS: usize = 4;
+
+/// Width of the live stats sidebar in the wide layout.
+const SIDEBAR_WIDTH: u16 = 24;
+/// Rows of the heat strip in the sidebar.
+const SIDEBAR_STRIP_ROWS: u16 = 3;
+
+/// Rows of fixed height in the test and results layout.
+const TEST_FIXED_ROWS: u16 = 3 + 1 + 3 + 3;
+/// Rows the results table needs for its borders, header and a few runs.
+const RESULTS_TABLE_ROWS: u16 = 8;
+
+/// Smallest area the current screen is laid out in without its parts
+/// collapsing, from the fixed rows and columns of its layout.
+pub(crate) fn min_size(app: &App) -> (u16, u16) {
+    match &app.app_state {
+        AppState::Menu => (46, 2 + 1 + RECENT_RUNS as u16 + 3 + 3),
+        AppState::Input => (30, TEST_FIXED_ROWS + 2),
+        AppState::Pause => (34, TEST_FIXED_ROWS + 2),
+        AppState::Settings => (50, max(settings_rows() + 8, TEST_FIXED_ROWS + 2)),
+        // The table gets the top 40% of the screen.
+        AppState::Results(_) => (50, RESULTS_TABLE_ROWS * 100 / 40),
+        AppState::Stats(_) => (STATS_WIDTH, 22),
+        _ => (40, 12),
+    }
+}
+
+pub(crate) fn fits(area: Rect, app: &App) -> bool {
+    let (width, height) = min_size(app);
+    area.width >= width && area.height >= height
+}
+
+/// Placeholder shown instead of a screen the terminal is too small for.
+pub(crate) fn render_too_small(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+    let (width, height) = min_size(app);
+    let lines = vec![
+        Line::styled(
+            "Terminal too small",
+            Style::new().add_modifier(Modifier::BOLD),
+        ),
+        Line::from(format!(
+            "{}x{}, needs at least {}x{}",
+            area.width, area.height, width, height
+        )),
+        Line::styled("Resize it to continue", Style::new().fg(app.theme.dim)),
+    ];
+    let [text_area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        text_area,
+    );
+}
+
+pub(crate) fn render(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    match &app.app_state {
+        AppState::Menu => return render_menu(frame, app),
+        AppState::Preview => return render_preview(frame, app),
+        AppState::Layouts(_) => return render_layouts(frame, app),
+        AppState::History(..) => return render_history(frame, app),
+        AppState::Rivals(..) => return render_rivals(frame, app),
+        AppState::Replay(..) => return render_replay(frame, app),
+        AppState::Plan(..) => return render_plan(frame, app),
+        AppState::Report => return render_report(frame, app),
+        AppState::Stats(_) => return render_stats(frame, app),
+        AppState::Practice(_) => return render_practice(frame, app),
+        AppState::Lobby => return render_lobby(frame, app),
+        AppState::Zen(zen) | AppState::ZenResults(zen) => {
+            return render_zen(frame, zen, &app.keys, app.theme);
+        }
+        _ => {}
+    }
+
+    let layout = LayoutMode::from_width(frame.area().width);
+    let in_test = matches!(
+        app.app_state,
+        AppState::Input | AppState::Pause | AppState::Settings
+    );
+    let (main_area, sidebar) = if in_test && layout == LayoutMode::Wide && app.sidebar {
+        let [main_area, sidebar] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(SIDEBAR_WIDTH)])
+                .areas(frame.area());
+        (main_area, Some(sidebar))
+    } else {
+        (frame.area(), None)
+    };
+
+    let vertical_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(40),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(3),
+        ])
+        .split(main_area);
+
+    let is_settings = matches!(app.app_state, AppState::Settings);
+    let accuracy = if matches!(&app.app_state, AppState::Results(_)) {
+        Some(app.calculate_accuracy())
+    } else {
+        None
+    };
+    let wpm_summary = accuracy.map(|_| {
+        let wpm = app.live_wpm();
+        let lang = lang_code(app.lang);
+        // No reference typists are bundled for timed tests, quotes or pairs, and
+        // runs timed only within words aren't comparable.
+        let comparison = cohort::percentile(
+            ternary!(app.is_timed(), "time", "words"),
+            &lang,
+            app.words_limit,
+            wpm,
+        )
+        .filter(|_| {
+            app.show_cohort
+                && !app.boundary_pause
+                && app.quote.is_none()
+                && app.snippet.is_none()
+                && app.ngrams.is_none()
+                && app.pairs.is_empty()
+        })
+        .map(|p| {
+            format!(
+                " - you're at the {} percentile of typists for {}-word {} tests",
+                cohort::ordinal(p.round() as u32),
+                app.words_limit,
+                lang
+            )
+        })
+        .unwrap_or_default();
+        format!("{:.0} wpm{}{}", wpm, comparison, app.score_label())
+    });
+    let word_rows = accuracy.map(|_| app.word_rows());
+    let time = app.finished_time().unwrap_or_default();
+    let bar = app.result_actions();
+    let new_best = accuracy
+        .zip(app.new_best)
+        .map(|(accuracy, best)| new_best_label(best, app.live_wpm(), accuracy));
+    let chart_end = accuracy.map(|_| (time, app.correct_chars()));
+    let metrics = accuracy.map(|_| {
+        app.metrics
+            .summary(app.elapsed())
+            .into_iter()
+            .map(|(label, value)| format!("{} {}", label, value))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    });
+
+    match &mut app.app_state {
+        AppState::Input | AppState::Pause | AppState::Settings => {
+            let is_paused = matches!(app.app_state, AppState::Pause);
+
+            let help_text = if is_paused {
+                format!(
+                    "Any key to resume | {} to Exit | {} for settings | {} for plan | {} for zen | {} for stats",
+                    app.keys.label(Action::Quit),
+                    app.keys.label(Action::Settings),
+                    app.keys.label(Action::Plan),
+                    app.keys.label(Action::Zen),
+                    app.keys.label(Action::Stats)
+                )
+            } else {
+                format!(
+                    "Press {} to pause",
+                    app.keys.label(Action::Pause).to_uppercase()
+                )
+            };
+            match app.word_gen.relaxed {
+                Some(relaxed) if !is_paused => {
+                    Line::styled(relaxed.notice(), Style::new().fg(theme.wrong))
+                        .render(vertical_chunks[5], frame.buffer_mut())
+                }
+                _ => Line::from(help_text).render(vertical_chunks[5], frame.buffer_mut()),
+            }
+
+            if let Some(translation) = app.translation() {
+                let [line_area, translation_area] =
+                    Layout::vertical([Constraint::Length(2), Constraint::Length(1)])
+                        .areas(vertical_chunks[1]);
+                frame.render_widget(sentence_line(app), line_area);
+                let translation = Paragraph::new(translation)
+                    .alignment(Alignment::Center)
+                    .style(Style::new().fg(theme.dim).add_modifier(Modifier::ITALIC));
+                frame.render_widget(translation, translation_area);
+            } else if app.snippet.is_some() {
+                render_code(frame, app, vertical_chunks[0].union(vertical_chunks[1]));
+            } else if app.shows_line() {
+                frame.render_widget(sentence_line(app), vertical_chunks[1]);
+            } else {
+                let typed = graphemes::count(&app.input);
+                let rest = graphemes::skip(&app.current_word, typed);
+                let shown = &app.current_word[..app.current_word.len() - rest.len()];
+                let word_display = Paragraph::new(vec![
+                    Line::from(format!("{}{}", shown, veil(rest, 0, app.reveal))),
+                    Line::styled(
+                        app.upcoming_words().join(" "),
+                        Style::new().fg(theme.dim).remove_modifier(Modifier::BOLD),
+                    ),
+                ])
+                .alignment(Alignment::Center)
+                .style(Style::new().add_modifier(Modifier::BOLD));
+                frame.render_widget(word_display, vertical_chunks[1]);
+            }
+
+            if app.caps_warning() {
+                let warning = Paragraph::new("Caps Lock? Letters are coming out in the wrong case")
+                    .alignment(Alignment::Center)
+                    .style(Style::new().fg(theme.wrong).add_modifier(Modifier::BOLD));
+                frame.render_widget(warning, vertical_chunks[2]);
+            } else if let Some(governor) = &app.governor
+                && governor.is_warning()
+            {
+                let warning = Paragraph::new(format!(
+                    "Slow down - pace limit {:.0} wpm",
+                    governor.max_wpm
+                ))
+                .alignment(Alignment::Center)
+                .style(Style::new().fg(theme.wrong));
+                frame.render_widget(warning, vertical_chunks[2]);
+            } else if app.clock.is_started() && sidebar.is_none() {
+                let (net, raw) = app.live_speeds();
+                let accuracy = app.live_accuracy();
+                let speeds = Paragraph::new(Line::from(vec![
+                    Span::raw(format!("{:.0} wpm | {:.0} raw | ", net, raw)),
+                    Span::styled(
+                        format!("{:.1}%", accuracy),
+                        Style::new().fg(app.accuracy_color(accuracy)),
+                    ),
+                ]))
+                .alignment(Alignment::Center)
+                .style(Style::new().fg(theme.dim));
+                frame.render_widget(speeds, vertical_chunks[2]);
+            }
+
+            let mut styled_input = stylize(app.input.as_str(), &app.wrong_input_chars, &theme);
+            // The rest of the word ahead of the caret, so the eye can stay on the input.
+            let ghost = graphemes::skip(&app.current_word, graphemes::count(&app.input));
+            styled_input.push(Span::styled(
+                visible(&veil(ghost, 0, app.reveal)),
+                Style::new().fg(theme.dim),
+            ));
+            for (mark, style) in [(app.pace_mark(), PACE_MARK), (app.ghost_mark(), GHOST_MARK)] {
+                if let Some((word, ch)) = mark
+                    && word == app.words.len()
+                {
+                    styled_input = mark_at(styled_input, ch, style);
+                }
+            }
+            let mut input_block = theme
+                .block()
+                .borders(layout.borders())
+                .border_style(app.pulse_style())
+                .title(ternary!(
+                    !is_paused,
+                    match &app.session {
+                        Some(session) => format!("{} - {}", session.title(), app.counter()),
+                        None if app.is_cycling() =>
+                            format!("{} {}", app.counter(), lang_code(app.current_lang)),
+                        None => app.counter(),
+                    },
+                    "Paused".to_string()
+                ));
+            if let Some(lead) = app.pace_lead() {
+                let pace = match lead {
+                    0 => "on pace".to_string(),
+                    lead if lead > 0 => format!("{} behind pace", lead),
+                    lead => format!("{} ahead of pace", -lead),
+                };
+                input_block = input_block.title_bottom(
+                    Line::styled(format!(" {} ", pace), Style::new().fg(theme.dim)).right_aligned(),
+                );
+            }
+            let input_paragraph = Paragraph::new(Line::from(styled_input))
+                .block(input_block)
+                .alignment(Alignment::Center);
+            frame.render_widget(input_paragraph, vertical_chunks[3]);
+
+            let show_race = !app.race.is_solo() || app.net.is_some();
+            let extra_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(ternary!(!app.clock.is_started(), 3, 1)),
+                    Constraint::Length(ternary!(show_race, app.race.shown() as u16 + 2, 0)),
+                ])
+                .split(vertical_chunks[4]);
+
+            if !app.clock.is_started() {
+                let start_prompt = Paragraph::new("Enter any character to start")
+                    .block(
+                        theme
+                            .block()
+                            .border_type(BorderType::Rounded)
+                            .borders(layout.borders()),
+                    )
+                    .alignment(Alignment::Center);
+                frame.render_widget(start_prompt, extra_chunks[0]);
+            } else if sidebar.is_none() {
+                let errors: Vec<usize> = app.words.iter().map(|w| w.wrong_chars.len()).collect();
+                frame.render_widget(HeatStrip::new(&errors), extra_chunks[0]);
+            }
+
+            if show_race {
+                frame.render_widget(
+                    RaceHud::new(&app.race)
+                        .status(app.net_status())
+                        .theme(theme),
+                    extra_chunks[1],
+                );
+            }
+
+            if let Some(sidebar) = sidebar {
+                // Starting level with the word being typed.
+                let top = vertical_chunks[1].y;
+                let area = Rect {
+                    y: top,
+                    height: sidebar.bottom().saturating_sub(top),
+                    ..sidebar
+                };
+                render_sidebar(frame, app, area);
+            }
+        }
+        AppState::Results(table_state) => {
+            action_bar(&bar, app.pressed, &app.keys, &theme)
+                .render(vertical_chunks[5], frame.buffer_mut());
+
+            let forgiven: usize = app.words.iter().map(|w| w.forgiven).sum();
+            let place = app.race.racers[PLAYER]
+                .place
+                .filter(|_| !app.race.is_solo())
+                .map(|p| format!(", place {}/{}", p, app.race.shown()))
+                .unwrap_or_default()
+                + &ternary!(
+                    forgiven > 0,
+                    format!(", {} rollover typos forgiven", forgiven),
+                    String::new()
+                )
+                + &app
+                    .unsaved
+                    .as_ref()
+                    .map(|reason| format!(" (not saved: {})", reason))
+                    .unwrap_or_default();
+            let result_text = ternary!(
+                app.wrong_words.is_empty(),
+                format!(
+                    "No mistakes, well done! Time elapsed: {}{}",
+                    clock::format_time(time),
+                    place
+                ),
+                format!(
+                    "{} wrong typed words out of {}, Accuracy: {:.2}%, time elapsed: {}{}",
+                    app.wrong_words.len(),
+                    app.words.len(),
+                    accuracy.unwrap(),
+                    clock::format_time(time),
+                    place
+                )
+            );
+
+            let mut summary = vec![Line::from(wpm_summary.unwrap_or_default())];
+            if let Some(label) = new_best {
+                summary.push(Line::styled(
+                    label,
+                    Style::new()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if app.unsaved.is_none() {
+                summary.push(Line::styled(
+                    app.streak.label(calendar::today()),
+                    Style::new().fg(theme.dim),
+                ));
+            }
+            let summary_paragraph = Paragraph::new(summary).alignment(Alignment::Center);
+            frame.render_widget(summary_paragraph, vertical_chunks[1]);
+
+            let metrics_line = Paragraph::new(metrics.unwrap_or_default())
+                .alignment(Alignment::Center)
+                .style(Style::new().fg(theme.dim));
+            frame.render_widget(metrics_line, vertical_chunks[2]);
+
+            let mut result_block = theme.block().borders(layout.borders());
+            if let Some(gate) = app.gate {
+                let (status, color) = if gate.passed {
+                    ("Gate open - any key to continue".to_string(), theme.correct)
+                } else {
+                    (format!("Gate needs {}", gate.target()), theme.wrong)
+                };
+                result_block = result_block.title(
+                    Line::styled(format!(" {} ", status), Style::new().fg(color)).centered(),
+                );
+            }
+            if let Some(exported) = &app.exported {
+                let (notice, color) = match exported {
+                    Ok(path) => (format!("Exported to {}", path.display()), theme.correct),
+                    Err(err) => (format!("Export failed: {}", err), theme.wrong),
+                };
+                result_block = result_block.title_bottom(
+                    Line::styled(format!(" {} ", notice), Style::new().fg(color)).centered(),
+                );
+            }
+            if let Some(seed) = &app.seed {
+                result_block = result_block.title_bottom(
+                    Line::styled(
+                        format!(" seed {} ", words::seed_phrase(seed)),
+                        Style::new().fg(theme.dim),
+                    )
+                    .left_aligned(),
+                );
+            }
+            if let Some(quote) = app.quote {
+                result_block = result_block.title_bottom(
+                    Line::styled(format!(" - {} ", quote.author), Style::new().fg(theme.dim))
+                        .right_aligned(),
+                );
+            }
+            if let Some(snippet) = app.snippet {
+                result_block = result_block.title_bottom(
+                    Line::styled(
+                        format!(" {}: {} ", app.code_lang.label(), snippet.title),
+                        Style::new().fg(theme.dim),
+                    )
+                    .right_aligned(),
+                );
+            }
+            let result_paragraph = Paragraph::new(Line::from(result_text))
+                .block(result_block)
+                .alignment(Alignment::Center);
+            frame.render_widget(result_paragraph, vertical_chunks[3]);
+
+            let show_definitions =
+                app.word_gen.mode == WordMode::Rare && layout != LayoutMode::Compact;
+            let (sort_column, descending) = app.results_sort;
+            let mut header: Vec<String> = SortColumn::ALL
+                .iter()
+                .map(|&column| {
+                    let arrow = match (column == sort_column, descending) {
+                        (true, true) => " v",
+                        (true, false) => " ^",
+                        (false, _) => "",
+                    };
+                    format!("{}{}", column.title(), arrow)
+                })
+                .collect();
+            if show_definitions {
+                header.push("definition".to_string());
+            }
+
+            let rows: Vec<Row> = word_rows
+                .unwrap_or_default()
+                .iter()
+                .map(|r| {
+                    let w = &app.words[r.index];
+                    let word = if !app.wrong_words.contains(&r.index) {
+                        Line::styled(
+                            w.word.trim_end_matches('\n'),
+                            Style::new().fg(theme.correct),
+                        )
+                    } else {
+                        Line::from(stylize(
+                            w.word.trim_end_matches('\n'),
+                            &w.wrong_chars,
+                            &theme,
+                        ))
+                    };
+                    let mut cells = vec![
+                        Cell::from((r.index + 1).to_string()),
+                        Cell::from(word),
+                        Cell::from(format!("{:.2}s", r.time.as_secs_f32())),
+                        Cell::from(format!("{:.0}", r.wpm)),
+                        Cell::from(r.errors.to_string()),
+                    ];
+                    if show_definitions {
+                        cells.push(Cell::from(Span::styled(
+                            words::definition(&w.word).unwrap_or_default(),
+                            Style::new().fg(theme.dim),
+                        )));
+                    }
+                    Row::new(cells)
+                })
+                .collect();
+
+            let mut widths = vec![
+                Constraint::Length(4),
+                Constraint::Min(12),
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(7),
+            ];
+            if show_definitions {
+                widths.push(Constraint::Fill(2));
+            }
+
+            let table = Table::new(rows, widths)
+                .header(Row::new(header).style(Style::new().add_modifier(Modifier::BOLD)))
+                .block(
+                    theme
+                        .block()
+                        .borders(layout.borders())
+                        .title("Results")
+                        .border_type(BorderType::Rounded),
+                )
+                .highlight_symbol("> ")
+                .row_highlight_style(
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            // A chart needs a few seconds to show any progression.
+            let table_area = if app.timeline.len() >= 2 && layout != LayoutMode::Compact {
+                let [table_area, chart_area] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Percentage(45)])
+                        .areas(vertical_chunks[0]);
+                frame.render_widget(
+                    WpmChart::new(&app.timeline, chart_end.unwrap_or_default(), theme),
+                    chart_area,
+                );
+                table_area
+            } else {
+                vertical_chunks[0]
+            };
+            frame.render_stateful_widget(table, table_area, &mut table_state.to_owned());
+
+            if app.ngrams.is_some() {
+                render_ngram_accuracy(frame, app, vertical_chunks[4], layout);
+            } else if app.is_cycling() {
+                let languages: Vec<Line> = app
+                    .language_stats()
+                    .iter()
+                    .map(|(lang, words, wpm, accuracy)| {
+                        Line::from(format!(
+                            "{}: {} words, {:.0} wpm, {:.2}%",
+                            lang_code(*lang),
+                            words,
+                            wpm,
+                            accuracy
+                        ))
+                    })
+                    .collect();
+                let languages_paragraph = Paragraph::new(languages)
+                    .block(
+                        theme
+                            .block()
+                            .borders(layout.borders())
+                            .title("Languages")
+                            .border_type(BorderType::Rounded),
+                    )
+                    .alignment(Alignment::Center);
+                frame.render_widget(languages_paragraph, vertical_chunks[4]);
+            } else if matches!(app.word_gen.mode, WordMode::Curve | WordMode::Adaptive) {
+                let segments: Vec<Line> = app
+                    .segment_stats(CURVE_SEGMENTS)
+                    .iter()
+                    .map(|s| {
+                        Line::from(format!(
+                            "words {}-{}: avg length {:.1}, {:.0} wpm, {:.2}%",
+                            s.words.start + 1,
+                            s.words.end,
+                            s.avg_len,
+                            s.wpm,
+                            s.accuracy
+                        ))
+                    })
+                    .collect();
+                let segments_paragraph = Paragraph::new(segments)
+                    .block(
+                        theme
+                            .block()
+                            .borders(layout.borders())
+                            .title("Segments")
+                            .border_type(BorderType::Rounded),
+                    )
+                    .alignment(Alignment::Center);
+                frame.render_widget(segments_paragraph, vertical_chunks[4]);
+            } else {
+                render_key_errors(frame, app, vertical_chunks[4], layout);
+            }
+        }
+        AppState::Menu
+        | AppState::Preview
+        | AppState::Plan(..)
+        | AppState::Report
+        | AppState::Zen(_)
+        | AppState::ZenResults(_)
+        | AppState::Layouts(_)
+        | AppState::History(..)
+        | AppState::Rivals(..)
+        | AppState::Replay(..)
+        | AppState::Lobby
+        | AppState::Stats(_)
+        | AppState::Practice(_) => {}
+    }
+
+    if matches!(app.app_state, AppState::Pause) {
+        render_pause(frame, app);
+    }
+    if is_settings {
+        render_settings(frame, app);
+    }
+}
+
+/// Popup over a paused test with where it stands so far.
+fn render_pause(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let label = Style::new().fg(theme.dim);
+    let (net, raw) = app.live_speeds();
+    let accuracy = app.live_accuracy();
+    let words = ternary!(
+        app.is_timed(),
+        app.words.len().to_string(),
+        format!("{}/{}", app.words.len(), app.limit())
+    );
+    let lines = vec![
+        Line::from(vec![
+            Span::styled("Elapsed   ", label),
+            Span::raw(clock::format_time(app.elapsed())),
+        ]),
+        Line::from(vec![Span::styled("Words     ", label), Span::raw(words)]),
+        Line::from(vec![
+            Span::styled("Accuracy  ", label),
+            Span::styled(
+                format!("{:.1}%", accuracy),
+                Style::new().fg(app.accuracy_color(accuracy)),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Speed     ", label),
+            Span::raw(format!("{:.0} wpm, {:.0} raw", net, raw)),
+        ]),
+        Line::default(),
+        Line::styled("Any key to resume", label).centered(),
+    ];
+
+    let area = frame.area();
+    let [popup_area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup_area] = Layout::horizontal([Constraint::Length(34)])
+        .flex(Flex::Center)
+        .areas(popup_area);
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            theme
+                .block()
+                .title("Paused")
+                .border_type(BorderType::Rounded)
+                .padding(Padding::horizontal(2)),
+        ),
+        popup_area,
+    );
+}
+
+/// Live stats of the running test and a heat strip of its words, shown
+/// beside it on wide terminals.
+fn render_sidebar(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let label = Style::new().fg(theme.dim);
+    let lines = if app.clock.is_started() {
+        let (net, raw) = app.live_speeds();
+        let accuracy = app.live_accuracy();
+        vec![
+            Line::from(vec![
+                Span::styled("wpm      ", label),
+                Span::raw(format!("{:.0}", net)),
+            ]),
+            Line::from(vec![
+                Span::styled("raw      ", label),
+                Span::raw(format!("{:.0}", raw)),
+            ]),
+            Line::from(vec![
+                Span::styled("accuracy ", label),
+                Span::styled(
+                    format!("{:.1}%", accuracy),
+                    Style::new().fg(app.accuracy_color(accuracy)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("streak   ", label),
+                Span::raw(app.streak().to_string()),
+            ]),
+            Line::from(vec![
+                Span::styled("time     ", label),
+                Span::raw(format!("{:.0}s", app.elapsed().as_secs_f32())),
+            ]),
+            Line::from(vec![
+                Span::styled("words    ", label),
+                Span::raw(app.words.len().to_string()),
+            ]),
+        ]
+    } else {
+        vec![Line::styled("Waiting for the first key", label)]
+    };
+    let strip_rows = ternary!(app.words.is_empty(), 0, SIDEBAR_STRIP_ROWS + 1);
+    let height = (lines.len() as u16 + strip_rows + 2).min(area.height);
+    let block = theme
+        .block()
+        .title("Live")
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1));
+    let area = Rect { height, ..area };
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [stats_area, strip_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(strip_rows)]).areas(inner);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), stats_area);
+    if strip_rows > 0 {
+        let errors: Vec<usize> = app.words.iter().map(|w| w.wrong_chars.len()).collect();
+        // A blank row between the stats and the strip.
+        let strip_area = Rect {
+            y: strip_area.y + 1,
+            height: strip_area.height.saturating_sub(1),
+            ..strip_area
+        };
+        frame.render_widget(HeatStrip::new(&errors), strip_area);
+    }
+}
+
+/// Accuracy of each n-gram of a finished drill, least accurate first.
+fn render_ngram_accuracy(frame: &mut Frame, app: &App, area: Rect, layout: LayoutMode) {
+    let theme = app.theme;
+    let mut spans = Vec::new();
+    for (i, gram) in app.ngram_accuracy().iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(", "));
+        }
+        let accuracy = gram.percent();
+        spans.push(Span::styled(
+            format!("{} {:.0}%", gram.gram, accuracy),
+            Style::new().fg(app.accuracy_color(accuracy)),
+        ));
+    }
+    let paragraph = Paragraph::new(Line::from(spans))
+        .block(
+            theme
+                .block()
+                .borders(layout.borders())
+                .title("N-gram accuracy")
+                .border_type(BorderType::Rounded),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+/// Keys mistyped in the finished test, colored by how many mistakes each
+/// had next to the worst one, and their error rates over every saved run.
+fn render_key_errors(frame: &mut Frame, app: &App, area: Rect, layout: LayoutMode) {
+    let theme = app.theme;
+    let worst = app.worst_keys();
+    let Some(&(_, most)) = worst.first() else {
+        return;
+    };
+    let mut run = Vec::new();
+    for (i, &(ch, count)) in worst.iter().take(WORST_KEYS).enumerate() {
+        if i > 0 {
+            run.push(Span::raw(", "));
+        }
+        let color = if count * 3 >= most * 2 {
+            theme.wrong
+        } else if count * 3 >= most {
+            Color::Yellow
+        } else {
+            Color::Reset
+        };
+        run.push(Span::styled(
+            format!(
+                "{}: {} {}",
+                key_label(ch),
+                count,
+                ternary!(count == 1, "error", "errors")
+            ),
+            Style::new().fg(color),
+        ));
+    }
+    let mut lines = vec![Line::from(run)];
+
+    let mut rates: Vec<(char, f32)> = app
+        .key_stats
+        .errors
+        .keys()
+        .map(|&ch| (ch, app.key_stats.error_rate(ch)))
+        .collect();
+    rates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if !rates.is_empty() {
+        let all_time: Vec<String> = rates
+            .iter()
+            .take(WORST_KEYS)
+            .map(|&(ch, rate)| format!("{} {:.1}%", key_label(ch), rate))
+            .collect();
+        lines.push(Line::styled(
+            format!("All runs: {}", all_time.join(", ")),
+            Style::new().fg(theme.dim),
+        ));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            theme
+                .block()
+                .borders(layout.borders())
+                .title("Mistyped keys")
+                .border_type(BorderType::Rounded),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+/// Actions of the results screen with their keys. The one pressed last
+/// flashes briefly.
+fn action_bar(
+    actions: &[ResultAction],
+    pressed: Option<(ResultAction, Instant)>,
+    keys: &Keymap,
+    theme: &Theme,
+) -> Line<'static> {
+    let flashing = pressed
+        .filter(|(_, at)| at.elapsed() < PRESS_FLASH)
+        .map(|(action, _)| action);
+    let mut spans = Vec::new();
+    for (i, &action) in actions.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" | ", Style::new().fg(theme.dim)));
+        }
+        let style = ternary!(
+            flashing == Some(action),
+            Style::new()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::REVERSED),
+            Style::new()
+        );
+        spans.push(Span::styled(
+            format!("{} {}", action.key(keys), action.name()),
+            style,
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Banner of a run that beat a personal best, with the bests it beat.
+fn new_best_label(best: NewBest, wpm: f32, accuracy: f32) -> String {
+    let wpm = best
+        .wpm
+        .map(|was| format!("{:.0} wpm (was {:.0})", wpm, was));
+    let accuracy = best
+        .accuracy
+        .map(|was| format!("{:.2}% accuracy (was {:.2}%)", accuracy, was));
+    let beaten: Vec<String> = wpm.into_iter().chain(accuracy).collect();
+    format!("New personal best! {}", beaten.join(", "))
+}
+
+/// Spaces and other blank keys are named rather than left invisible.
+fn key_label(ch: char) -> String {
+    match ch {
+        ' ' => "space".to_string(),
+        ch if ch.is_whitespace() => format!("{:?}", ch),
+        ch => ch.to_string(),
+    }
+}
+
+/// The current line in sentence mode: typed words colored by mistakes, the
+/// caret under the next character, and the rest of the line dimmed.
+pub(crate) fn sentence_line(app: &App) -> Paragraph<'_> {
+    let theme = app.theme;
+    let (done, upcoming) = app.line();
+    let mut spans: Vec<Span> = Vec::new();
+    for word in done {
+        spans.extend(stylize(&word.word, &word.wrong_chars, &theme));
+        spans.push(Span::raw(" "));
+    }
+
+    let typed = graphemes::count(&app.input);
+    spans.extend(stylize(&app.input, &app.wrong_input_chars, &theme));
+    let rest = graphemes::skip(&app.current_word, typed);
+    let caret = graphemes::nth(rest, 0).unwrap_or(" ");
+    spans.push(Span::styled(
+        caret,
+        Style::new().add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+    ));
+    spans.push(Span::styled(
+        veil(graphemes::skip(rest, 1), 1, app.reveal),
+        Style::new().add_modifier(Modifier::BOLD),
+    ));
+
+    // Characters past the caret, spaces included, for shadow typing.
+    let mut ahead = graphemes::count(rest).max(1);
+    for word in &upcoming {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            veil(word, ahead + 1, app.reveal),
+            Style::new().fg(theme.dim),
+        ));
+        ahead += graphemes::count(word) + 1;
+    }
+
+    // Words of the line in order, from the first one shown.
+    let first = app.words.len() - done.len();
+    let lengths: Vec<usize> = done
+        .iter()
+        .map(|w| w.word.as_str())
+        .chain(iter::once(app.current_word.as_str()))
+        .chain(upcoming.iter().copied())
+        .map(|w| graphemes::count(w) + 1)
+        .collect();
+    for (mark, style) in [(app.pace_mark(), PACE_MARK), (app.ghost_mark(), GHOST_MARK)] {
+        if let Some((word, ch)) = mark
+            && (first..first + lengths.len()).contains(&word)
+        {
+            let at: usize = lengths[..word - first].iter().sum::<usize>() + ch;
+            spans = mark_at(spans, at, style);
+        }
+    }
+    Paragraph::new(Line::from(spans))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+}
+
+/// The snippet in code mode: typed lines colored by mistakes, the current
+/// line with the caret and the lines still to come, scrolled to keep the
+/// current one in view.
+fn render_code(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let mut lines: Vec<Line> = app
+        .words
+        .iter()
+        .map(|word| Line::from(stylize(&word.word, &word.wrong_chars, &theme)))
+        .collect();
+    let current = lines.len();
+
+    let typed = graphemes::count(&app.input);
+    let rest = graphemes::skip(&app.current_word, typed);
+    let mut spans = stylize(&app.input, &app.wrong_input_chars, &theme);
+    spans.push(Span::styled(
+        visible(graphemes::nth(rest, 0).unwrap_or(" ")),
+        Style::new().add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+    ));
+    spans.push(Span::styled(
+        visible(graphemes::skip(rest, 1)),
+        Style::new().add_modifier(Modifier::BOLD),
+    ));
+    lines.push(Line::from(spans));
+    lines.extend(
+        app.target
+            .iter()
+            .map(|line| Line::styled(line.trim_end_matches('\n'), Style::new().fg(theme.dim))),
+    );
+
+    // The current line stays a third of the way down once the snippet
+    // outgrows the area.
+    let top = current.saturating_sub(area.height as usize / 3);
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Paragraph::new(lines).scroll((top as u16, 0)), area);
+}
+
+/// Line breaks and tabs of code as symbols, since terminals don't draw them.
+fn visible(text: &str) -> String {
+    text.replace('\n', "⏎").replace('\t', "→")
+}
+
+/// Shadow typing: `text` starts `ahead` characters past the caret, and
+/// characters `reveal` or more past it are veiled. Nothing is veiled when
+/// `reveal` is 0.
+fn veil(text: &str, ahead: usize, reveal: u32) -> String {
+    if reveal == 0 {
+        return text.to_string();
+    }
+    text.graphemes(true)
+        .enumerate()
+        .map(|(i, g)| ternary!(ahead + i < reveal as usize, g, "·"))
+        .collect()
+}
+
+/// Where typing at the pace target would be.
+const PACE_MARK: Style = Style::new().add_modifier(Modifier::REVERSED);
+/// Where the ghost is, in the color of its bar in the race.
+const GHOST_MARK: Style = Style::new()
+    .fg(Color::Yellow)
+    .add_modifier(Modifier::REVERSED);
+
+/// Applies `style` to the grapheme at `at` of the spans' text.
+fn mark_at<'a>(spans: Vec<Span<'a>>, at: usize, style: Style) -> Vec<Span<'a>> {
+    let mut marked = Vec::with_capacity(spans.len() + 2);
+    let mut start = 0;
+    for span in spans {
+        let len = graphemes::count(&span.content);
+        if !(start..start + len).contains(&at) {
+            start += len;
+            marked.push(span);
+            continue;
+        }
+        let chars: Vec<&str> = span.content.graphemes(true).collect();
+        let i = at - start;
+        marked.push(Span::styled(chars[..i].concat(), span.style));
+        marked.push(Span::styled(chars[i].to_string(), span.style.patch(style)));
+        marked.push(Span::styled(chars[i + 1..].concat(), span.style));
+        start += len;
+    }
+    marked
+}
+
+pub(crate) fn stylize<'a>(
+    word: &str,
+    wrong_chars: &HashSet<usize>,
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    word.graphemes(true)
+        .enumerate()
+        .map(|(i, grapheme)| {
+            let style = if wrong_chars.contains(&i) {
+                Style::new().fg(theme.wrong)
+            } else {
+                Style::new().fg(theme.correct)
+            };
+            Span::styled(visible(grapheme), style)
+        })
+        .collect()
+}
@@ -0,0 +1,663 @@
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use once_cell::sync::Lazy;
+use rand::{
+    Rng, SeedableRng,
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+};
+use random_word::Lang;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocklist::Blocklist,
+    cache::{Source, SourceCache, Words},
+    calendar,
+    filter::WordFilter,
+    packs,
+};
+
+/// Share of the ranked pool sampled around the target difficulty.
+const CURVE_WINDOW: f32 = 0.05;
+/// Share of the hardest ranked words sampled by `WordMode::Rare` when no
+/// bundled list exists for the language.
+const RARE_TAIL: f32 = 0.1;
+/// Draws tried before picking from the pool of words the blocklist and the
+/// filter allow.
+const MAX_DRAWS: usize = 100;
+/// Adaptive difficulty of a new session, as a share of the ranked dictionary.
+const ADAPT_START: f32 = 0.3;
+/// Difficulty gained per clean word, multiplied by the clean streak up to
+/// [`ADAPT_MAX_STREAK`].
+const ADAPT_STEP: f32 = 0.01;
+const ADAPT_MAX_STREAK: u32 = 5;
+/// Difficulty lost on a mistyped word.
+const ADAPT_DROP: f32 = 0.1;
+/// How much more often a word comes up in weak mode for each weak key it
+/// has, scaled by how often that key is missed next to the weakest one.
+const WEAK_WEIGHT: f32 = 10.0;
+/// Seed standing for the words of the day.
+const DAILY_SEED: &str = "daily";
+
+/// Bundled `word<TAB>definition` pairs used for English vocabulary practice.
+static HARD_WORDS_EN: Lazy<Vec<(&'static str, &'static str)>> = Lazy::new(|| {
+    include_str!("../assets/hard_words_en.tsv")
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect()
+});
+
+pub fn definition(word: &str) -> Option<&'static str> {
+    HARD_WORDS_EN
+        .iter()
+        .find(|(w, _)| *w == word)
+        .map(|(_, definition)| *definition)
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WordMode {
+    #[default]
+    Random,
+    /// Words get harder as the test progresses.
+    Curve,
+    /// Low-frequency vocabulary, with definitions shown in Results.
+    Rare,
+    /// Words get harder after clean ones and easier after mistakes.
+    Adaptive,
+    /// Words with the most-missed keys of every saved run come up more often.
+    Weak,
+}
+
+impl WordMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            WordMode::Random => "random",
+            WordMode::Curve => "curve",
+            WordMode::Rare => "rare",
+            WordMode::Adaptive => "adaptive",
+            WordMode::Weak => "weak",
+        }
+    }
+}
+
+/// Characters joining parts of a word, as in "don't" or "well-known".
+const JOINERS: [char; 3] = ['-', '\'', '’'];
+
+/// How words containing hyphens or apostrophes are typed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Joiners {
+    /// Typed as they are.
+    #[default]
+    Keep,
+    /// Typed without the joining characters: "dont", "wellknown".
+    Strip,
+    /// Every part is a word of its own: "well", "known".
+    Split,
+}
+
+impl Joiners {
+    /// The words to type in place of `word`, never empty.
+    pub fn apply(self, word: &str) -> Vec<String> {
+        let words: Vec<String> = match self {
+            Joiners::Keep => vec![word.to_string()],
+            Joiners::Strip => vec![word.replace(JOINERS, "")],
+            Joiners::Split => word.split(JOINERS).map(str::to_string).collect(),
+        };
+        let words: Vec<String> = words.into_iter().filter(|w| !w.is_empty()).collect();
+        if words.is_empty() {
+            vec![word.to_string()]
+        } else {
+            words
+        }
+    }
+}
+
+/// Constraint dropped because no word met it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relaxed {
+    /// The charset and pattern filter.
+    Filter,
+    Blocklist,
+    /// The word list or pack, which is empty.
+    Source,
+}
+
+impl Relaxed {
+    pub fn notice(self) -> &'static str {
+        match self {
+            Relaxed::Filter => {
+                "No words match the charset, pattern and lengths, showing unfiltered words"
+            }
+            Relaxed::Blocklist => "The blocklist blocks every word, showing blocked words",
+            Relaxed::Source => "The word list is empty, showing dictionary words",
+        }
+    }
+}
+
+/// Words of one language that may be drawn, see [`WordGen::pool`].
+struct Allowed {
+    lang: Lang,
+    words: Vec<&'static str>,
+    /// Set when nothing met every constraint and `words` meet fewer.
+    relaxed: Option<Relaxed>,
+}
+
+/// Draw weights of a language's words in weak mode.
+struct WeakPool {
+    lang: Lang,
+    words: Vec<&'static str>,
+    weights: WeightedIndex<f32>,
+}
+
+/// Picks the next word to type according to the active `WordMode`.
+pub struct WordGen {
+    pub mode: WordMode,
+    /// Exponent applied to test progress: 1.0 is linear, higher values keep
+    /// the start easy for longer and ramp up late.
+    pub curve: f32,
+    /// Where adaptive mode draws from the ranked dictionary, 0.0 being the
+    /// easiest words and 1.0 the hardest.
+    pub level: f32,
+    /// Words typed cleanly in a row, speeding up the climb of `level`.
+    streak: u32,
+    ranked: SourceCache,
+    /// Installed word pack replacing the built-in dictionary.
+    pack: Option<(String, Words)>,
+    /// Word file given in the config or on the command line, taking
+    /// precedence over packs.
+    wordlist: Option<(String, Words)>,
+    blocklist: Blocklist,
+    filter: WordFilter,
+    /// Allowed words of the last language drawn from, kept for narrow filters
+    /// that random draws rarely meet.
+    allowed: Option<Allowed>,
+    /// Constraint the last word was drawn without.
+    pub relaxed: Option<Relaxed>,
+    /// Most-missed keys with their error rates, weakest first, that weak
+    /// mode favors words with.
+    weak_keys: Vec<(char, f32)>,
+    /// Weights of the last language drawn from in weak mode, kept until the
+    /// keys or the words change.
+    weak_pool: Option<WeakPool>,
+    rng: StdRng,
+    /// Whether words follow a seed, so ranked pools are waited for instead
+    /// of filled in with random words that would differ between runs.
+    seeded: bool,
+}
+
+impl WordGen {
+    pub fn new(mode: WordMode, curve: f32) -> Self {
+        Self {
+            mode,
+            curve: if curve > 0.0 { curve } else { 1.0 },
+            level: ADAPT_START,
+            streak: 0,
+            ranked: SourceCache::default(),
+            pack: None,
+            wordlist: None,
+            blocklist: Blocklist::default(),
+            filter: WordFilter::default(),
+            allowed: None,
+            relaxed: None,
+            weak_keys: Vec::new(),
+            weak_pool: None,
+            rng: StdRng::from_entropy(),
+            seeded: false,
+        }
+    }
+
+    /// Starts drawing the words picked by `seed` over again, or random ones
+    /// without a seed.
+    pub fn reseed(&mut self, seed: Option<&str>) {
+        self.seeded = seed.is_some();
+        self.rng = seed.map_or_else(StdRng::from_entropy, |seed| {
+            StdRng::seed_from_u64(phrase_seed(&seed_phrase(seed)))
+        });
+    }
+
+    /// Moves the adaptive difficulty after a completed word: up with every
+    /// clean word, faster along a streak, and down sharply on a mistake.
+    pub fn adapt(&mut self, clean: bool) {
+        if clean {
+            self.streak += 1;
+            self.level += ADAPT_STEP * self.streak.min(ADAPT_MAX_STREAK) as f32;
+        } else {
+            self.streak = 0;
+            self.level -= ADAPT_DROP;
+        }
+        self.level = self.level.clamp(0.0, 1.0);
+    }
+
+    /// Random numbers of the test, seeded along with the words.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    pub fn set_pack(&mut self, code: Option<&str>) {
+        self.pack = code.and_then(|code| Some((code.to_string(), packs::load(code)?)));
+        self.allowed = None;
+        self.weak_pool = None;
+    }
+
+    pub fn set_wordlist(&mut self, wordlist: Option<(String, Words)>) {
+        self.wordlist = wordlist;
+        self.allowed = None;
+        self.weak_pool = None;
+    }
+
+    pub fn set_weak_keys(&mut self, keys: Vec<(char, f32)>) {
+        if keys != self.weak_keys {
+            self.weak_keys = keys;
+            self.weak_pool = None;
+        }
+    }
+
+    pub fn weak_keys(&self) -> &[(char, f32)] {
+        &self.weak_keys
+    }
+
+    pub fn set_blocklist(&mut self, blocklist: Blocklist) {
+        self.blocklist = blocklist;
+        self.allowed = None;
+    }
+
+    pub fn set_filter(&mut self, filter: WordFilter) {
+        self.filter = filter;
+        self.allowed = None;
+    }
+
+    /// Keeps words to `min` to `max` characters, 0 leaving that end open.
+    pub fn set_lengths(&mut self, min: usize, max: usize) {
+        self.filter = std::mem::take(&mut self.filter).with_lengths(min, max);
+        self.allowed = None;
+    }
+
+    pub fn lengths(&self) -> (usize, usize) {
+        self.filter.lengths()
+    }
+
+    pub fn blocks(&self, word: &str) -> bool {
+        self.blocklist.blocks(word)
+    }
+
+    /// Whether `word` may be drawn: not blocked and meeting the filter.
+    fn allows(&self, word: &str) -> bool {
+        !self.blocks(word) && self.filter.allows(word)
+    }
+
+    /// Custom words replacing the language's dictionary, with their cache name.
+    fn custom(&self) -> Option<&(String, Words)> {
+        self.wordlist.as_ref().or(self.pack.as_ref())
+    }
+
+    /// Every word the generator draws from for `lang`, blocked and filtered
+    /// out ones left out.
+    pub fn pool(&self, lang: Lang) -> Vec<&'static str> {
+        let words = match self.custom() {
+            Some((_, words)) => words,
+            None => random_word::all(lang),
+        };
+        words
+            .iter()
+            .copied()
+            .filter(|word| self.allows(word))
+            .collect()
+    }
+
+    fn random(&mut self, lang: Lang) -> &'static str {
+        let words = match self.custom() {
+            Some((_, words)) => *words,
+            None => random_word::all(lang),
+        };
+        words.choose(&mut self.rng).copied().unwrap_or_default()
+    }
+
+    /// `progress` is the completed share of the test, from 0.0 to 1.0.
+    /// Never empty: when nothing meets the constraints, the narrowest ones are
+    /// dropped and [`relaxed`](Self::relaxed) says which.
+    pub fn next(&mut self, lang: Lang, progress: f32) -> &'static str {
+        // Random words under a filter come straight from the filtered pool,
+        // computed once instead of checking draw after draw.
+        if self.mode == WordMode::Random && !self.filter.is_empty() {
+            return self.draw_allowed(lang);
+        }
+        for _ in 0..MAX_DRAWS {
+            let word = self.draw(lang, progress);
+            if !word.is_empty() && self.allows(word) {
+                self.relaxed = None;
+                return word;
+            }
+        }
+        // Broad blocklist patterns and narrow filters leave little of the pool.
+        self.draw_allowed(lang)
+    }
+
+    /// A word of [`relaxed_pool`](Self::relaxed_pool), cached per language.
+    fn draw_allowed(&mut self, lang: Lang) -> &'static str {
+        if self
+            .allowed
+            .as_ref()
+            .is_none_or(|allowed| allowed.lang != lang)
+        {
+            self.allowed = Some(self.relaxed_pool(lang));
+        }
+        let allowed = self
+            .allowed
+            .as_ref()
+            .expect("allowed words were just cached");
+        self.relaxed = allowed.relaxed;
+        allowed
+            .words
+            .choose(&mut self.rng)
+            .copied()
+            .unwrap_or_else(|| any(lang, &mut self.rng))
+    }
+
+    /// [`pool`](Self::pool), or when it's empty, the words left after dropping
+    /// the filter, then the blocklist, then the custom words.
+    fn relaxed_pool(&self, lang: Lang) -> Allowed {
+        let source = match self.custom() {
+            Some((_, words)) => *words,
+            None => random_word::all(lang),
+        };
+        let source: Vec<&'static str> = source.iter().copied().filter(|w| !w.is_empty()).collect();
+        let unblocked = || source.iter().copied().filter(|w| !self.blocks(w)).collect();
+        let (words, relaxed) = [
+            (self.pool(lang), None),
+            (unblocked(), Some(Relaxed::Filter)),
+            (source.clone(), Some(Relaxed::Blocklist)),
+            (random_word::all(lang).to_vec(), Some(Relaxed::Source)),
+        ]
+        .into_iter()
+        .find(|(words, _)| !words.is_empty())
+        .unwrap_or_default();
+        Allowed {
+            lang,
+            words,
+            relaxed,
+        }
+    }
+
+    fn draw(&mut self, lang: Lang, progress: f32) -> &'static str {
+        match self.mode {
+            WordMode::Random => self.random(lang),
+            WordMode::Curve => {
+                let target = progress.clamp(0.0, 1.0).powf(self.curve);
+                self.ranked_near(lang, target)
+            }
+            WordMode::Adaptive => self.ranked_near(lang, self.level),
+            WordMode::Weak => self.weighted(lang),
+            WordMode::Rare => {
+                if lang == Lang::En && self.custom().is_none() {
+                    return HARD_WORDS_EN
+                        .choose(&mut self.rng)
+                        .map(|(word, _)| *word)
+                        .unwrap_or_else(|| any(lang, &mut self.rng));
+                }
+                let Some(pool) = self.ranked(lang) else {
+                    return self.random(lang);
+                };
+                let tail = ((pool.len() as f32 * RARE_TAIL) as usize).max(1);
+                pool[self.rng.gen_range(pool.len() - tail..pool.len())]
+            }
+        }
+    }
+
+    /// A word drawn with the weights of [`weak_pool`](Self::weak_pool), or a
+    /// random one before any key has been missed.
+    fn weighted(&mut self, lang: Lang) -> &'static str {
+        if self.weak_keys.is_empty() {
+            return self.random(lang);
+        }
+        if self.weak_pool.as_ref().is_none_or(|pool| pool.lang != lang) {
+            self.weak_pool = self.weak_pool(lang);
+        }
+        match &self.weak_pool {
+            Some(pool) => pool.words[pool.weights.sample(&mut self.rng)],
+            None => self.random(lang),
+        }
+    }
+
+    /// Every word of `lang` weighted up by the weak keys it has.
+    fn weak_pool(&self, lang: Lang) -> Option<WeakPool> {
+        let words: Vec<&'static str> = match self.custom() {
+            Some((_, words)) => words.to_vec(),
+            None => random_word::all(lang).to_vec(),
+        };
+        let weakest = self.weak_keys.first().map_or(1.0, |&(_, rate)| rate);
+        let weights = words.iter().map(|word| {
+            let word = word.to_lowercase();
+            1.0 + self
+                .weak_keys
+                .iter()
+                .filter(|&&(ch, _)| word.contains(ch))
+                .map(|&(_, rate)| WEAK_WEIGHT * rate / weakest)
+                .sum::<f32>()
+        });
+        let weights = WeightedIndex::new(weights).ok()?;
+        Some(WeakPool {
+            lang,
+            words,
+            weights,
+        })
+    }
+
+    /// A word around `target` of the way from the easiest word to the hardest.
+    fn ranked_near(&mut self, lang: Lang, target: f32) -> &'static str {
+        // Ranking runs in the background; plain words fill in until it's
+        // ready, except in seeded tests.
+        let Some(pool) = self.ranked(lang) else {
+            return self.random(lang);
+        };
+        let window = ((pool.len() as f32 * CURVE_WINDOW) as usize).max(1);
+        let center = (target * (pool.len() - 1) as f32) as usize;
+        let from = center.saturating_sub(window / 2).min(pool.len() - window);
+        pool[self.rng.gen_range(from..from + window)]
+    }
+
+    fn ranked(&mut self, lang: Lang) -> Option<Words> {
+        let (name, all) = match self.custom() {
+            Some((name, words)) => (format!("ranked-{}", name), *words),
+            None => (
+                format!("ranked-{:?}", lang).to_lowercase(),
+                random_word::all(lang),
+            ),
+        };
+        let source = Source {
+            name,
+            input_len: all.len(),
+        };
+        let pool = if self.seeded {
+            Some(self.ranked.wait(&source, move || rank(all)))
+        } else {
+            self.ranked.get(&source, move || rank(all))
+        };
+        pool.filter(|pool| !pool.is_empty())
+    }
+}
+
+/// Reads a whitespace-separated word file. Like installed packs, the words
+/// are kept for the whole session.
+pub fn load_wordlist(path: &Path) -> Result<(String, Words), Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read wordlist {}: {}", path.display(), err))?;
+    let words: Vec<&'static str> = Box::leak(text.into_boxed_str())
+        .split_whitespace()
+        .collect();
+    if words.is_empty() {
+        return Err(format!("Wordlist {} contains no words", path.display()).into());
+    }
+
+    // Ranked pools are cached on disk by name, so the name follows the contents.
+    let mut hasher = DefaultHasher::new();
+    words.hash(&mut hasher);
+    let name = format!("wordlist-{:016x}", hasher.finish());
+    Ok((name, Box::leak(words.into_boxed_slice())))
+}
+
+/// Any dictionary word, drawn from `rng` so seeded tests stay reproducible.
+fn any(lang: Lang, rng: &mut StdRng) -> &'static str {
+    random_word::all(lang)
+        .choose(rng)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Phrase a seed stands for: the seed itself, or for "daily" the local date,
+/// e.g. "daily-2026-10-15", so everyone types the same words that day.
+pub fn seed_phrase(seed: &str) -> String {
+    if seed.eq_ignore_ascii_case(DAILY_SEED) {
+        format!("{}-{}", DAILY_SEED, calendar::today())
+    } else {
+        seed.to_string()
+    }
+}
+
+/// Seed of a phrase like "banana42". FNV-1a, so a phrase picks the same
+/// words on every machine and build.
+fn phrase_seed(phrase: &str) -> u64 {
+    phrase.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Dictionary sorted from easiest to hardest word.
+fn rank(all: &'static [&'static str]) -> Vec<&'static str> {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for ch in all.iter().flat_map(|w| w.chars()) {
+        *counts.entry(ch).or_default() += 1;
+    }
+    let total = counts.values().sum::<usize>() as f32;
+
+    let mut scored: Vec<(f32, &'static str)> = all
+        .iter()
+        .map(|w| (difficulty(w, &counts, total), *w))
+        .collect();
+    scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+    scored.into_iter().map(|(_, w)| w).collect()
+}
+
+/// Information content of the word's characters: long words and words made of
+/// rare characters both score higher.
+fn difficulty(word: &str, counts: &HashMap<char, usize>, total: f32) -> f32 {
+    word.chars()
+        .map(|ch| -(counts.get(&ch).copied().unwrap_or(1) as f32 / total).ln())
+        .sum()
+}
+
+/// Rough difficulty of a target text, see `estimate`.
+pub struct TextDifficulty {
+    /// Share of characters that aren't plain lowercase letters or spaces.
+    pub rare: f32,
+    /// Share of punctuation characters.
+    pub punctuation: f32,
+    pub avg_len: f32,
+    /// 1 (easy) to 10 (hard).
+    pub score: u32,
+}
+
+pub fn estimate(words: &[&str]) -> TextDifficulty {
+    let chars: Vec<char> = words.iter().flat_map(|w| w.chars()).collect();
+    let total = chars.len().max(1) as f32;
+    let rare = chars
+        .iter()
+        .filter(|ch| !ch.is_lowercase() || !ch.is_alphabetic())
+        .count() as f32
+        / total;
+    let punctuation = chars.iter().filter(|ch| ch.is_ascii_punctuation()).count() as f32 / total;
+    let avg_len = total / words.len().max(1) as f32;
+
+    // Average word length carries most of the weight; rare characters and
+    // punctuation each add up to a few points.
+    let score = (avg_len / 2.0 + rare * 10.0 + punctuation * 15.0).round() as u32;
+    TextDifficulty {
+        rare,
+        punctuation,
+        avg_len,
+        score: score.clamp(1, 10),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_puts_rare_characters_last() {
+        let ranked = rank(&["zzq", "aa", "a", "ab"]);
+        assert_eq!(ranked.first(), Some(&"a"));
+        assert_eq!(ranked.last(), Some(&"zzq"));
+    }
+
+    #[test]
+    fn estimate_scores_long_punctuated_text_higher() {
+        let easy = estimate(&["the", "cat", "sat"]);
+        let hard = estimate(&["Extraordinary,", "Juxtaposition;", "Onomatopoeia!"]);
+        assert!(easy.score < hard.score);
+        assert_eq!(easy.rare, 0.0);
+        assert_eq!(easy.avg_len, 3.0);
+    }
+
+    const WORDS: Words = &["alpha", "beta", "gamma", "delta", "epsilon", "zeta"];
+
+    fn generator(seed: Option<&str>) -> WordGen {
+        let mut word_gen = WordGen::new(WordMode::Random, 1.0);
+        word_gen.set_wordlist(Some(("test".to_string(), WORDS)));
+        word_gen.reseed(seed);
+        word_gen
+    }
+
+    fn draw(word_gen: &mut WordGen, count: usize) -> Vec<&'static str> {
+        (0..count).map(|_| word_gen.next(Lang::En, 0.0)).collect()
+    }
+
+    #[test]
+    fn seed_repeats_the_words() {
+        let first = draw(&mut generator(Some("banana42")), 20);
+        assert_eq!(draw(&mut generator(Some("banana42")), 20), first);
+        assert_ne!(draw(&mut generator(Some("banana43")), 20), first);
+
+        // Reseeding starts the same words over.
+        let mut word_gen = generator(Some("banana42"));
+        draw(&mut word_gen, 5);
+        word_gen.reseed(Some("banana42"));
+        assert_eq!(draw(&mut word_gen, 20), first);
+    }
+
+    #[test]
+    fn phrase_seed_is_stable() {
+        assert_eq!(phrase_seed(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(phrase_seed("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn words_come_from_the_wordlist() {
+        let mut word_gen = generator(None);
+        assert!(draw(&mut word_gen, 50).iter().all(|w| WORDS.contains(w)));
+        assert_eq!(word_gen.pool(Lang::En), WORDS);
+    }
+
+    #[test]
+    fn daily_seed_follows_the_date() {
+        let today = format!("daily-{}", calendar::today());
+        assert_eq!(seed_phrase("daily"), today);
+        assert_eq!(seed_phrase("Daily"), today);
+        assert_eq!(seed_phrase("banana42"), "banana42");
+    }
+
+    #[test]
+    fn lengths_narrow_the_pool() {
+        let mut word_gen = generator(Some("lengths"));
+        word_gen.set_lengths(5, 5);
+        assert_eq!(word_gen.pool(Lang::En), ["alpha", "gamma", "delta"]);
+        assert!(draw(&mut word_gen, 20).iter().all(|w| w.len() == 5));
+    }
+}
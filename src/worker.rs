@@ -0,0 +1,121 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    drills::Speeds,
+    ghosts::Ghosts,
+    history::{self, Entry},
+    journal::{Journal, JournalEvent},
+    keystats::KeyStats,
+    records::Records,
+    rivals::Rivals,
+    score::Leaderboard,
+    telemetry::Usage,
+    tour,
+};
+
+/// Disk work handed off by the UI thread.
+pub enum Job {
+    /// `JournalEvent::Start` opens a new journal, other events are appended.
+    Journal(JournalEvent),
+    ResumeJournal,
+    /// Syncs the journal to disk, e.g. while the test is paused.
+    SyncJournal,
+    DiscardJournal,
+    History(Entry),
+    KeyStats(KeyStats),
+    Leaderboard(Leaderboard),
+    Speeds(Speeds),
+    Rivals(Rivals),
+    Records(Records),
+    Ghosts(Ghosts),
+    Usage(Usage),
+    TourSeen,
+    /// Acknowledged once every job queued before it is done.
+    Flush(Sender<()>),
+}
+
+/// Runs `Job`s in order on a background thread and reports failures back, so
+/// a slow disk never stalls rendering.
+pub struct IoWorker {
+    jobs: Option<Sender<Job>>,
+    failures: Receiver<String>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Default for IoWorker {
+    fn default() -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let (failure_tx, failure_rx) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let mut journal = Journal::default();
+            while let Ok(job) = job_rx.recv() {
+                if let Err(err) = run(&mut journal, job) {
+                    let _ = failure_tx.send(err.to_string());
+                }
+            }
+        });
+
+        Self {
+            jobs: Some(job_tx),
+            failures: failure_rx,
+            thread: Some(thread),
+        }
+    }
+}
+
+fn run(journal: &mut Journal, job: Job) -> Result<(), Box<dyn std::error::Error>> {
+    match job {
+        Job::Journal(JournalEvent::Start { lang, limit }) => journal.begin(lang, limit),
+        Job::Journal(JournalEvent::Key { ms, ch }) => journal.key(ms, ch),
+        Job::Journal(JournalEvent::Word { ms, word, wrong }) => journal.word(ms, &word, wrong),
+        Job::ResumeJournal => journal.resume(),
+        Job::SyncJournal => journal.sync(),
+        Job::DiscardJournal => journal.discard(),
+        Job::History(entry) => history::append(&entry)?,
+        Job::KeyStats(stats) => stats.save()?,
+        Job::Leaderboard(board) => board.save()?,
+        Job::Speeds(speeds) => speeds.append()?,
+        Job::Rivals(rivals) => rivals.save()?,
+        Job::Records(records) => records.save()?,
+        Job::Ghosts(ghosts) => ghosts.save()?,
+        Job::Usage(usage) => usage.save()?,
+        Job::TourSeen => tour::mark_seen()?,
+        Job::Flush(done) => {
+            let _ = done.send(());
+        }
+    }
+    Ok(())
+}
+
+impl IoWorker {
+    pub fn send(&self, job: Job) {
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+    }
+
+    /// Blocks until the queued jobs are written.
+    pub fn flush(&self) {
+        let (done_tx, done_rx) = mpsc::channel();
+        self.send(Job::Flush(done_tx));
+        let _ = done_rx.recv();
+    }
+
+    /// Errors of jobs finished since the last call.
+    pub fn failures(&self) -> Vec<String> {
+        self.failures.try_iter().collect()
+    }
+}
+
+impl Drop for IoWorker {
+    /// Finishes the queued jobs so nothing is lost on exit.
+    fn drop(&mut self) {
+        self.jobs.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
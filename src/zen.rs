@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use crate::clock::wpm;
+
+/// Free writing without a target text; only volume and time are tracked.
+#[derive(Default)]
+pub struct Zen {
+    pub text: String,
+    start: Option<Instant>,
+    /// Characters typed, deleted ones included.
+    pub keystrokes: usize,
+    /// Characters deleted with Backspace.
+    pub corrections: usize,
+    /// Writing time, set once finished.
+    finished: Option<Duration>,
+}
+
+impl Zen {
+    pub fn push(&mut self, ch: char) {
+        self.start.get_or_insert_with(Instant::now);
+        self.text.push(ch);
+        self.keystrokes += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.text.pop().is_some() {
+            self.corrections += 1;
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.start.is_some()
+    }
+
+    /// Stops the timer for the results.
+    pub fn finish(&mut self) {
+        self.finished = Some(self.elapsed());
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// Speed of every key typed, five characters to a word. There is no
+    /// target, so there is no accuracy or net speed.
+    pub fn raw_wpm(&self) -> f32 {
+        wpm(self.keystrokes, self.elapsed())
+    }
+
+    pub fn words(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+
+    pub fn chars(&self) -> usize {
+        self.text.chars().filter(|ch| *ch != '\n').count()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.finished
+            .or_else(|| self.start.map(|start| start.elapsed()))
+            .unwrap_or_default()
+    }
+
+    /// Rows needed to show the text wrapped at `width` columns.
+    pub fn rows(&self, width: u16) -> u16 {
+        let width = width.max(1) as usize;
+        self.text
+            .split('\n')
+            .map(|line| line.chars().count().div_ceil(width).max(1))
+            .sum::<usize>() as u16
+    }
+}